@@ -0,0 +1,148 @@
+use std::str::FromStr;
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use fastcrypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    secp256k1::{Secp256k1PublicKey, Secp256k1Signature},
+    secp256r1::{Secp256r1PublicKey, Secp256r1Signature},
+    traits::{ToFromBytes, VerifyingKey},
+};
+use sui_sdk::types::{
+    base_types::SuiAddress,
+    crypto::{PublicKey, Signature, SignatureScheme, SuiSignature},
+};
+use thiserror::Error;
+
+/// The intent prefix of personal messages: scope `PersonalMessage`, version `V0`, app `Sui`
+const PERSONAL_MESSAGE_INTENT: [u8; 3] = [3, 0, 0];
+
+/// Verifies a Sui personal-message signature, as produced by `sui keytool sign-personal-message`
+/// or a wallet's `signPersonalMessage`, over `message` by `address`.
+///
+/// The signed digest is the Blake2b-256 hash of the personal message intent followed by the
+/// BCS encoding of the message. Only single-key signatures (Ed25519, Secp256k1 and Secp256r1)
+/// are supported.
+///
+/// # Arguments
+/// * `base64_signature` - The Sui signature (flag, signature and public key), base64 encoded
+/// * `message` - The message expected to be signed
+/// * `address` - The address expected to have signed it
+///
+/// # Returns
+/// * `Result<(), AuthError>` - `Ok(())` if the signature is valid, or an error if it cannot be
+///   parsed, its public key is not the address' or it does not match the message
+pub fn verify_personal_message_signature(
+    base64_signature: &str,
+    message: &[u8],
+    address: SuiAddress,
+) -> Result<(), AuthError> {
+    let signature =
+        Signature::from_str(base64_signature).map_err(|_| AuthError::MalformedSignature)?;
+    let scheme = signature.scheme();
+    let public_key = PublicKey::try_from_bytes(scheme, signature.public_key_bytes())
+        .map_err(|_| AuthError::MalformedSignature)?;
+    if SuiAddress::from(&public_key) != address {
+        return Err(AuthError::SignerMismatch(address));
+    }
+
+    let mut intent_message = PERSONAL_MESSAGE_INTENT.to_vec();
+    intent_message.extend(bcs::to_bytes(message).map_err(|_| AuthError::MalformedSignature)?);
+    let digest = Blake2b::<U32>::digest(&intent_message);
+
+    let signature_bytes = signature.signature_bytes();
+    let verified =
+        match scheme {
+            SignatureScheme::ED25519 => {
+                Ed25519PublicKey::from_bytes(public_key.as_ref()).and_then(|public_key| {
+                    public_key.verify(&digest, &Ed25519Signature::from_bytes(signature_bytes)?)
+                })
+            }
+            SignatureScheme::Secp256k1 => Secp256k1PublicKey::from_bytes(public_key.as_ref())
+                .and_then(|public_key| {
+                    public_key.verify(&digest, &Secp256k1Signature::from_bytes(signature_bytes)?)
+                }),
+            SignatureScheme::Secp256r1 => Secp256r1PublicKey::from_bytes(public_key.as_ref())
+                .and_then(|public_key| {
+                    public_key.verify(&digest, &Secp256r1Signature::from_bytes(signature_bytes)?)
+                }),
+            scheme => return Err(AuthError::UnsupportedScheme(scheme)),
+        };
+    verified.map_err(|_| AuthError::InvalidSignature)
+}
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Malformed signature")]
+    MalformedSignature,
+    #[error("Signature is not from {0}")]
+    SignerMismatch(SuiAddress),
+    #[error("Unsupported signature scheme {0:?}")]
+    UnsupportedScheme(SignatureScheme),
+    #[error("Invalid signature")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+    use sui_sdk::types::crypto::{get_key_pair, AccountKeyPair, EncodeDecodeBase64, SuiKeyPair};
+
+    use super::*;
+
+    fn new_key_pair() -> (SuiAddress, SuiKeyPair) {
+        let key_pair = SuiKeyPair::Ed25519(get_key_pair::<AccountKeyPair>().1);
+        (SuiAddress::from(&key_pair.public()), key_pair)
+    }
+
+    fn sign(key_pair: &SuiKeyPair, message: &[u8]) -> String {
+        let message = IntentMessage::new(
+            Intent::personal_message(),
+            PersonalMessage {
+                message: message.to_vec(),
+            },
+        );
+        Signature::new_secure(&message, key_pair).encode_base64()
+    }
+
+    #[test]
+    fn signature_of_the_message_by_the_address_is_valid() {
+        let (address, key_pair) = new_key_pair();
+        let signature = sign(&key_pair, b"1700000000:nonce:/status:");
+        assert!(verify_personal_message_signature(
+            &signature,
+            b"1700000000:nonce:/status:",
+            address
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn signature_by_another_address_is_refused() {
+        let (address, _) = new_key_pair();
+        let (_, other_key_pair) = new_key_pair();
+        let signature = sign(&other_key_pair, b"message");
+        assert!(matches!(
+            verify_personal_message_signature(&signature, b"message", address),
+            Err(AuthError::SignerMismatch(signer)) if signer == address
+        ));
+    }
+
+    #[test]
+    fn signature_of_another_message_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        let signature = sign(&key_pair, b"message");
+        assert!(matches!(
+            verify_personal_message_signature(&signature, b"tampered", address),
+            Err(AuthError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn malformed_signature_is_refused() {
+        let (address, _) = new_key_pair();
+        assert!(matches!(
+            verify_personal_message_signature("not base64!", b"message", address),
+            Err(AuthError::MalformedSignature)
+        ));
+    }
+}
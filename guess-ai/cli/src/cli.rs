@@ -1,30 +1,175 @@
-use anyhow::Result;
-use sui_sdk::{json::SuiJsonValue, types::base_types::ObjectID, wallet_context::WalletContext};
-use tracing::{info, instrument};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use guess_ai::{
+    audit::{AuditEntry, AuditLog, AuditOutcome, AuditQuery},
+    client::{parse_move_abort, GuessAiAbortCode},
+    contract_bindings as bindings,
+    network::{Network, NetworkGuard},
+};
+use guess_ai_common::game_state::{read_game_state, GameState};
+use serde::Serialize;
+use serde_json::{json, Value};
+use shared_crypto::intent::Intent;
+use sui_keys::keystore::AccountKeystore;
+use sui_sdk::{
+    rpc_types::{
+        EventFilter, SuiExecutionStatus, SuiObjectDataOptions, SuiTransactionBlockEffects,
+        SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponseOptions,
+        SuiTransactionBlockResponseQuery, TransactionFilter,
+    },
+    types::{
+        base_types::{ObjectID, SuiAddress},
+        crypto::Signature,
+        multisig::MultiSigPublicKey,
+        object::Owner,
+        transaction::{ObjectArg, Transaction, TransactionData},
+        Identifier,
+    },
+    wallet_context::WalletContext,
+    SuiClient,
+};
+use thiserror::Error;
+use tracing::{error, info, instrument};
+
+use crate::{
+    multisig::{
+        combine_signatures, encode_transaction_data, signer, CallArgument, MoveCall,
+        MultisigProposal,
+    },
+    simulator::HistoricalGuess,
+};
+
+/// The gas budget of the admin transactions
+const GAS_BUDGET: u64 = 50_000_000; // 0.05 SUI
 
-/// The Atoma contract db module name.
-const GUESS_AI_MODULE_NAME: &str = "guess_ai";
+/// The event emitted for every guess
+const NEW_GUESS_EVENT: &str = "NewGuessEvent";
 
-/// The gas budget for the node registration transaction
-const GAS_BUDGET: u64 = 50_000_000; // 0.05 SUI
+/// The outcome of an admin transaction
+#[derive(Debug, Serialize)]
+pub struct TransactionOutput {
+    /// The digest of the transaction
+    pub digest: String,
 
-/// Set the fee rate increase per guess per milli
-const SET_FEE_RATE_INCREASE_COMMAND: &str = "set_fee_rate_increase_per_guess_per_mille";
+    /// Whether the transaction was only dry-run, and not executed
+    pub dry_run: bool,
 
-/// Set the game inactive
-const SET_GAME_INACTIVE_COMMAND: &str = "set_game_inactive";
+    /// The effects of the transaction, or of its dry run
+    pub effects: Option<SuiTransactionBlockEffects>,
 
-/// Set the agent address
-const SET_AGENT_ADDRESS_COMMAND: &str = "set_agent_address";
+    /// The unsigned transaction, base64 encoded, if it awaits the signatures of the multisig
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_bytes: Option<String>,
+}
 
-/// Set the starting fee for the game
-const SET_STARTING_FEE_COMMAND: &str = "set_starting_fee";
+/// A privileged call to the GuessAI game contract
+#[derive(Clone, Debug, Subcommand)]
+pub enum AdminAction {
+    /// Sets the fee increase applied every fee update, in per mille
+    SetFeeRateIncrease {
+        fee_rate_increase_per_guess_per_mille: u64,
+    },
+    /// Sets the fee of the first guess of a game, in MIST
+    SetStartingFee { starting_fee: u64 },
+    /// Sets the number of guesses between fee updates
+    SetUpdateFeeEveryNGuesses { update_fee_every_n_guesses: u64 },
+    /// Sets the share of each fee kept by the protocol, in per mille
+    #[command(name = "set-protocol-fee")]
+    SetProtocolFeePerMille { protocol_fee_per_mille: u64 },
+    /// Sets the address of the agent allowed to withdraw funds and submit attestations
+    SetAgentAddress { agent_address: String },
+    /// Sets the game inactive, so that it stops accepting guesses
+    SetGameInactive,
+}
 
-/// Set the fee update interval in guesses
-const SET_UPDATE_FEE_EVERY_N_GUESSES_COMMAND: &str = "set_update_fee_every_n_guesses";
+impl AdminAction {
+    /// The name of the action, as recorded in the audit log
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SetFeeRateIncrease { .. } => {
+                bindings::SetFeeRateIncreasePerGuessPerMille::FUNCTION_NAME
+            }
+            Self::SetStartingFee { .. } => bindings::SetStartingFee::FUNCTION_NAME,
+            Self::SetUpdateFeeEveryNGuesses { .. } => {
+                bindings::SetUpdateFeeEveryNGuesses::FUNCTION_NAME
+            }
+            Self::SetProtocolFeePerMille { .. } => bindings::SetProtocolFeePerMille::FUNCTION_NAME,
+            Self::SetAgentAddress { .. } => bindings::SetAgentAddress::FUNCTION_NAME,
+            Self::SetGameInactive => bindings::SetGameInactive::FUNCTION_NAME,
+        }
+    }
 
-/// Set the protocol fee per mille
-const SET_PROTOCOL_FEE_PER_MILLE_COMMAND: &str = "set_protocol_fee_per_mille";
+    /// The parameters of the action, as recorded in the audit log
+    pub fn parameters(&self) -> Value {
+        match self {
+            Self::SetFeeRateIncrease {
+                fee_rate_increase_per_guess_per_mille,
+            } => {
+                json!({ "fee_rate_increase_per_guess_per_mille": fee_rate_increase_per_guess_per_mille })
+            }
+            Self::SetStartingFee { starting_fee } => json!({ "starting_fee": starting_fee }),
+            Self::SetUpdateFeeEveryNGuesses {
+                update_fee_every_n_guesses,
+            } => json!({ "update_fee_every_n_guesses": update_fee_every_n_guesses }),
+            Self::SetProtocolFeePerMille {
+                protocol_fee_per_mille,
+            } => json!({ "protocol_fee_per_mille": protocol_fee_per_mille }),
+            Self::SetAgentAddress { agent_address } => json!({ "agent_address": agent_address }),
+            Self::SetGameInactive => json!({}),
+        }
+    }
+
+    /// Decodes the action of a call to the GuessAI module, from its arguments after the db
+    /// and manager objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the function is not an admin action, or the arguments do not match
+    /// its parameters
+    fn from_call_arguments(function: &str, arguments: &[CallArgument]) -> Result<Self> {
+        let action = match (function, arguments) {
+            (bindings::SetGameInactive::FUNCTION_NAME, []) => Self::SetGameInactive,
+            (bindings::SetAgentAddress::FUNCTION_NAME, [CallArgument::Pure(bytes)]) => {
+                Self::SetAgentAddress {
+                    agent_address: bcs::from_bytes::<SuiAddress>(bytes)?.to_string(),
+                }
+            }
+            (function, [CallArgument::Pure(bytes)]) => {
+                let value = bcs::from_bytes::<u64>(bytes)?;
+                match function {
+                    bindings::SetFeeRateIncreasePerGuessPerMille::FUNCTION_NAME => {
+                        Self::SetFeeRateIncrease {
+                            fee_rate_increase_per_guess_per_mille: value,
+                        }
+                    }
+                    bindings::SetStartingFee::FUNCTION_NAME => Self::SetStartingFee {
+                        starting_fee: value,
+                    },
+                    bindings::SetUpdateFeeEveryNGuesses::FUNCTION_NAME => {
+                        Self::SetUpdateFeeEveryNGuesses {
+                            update_fee_every_n_guesses: value,
+                        }
+                    }
+                    bindings::SetProtocolFeePerMille::FUNCTION_NAME => {
+                        Self::SetProtocolFeePerMille {
+                            protocol_fee_per_mille: value,
+                        }
+                    }
+                    _ => return Err(anyhow!("`{function}` is not an admin action")),
+                }
+            }
+            _ => {
+                return Err(anyhow!(
+                    "`{function}` is not an admin action taking {} arguments",
+                    arguments.len()
+                ))
+            }
+        };
+        Ok(action)
+    }
+}
 
 /// The context for the Sui client to interact with the
 /// GuessAI game smart contract, on the Sui blockchain.
@@ -40,6 +185,19 @@ pub struct SuiClientContext {
 
     /// The wallet context for the current Sui client
     wallet_context: WalletContext,
+
+    /// Whether transactions are only dry-run, and not executed
+    dry_run: bool,
+
+    /// The audit log the admin actions are recorded in, if any
+    audit_log: Option<AuditLog>,
+
+    /// The multisig the admin transactions are sent from, if any. Transactions are then
+    /// returned unsigned, to be signed by its members, instead of executed
+    multisig: Option<MultiSigPublicKey>,
+
+    /// Guards the admin transactions on mainnet, see [`SuiClientContext::verify_network`]
+    network_guard: NetworkGuard,
 }
 
 impl SuiClientContext {
@@ -55,400 +213,581 @@ impl SuiClientContext {
             guess_ai_manager_id,
             guess_ai_package_id,
             wallet_context,
+            dry_run: false,
+            audit_log: None,
+            multisig: None,
+            network_guard: NetworkGuard::default(),
         }
     }
 
-    /// Sets the fee rate increase per guess per mille.
+    /// Only dry-runs the transactions instead of executing them, to preview their effects
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Records the admin actions executed through `execute_admin_action` in `audit_log`
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Sends the admin transactions from the multisig address, returning them unsigned so
+    /// that no single key can execute them. See `submit_multisig_proposal`
+    pub fn with_multisig(mut self, multisig: MultiSigPublicKey) -> Self {
+        self.multisig = Some(multisig);
+        self
+    }
+
+    /// Verifies that the wallet is connected to the selected network, from the chain
+    /// identifier of its RPC endpoint. Admin transactions are refused until the network is
+    /// verified, and on mainnet unless `allow_mainnet` is set. Dry runs and multisig
+    /// proposals, which change nothing on-chain, are always allowed.
     ///
     /// # Arguments
-    /// * `fee_rate_increase_per_guess_per_mille` - The fee rate increase per guess per mille
-    /// * `gas` - Optional gas object ID to use for the transaction
-    /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
+    /// * `selected` - The network selected with `--network`, if any
+    /// * `allow_mainnet` - Whether the admin transactions may be executed on mainnet
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    #[instrument(
-        level = "info",
-        name = "set_fee_rate_increase_per_guess_per_mille",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_fee_rate_increase_per_guess_per_mille(
+    /// * `Result<NetworkGuard>` - The guard of the verified network, or an error if the chain
+    ///   identifier cannot be fetched or the wallet is connected to another network
+    #[instrument(level = "info", skip(self))]
+    pub async fn verify_network(
         &mut self,
-        fee_rate_increase_per_guess_per_mille: u64,
-        gas: Option<ObjectID>,
-        gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
+        selected: Option<Network>,
+        allow_mainnet: bool,
+    ) -> Result<NetworkGuard> {
         let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_FEE_RATE_INCREASE_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                    SuiJsonValue::new(fee_rate_increase_per_guess_per_mille.to_string().into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
-
-        info!(
-            target = "sui-client-set-fee-rate-increase-per-guess-per-mille",
-            tx_hash = %tx.digest(),
-            "Set fee rate increase per guess per mille"
-        );
+        let chain_identifier = client.read_api().get_chain_identifier().await?;
+        self.network_guard = NetworkGuard::verify(selected, &chain_identifier, allow_mainnet)?;
+        Ok(self.network_guard)
+    }
 
-        let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
+    /// The active address of the wallet, which signs the transactions
+    pub fn active_address(&mut self) -> Result<SuiAddress> {
+        self.wallet_context.active_address()
+    }
 
-        Ok(response.digest.to_string())
+    /// The address the admin transactions are sent from: the multisig address if any,
+    /// otherwise the active address of the wallet
+    fn sender(&mut self) -> Result<SuiAddress> {
+        match &self.multisig {
+            Some(multisig) => Ok(SuiAddress::from(multisig)),
+            None => self.wallet_context.active_address(),
+        }
     }
 
-    /// Sets the game inactive.
+    /// Executes an admin action, and records it in the audit log (unless in dry-run mode, or
+    /// proposed to a multisig, as nothing changes on-chain).
     ///
     /// # Arguments
-    /// * `gas` - Optional gas object ID to use for the transaction
+    /// * `actor` - Who requested the action, e.g. the address of an admin
+    /// * `action` - The action to execute
     /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let tx_digest = client.set_game_inactive(None, None, None).await?;
-    /// ```
-    #[instrument(
-        level = "info",
-        name = "set_game_inactive",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_game_inactive(
+    /// * `Result<TransactionOutput>` - The digest and effects of the transaction, or an error if
+    ///   the transaction is refused on this network (see `verify_network`) or fails. A failure
+    ///   to record it in the audit log is logged, as the transaction was already sent
+    #[instrument(level = "info", skip(self), fields(action = action.name()))]
+    pub async fn execute_admin_action(
         &mut self,
-        gas: Option<ObjectID>,
+        actor: &str,
+        action: &AdminAction,
         gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
-        let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
+    ) -> Result<TransactionOutput> {
+        let allowed = if self.dry_run || self.multisig.is_some() {
+            Ok(())
+        } else {
+            self.network_guard.check(action.name())
+        };
+        // A refused action is recorded like a failed one
+        let result = match allowed {
+            Ok(()) => self.send_admin_action(action, gas_budget).await,
+            Err(e) => Err(e.into()),
+        };
+        if self.dry_run || self.multisig.is_some() {
+            return result;
+        }
+        self.record(actor, action.name(), action.parameters(), &result);
+        result
+    }
 
-        let tx = client
+    /// Builds the transaction of an admin action with the generated bindings of the contract,
+    /// and executes it, see `execute`
+    #[instrument(level = "info", skip_all, fields(action = action.name()))]
+    async fn send_admin_action(
+        &mut self,
+        action: &AdminAction,
+        gas_budget: Option<u64>,
+    ) -> Result<TransactionOutput> {
+        let client = self.wallet_context.get_client().await?;
+        let sender = self.sender()?;
+        let guess_ai_db = object_arg(&client, self.guess_ai_db).await?;
+        let guess_ai_manager = object_arg(&client, self.guess_ai_manager_id).await?;
+        let package_id = self.guess_ai_package_id;
+
+        let ptb = match action.clone() {
+            AdminAction::SetFeeRateIncrease {
+                fee_rate_increase_per_guess_per_mille,
+            } => bindings::SetFeeRateIncreasePerGuessPerMille {
+                guess_ai_db,
+                guess_ai_manager,
+                arg2: fee_rate_increase_per_guess_per_mille,
+            }
+            .build(package_id)?,
+            AdminAction::SetStartingFee { starting_fee } => bindings::SetStartingFee {
+                guess_ai_db,
+                guess_ai_manager,
+                arg2: starting_fee,
+            }
+            .build(package_id)?,
+            AdminAction::SetUpdateFeeEveryNGuesses {
+                update_fee_every_n_guesses,
+            } => bindings::SetUpdateFeeEveryNGuesses {
+                guess_ai_db,
+                guess_ai_manager,
+                arg2: update_fee_every_n_guesses,
+            }
+            .build(package_id)?,
+            AdminAction::SetProtocolFeePerMille {
+                protocol_fee_per_mille,
+            } => bindings::SetProtocolFeePerMille {
+                guess_ai_db,
+                guess_ai_manager,
+                arg2: protocol_fee_per_mille,
+            }
+            .build(package_id)?,
+            AdminAction::SetAgentAddress { agent_address } => bindings::SetAgentAddress {
+                guess_ai_db,
+                guess_ai_manager,
+                arg2: SuiAddress::from_str(&agent_address)?,
+            }
+            .build(package_id)?,
+            AdminAction::SetGameInactive => bindings::SetGameInactive {
+                guess_ai_db,
+                guess_ai_manager,
+            }
+            .build(package_id)?,
+        };
+
+        let gas_budget = gas_budget.unwrap_or(GAS_BUDGET);
+        let gas_price = client.read_api().get_reference_gas_price().await?;
+        let gas = client
             .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_GAME_INACTIVE_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
+            .select_gas(
+                sender,
+                None,
+                gas_budget,
+                vec![self.guess_ai_db, self.guess_ai_manager_id],
                 gas_price,
             )
             .await?;
+        let tx = TransactionData::new_programmable(sender, vec![gas], ptb, gas_budget, gas_price);
 
         info!(
-            target = "sui-client-set-game-inactive",
+            target = "sui-client-admin-action",
+            action = action.name(),
             tx_hash = %tx.digest(),
-            "Set game inactive"
+            "Built admin transaction"
         );
 
-        let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
-
-        Ok(response.digest.to_string())
+        self.execute(&client, tx).await
     }
 
-    /// Sets the agent address for the GuessAI game.
+    /// Decodes the admin action of a multisig proposal from its transaction, which is what the
+    /// members sign, rather than trusting the proposal's description of it.
     ///
     /// # Arguments
-    /// * `agent_address` - The address of the agent
-    /// * `gas` - Optional gas object ID to use for the transaction
-    /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
+    /// * `proposal` - The proposal to decode
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let tx_digest = client.set_agent_address(agent_address, None, None, None).await?;
-    /// ```
-    #[instrument(
-        level = "info",
-        name = "set_agent_address",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_agent_address(
-        &mut self,
-        agent_address: String,
-        gas: Option<ObjectID>,
-        gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
-        let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
+    /// * `Result<(TransactionData, MoveCall, AdminAction)>` - The transaction, its Move call and
+    ///   the admin action it makes, or an error if the transaction doesn't match the proposal's
+    ///   digest and sender, or is not a single admin call to the configured GuessAI objects
+    pub fn verify_multisig_proposal(
+        &self,
+        proposal: &MultisigProposal,
+    ) -> Result<(TransactionData, MoveCall, AdminAction)> {
+        let tx = proposal.verified_transaction_data()?;
+        let call = MoveCall::decode(&tx)?;
+        if call.package != self.guess_ai_package_id || call.module != bindings::MODULE_NAME {
+            return Err(anyhow!(
+                "The proposal calls {}::{}, not the GuessAI module {}::{}",
+                call.package,
+                call.module,
                 self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_AGENT_ADDRESS_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                    SuiJsonValue::new(agent_address.into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
-
-        info!(
-            target = "sui-client-set-agent-address",
-            tx_hash = %tx.digest(),
-            "Set agent address"
-        );
-
-        let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
-
-        Ok(response.digest.to_string())
+                bindings::MODULE_NAME
+            ));
+        }
+        let action = match call.arguments.as_slice() {
+            [CallArgument::Object(db), CallArgument::Object(manager), arguments @ ..]
+                if *db == self.guess_ai_db && *manager == self.guess_ai_manager_id =>
+            {
+                AdminAction::from_call_arguments(&call.function, arguments)?
+            }
+            _ => {
+                return Err(anyhow!(
+                    "The proposal is not called on the GuessAI db {} and manager {}",
+                    self.guess_ai_db,
+                    self.guess_ai_manager_id
+                ))
+            }
+        };
+        if action.name() != proposal.action {
+            return Err(anyhow!(
+                "The proposal is described as `{}`, but its transaction calls `{}`",
+                proposal.action,
+                action.name()
+            ));
+        }
+        Ok((tx, call, action))
     }
 
-    /// Sets the starting fee for the GuessAI game.     
+    /// Signs the transaction of a multisig proposal with a key of the wallet's keystore, once
+    /// verified (see `verify_multisig_proposal`).
     ///
     /// # Arguments
-    /// * `starting_fee` - The starting fee for the game
-    /// * `gas` - Optional gas object ID to use for the transaction
-    /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
+    /// * `proposal` - The proposal to sign
+    /// * `address` - The address of the key, the active address if unset
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let tx_digest = client.set_starting_fee(100, None, None, None).await?;
-    /// ```
-    #[instrument(
-        level = "info",
-        name = "set_starting_fee",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_starting_fee(
+    /// * `Result<Signature>` - The partial signature, or an error if the proposal cannot be
+    ///   verified or the keystore has no such key
+    #[instrument(level = "info", skip(self, proposal), fields(digest = %proposal.digest))]
+    pub fn sign_multisig_proposal(
         &mut self,
-        starting_fee: u64,
-        gas: Option<ObjectID>,
-        gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
-        let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_STARTING_FEE_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                    SuiJsonValue::new(starting_fee.to_string().into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
-
+        proposal: &MultisigProposal,
+        address: Option<SuiAddress>,
+    ) -> Result<Signature> {
+        let address = match address {
+            Some(address) => address,
+            None => self.wallet_context.active_address()?,
+        };
+        let (tx, _, _) = self.verify_multisig_proposal(proposal)?;
+        let signature = self.wallet_context.config.keystore.sign_secure(
+            &address,
+            &tx,
+            Intent::sui_transaction(),
+        )?;
         info!(
-            target = "sui-client-set-starting-fee",
-            tx_hash = %tx.digest(),
-            "Set starting fee"
+            target = "sui-client-multisig",
+            event = "multisig-proposal-signed",
+            digest = %proposal.digest,
+            signer = %address,
+            "Signed multisig proposal"
         );
-
-        let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
-
-        Ok(response.digest.to_string())
+        Ok(signature)
     }
 
-    /// Sets the fee update interval in guesses for the GuessAI game.
+    /// Combines the partial signatures of the multisig members, and executes the transaction of
+    /// the proposal (or dry-runs it, in dry-run mode). The execution is recorded in the audit log,
+    /// with the signers as actor, and the action decoded from the transaction.
     ///
     /// # Arguments
-    /// * `update_fee_every_n_guesses` - The number of guesses between fee updates
-    /// * `gas` - Optional gas object ID to use for the transaction
-    /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
+    /// * `proposal` - The proposal to execute
+    /// * `signatures` - The partial signatures of the members
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let tx_digest = client.set_update_fee_every_n_guesses(100, None, None, None).await?;
-    /// ```
-    #[instrument(
-        level = "info",
-        name = "set_update_fee_every_n_guesses",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_update_fee_every_n_guesses(
+    /// * `Result<TransactionOutput>` - The digest and effects of the transaction, or an error if
+    ///   no multisig is configured, the proposal is not from it or cannot be verified (see
+    ///   `verify_multisig_proposal`), the signatures do not reach its threshold, or the transaction is refused on this network (see `verify_network`) or fails
+    #[instrument(level = "info", skip_all, fields(digest = %proposal.digest, action = %proposal.action))]
+    pub async fn submit_multisig_proposal(
         &mut self,
-        update_fee_every_n_guesses: u64,
-        gas: Option<ObjectID>,
-        gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
+        proposal: &MultisigProposal,
+        signatures: Vec<Signature>,
+    ) -> Result<TransactionOutput> {
+        let multisig = self
+            .multisig
+            .clone()
+            .ok_or_else(|| anyhow!("No multisig is configured"))?;
+        let multisig_address = SuiAddress::from(&multisig);
+        if proposal.sender != multisig_address {
+            return Err(anyhow!(
+                "The proposal is sent from {}, not from the multisig address {multisig_address}",
+                proposal.sender
+            ));
+        }
+        let (tx, _, action) = self.verify_multisig_proposal(proposal)?;
         let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_UPDATE_FEE_EVERY_N_GUESSES_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                    SuiJsonValue::new(update_fee_every_n_guesses.to_string().into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
+        if self.dry_run {
+            return self.dry_run(&client, tx).await;
+        }
 
-        info!(
-            target = "sui-client-set-update-fee-every-n-guesses",
-            tx_hash = %tx.digest(),
-            "Set update fee every n guesses"
+        let signers = signatures
+            .iter()
+            .map(signer)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let signature = combine_signatures(&multisig, signatures)?;
+        let result = match self.network_guard.check(action.name()) {
+            Ok(()) => {
+                self.execute_signed(Transaction::from_generic_sig_data(tx, vec![signature]))
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        let actor = format!(
+            "multisig:{multisig_address}({})",
+            signers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
         );
+        self.record(&actor, action.name(), action.parameters(), &result);
+        result
+    }
 
-        let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
+    /// Records the outcome of an admin transaction in the audit log, if any.
+    ///
+    /// A failure to record it is logged rather than returned: the transaction was already
+    /// sent, and its outcome must still reach the caller.
+    fn record(
+        &mut self,
+        actor: &str,
+        action: &str,
+        parameters: Value,
+        result: &Result<TransactionOutput>,
+    ) {
+        if let Some(audit_log) = self.audit_log.as_mut() {
+            let tx_digest = match result {
+                Ok(output) => Some(output.digest.clone()),
+                Err(e) => match e.downcast_ref::<AdminCallError>() {
+                    Some(
+                        AdminCallError::MoveAbort { digest, .. }
+                        | AdminCallError::ExecutionFailed { digest, .. },
+                    ) => Some(digest.clone()),
+                    None => None,
+                },
+            };
+            if let Err(e) = audit_log.append(
+                actor,
+                action,
+                parameters,
+                tx_digest,
+                AuditOutcome::of(result),
+            ) {
+                error!(
+                    target = "sui-client-admin-action",
+                    event = "audit-log-error",
+                    action,
+                    "Failed to record admin action in the audit log: {e}"
+                );
+            }
+        }
+    }
 
-        Ok(response.digest.to_string())
+    /// Returns the entries of the audit log passing the query filter
+    pub fn query_audit_log(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        match &self.audit_log {
+            Some(audit_log) => Ok(audit_log.query(query)?),
+            None => Ok(Vec::new()),
+        }
     }
 
-    /// Sets the protocol fee rate in per mille (parts per thousand) for the GuessAI game.
+    /// Reads the state of the game from the GuessAI db object.
+    ///
+    /// # Returns
+    /// * `Result<GameState>` - The decoded state of the game, or an error if the object cannot be fetched or decoded
+    #[instrument(level = "info", name = "game_state", skip_all)]
+    pub async fn game_state(&self) -> Result<GameState> {
+        let client = self.wallet_context.get_client().await?;
+        Ok(read_game_state(&client, self.guess_ai_db).await?)
+    }
+
+    /// Reads the guesses made on-chain, from the `NewGuessEvent`s of the GuessAI module.
     ///
     /// # Arguments
-    /// * `protocol_fee_per_mille` - The protocol fee rate in per mille (e.g., 50 = 5%)
-    /// * `gas` - Optional gas object ID to use for the transaction
-    /// * `gas_budget` - Optional gas budget for the transaction (defaults to GAS_BUDGET)
-    /// * `gas_price` - Optional gas price for the transaction
+    /// * `limit` - The maximum number of guesses to read, the oldest first (all if unset)
     ///
     /// # Returns
-    /// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-    ///
-    /// # Example
-    /// ```rust,ignore
-    /// let tx_digest = client.set_protocol_fee_per_mille(50, None, None, None).await?;
-    /// ```
-    #[instrument(
-        level = "info",
-        name = "set_protocol_fee_per_mille",
-        skip_all,
-        fields(
-            active_address = %self.wallet_context.active_address()?,
-        )
-    )]
-    pub async fn set_protocol_fee_per_mille(
-        &mut self,
-        protocol_fee_per_mille: u64,
-        gas: Option<ObjectID>,
-        gas_budget: Option<u64>,
-        gas_price: Option<u64>,
-    ) -> Result<String> {
+    /// * `Result<Vec<HistoricalGuess>>` - The guesses, the oldest first, or an error if the events
+    ///   cannot be fetched or decoded
+    #[instrument(level = "info", name = "guess_history", skip(self))]
+    pub async fn guess_history(&self, limit: Option<usize>) -> Result<Vec<HistoricalGuess>> {
         let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                SET_PROTOCOL_FEE_PER_MILLE_COMMAND,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(self.guess_ai_manager_id),
-                    SuiJsonValue::new(protocol_fee_per_mille.to_string().into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
+        let filter = EventFilter::MoveModule {
+            package: self.guess_ai_package_id,
+            module: Identifier::new(bindings::MODULE_NAME)?,
+        };
+        let mut history = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client
+                .event_api()
+                .query_events(filter.clone(), cursor, None, false)
+                .await?;
+            for event in page.data {
+                if event.type_.name.as_str() != NEW_GUESS_EVENT {
+                    continue;
+                }
+                let mut guess: HistoricalGuess = serde_json::from_value(event.parsed_json)?;
+                guess.timestamp_ms = event.timestamp_ms;
+                history.push(guess);
+                if limit.is_some_and(|limit| history.len() >= limit) {
+                    return Ok(history);
+                }
+            }
+            if !page.has_next_page {
+                return Ok(history);
+            }
+            cursor = page.next_cursor;
+        }
+    }
 
-        info!(
-            target = "sui-client-set-protocol-fee-per-mille",
-            tx_hash = %tx.digest(),
-            "Set protocol fee per mille"
+    /// Reads when the treasury pool was paid out, from the successful
+    /// `withdraw_funds_from_treasury_pool` transactions of the GuessAI package.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u64>>` - The times of the payouts, in milliseconds since the Unix epoch,
+    ///   the oldest first, or an error if the transactions cannot be fetched
+    #[instrument(level = "info", name = "payout_history", skip(self))]
+    pub async fn payout_history(&self) -> Result<Vec<u64>> {
+        let client = self.wallet_context.get_client().await?;
+        let query = SuiTransactionBlockResponseQuery::new(
+            Some(TransactionFilter::MoveFunction {
+                package: self.guess_ai_package_id,
+                module: Some(bindings::MODULE_NAME.to_string()),
+                function: Some(bindings::WithdrawFundsFromTreasuryPool::FUNCTION_NAME.to_string()),
+            }),
+            Some(SuiTransactionBlockResponseOptions::new().with_effects()),
         );
+        let mut payouts = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client
+                .read_api()
+                .query_transaction_blocks(query.clone(), cursor, None, false)
+                .await?;
+            payouts.extend(
+                page.data
+                    .into_iter()
+                    .filter(|response| {
+                        response.effects.as_ref().is_some_and(|effects| {
+                            matches!(effects.status(), SuiExecutionStatus::Success)
+                        })
+                    })
+                    .filter_map(|response| response.timestamp_ms),
+            );
+            if !page.has_next_page {
+                return Ok(payouts);
+            }
+            cursor = page.next_cursor;
+        }
+    }
 
+    /// Executes a transaction signed by the active address of the wallet.
+    ///
+    /// In dry-run mode, the transaction is only dry-run. If a multisig is configured, the
+    /// transaction is returned unsigned instead, to be signed by its members.
+    ///
+    /// # Returns
+    /// * `Result<TransactionOutput>` - The digest and effects of the transaction, or an error if
+    ///   the transaction cannot be executed or its execution fails
+    async fn execute(&self, client: &SuiClient, tx: TransactionData) -> Result<TransactionOutput> {
+        if self.dry_run {
+            return self.dry_run(client, tx).await;
+        }
+        if self.multisig.is_some() {
+            return Ok(TransactionOutput {
+                digest: tx.digest().to_string(),
+                dry_run: false,
+                effects: None,
+                tx_bytes: Some(encode_transaction_data(&tx)?),
+            });
+        }
         let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
+        self.execute_signed(tx).await
+    }
+
+    /// Dry-runs a transaction, failing if its execution would
+    async fn dry_run(&self, client: &SuiClient, tx: TransactionData) -> Result<TransactionOutput> {
+        let digest = tx.digest().to_string();
+        let response = client.read_api().dry_run_transaction_block(tx).await?;
+        if let SuiExecutionStatus::Failure { error } = response.effects.status() {
+            return Err(AdminCallError::from_execution_failure(digest, error).into());
+        }
+        Ok(TransactionOutput {
+            digest,
+            dry_run: true,
+            effects: Some(response.effects),
+            tx_bytes: None,
+        })
+    }
 
-        Ok(response.digest.to_string())
+    /// Executes a signed transaction, failing if its execution does
+    async fn execute_signed(&self, tx: Transaction) -> Result<TransactionOutput> {
+        let response = self.wallet_context.execute_transaction_may_fail(tx).await?;
+        if let Some(SuiExecutionStatus::Failure { error }) =
+            response.effects.as_ref().map(|effects| effects.status())
+        {
+            return Err(
+                AdminCallError::from_execution_failure(response.digest.to_string(), error).into(),
+            );
+        }
+        Ok(TransactionOutput {
+            digest: response.digest.to_string(),
+            dry_run: false,
+            effects: response.effects,
+            tx_bytes: None,
+        })
+    }
+}
+
+/// Returns an object as a transaction argument: a mutable shared object argument if it is
+/// shared, or its current reference otherwise
+async fn object_arg(client: &SuiClient, object_id: ObjectID) -> Result<ObjectArg> {
+    let object = client
+        .read_api()
+        .get_object_with_options(object_id, SuiObjectDataOptions::new().with_owner())
+        .await?;
+    let data = object
+        .data
+        .ok_or_else(|| anyhow!("Object {object_id} not found"))?;
+    Ok(match data.owner {
+        Some(Owner::Shared {
+            initial_shared_version,
+        }) => ObjectArg::SharedObject {
+            id: object_id,
+            initial_shared_version,
+            mutable: true,
+        },
+        _ => ObjectArg::ImmOrOwnedObject(data.object_ref()),
+    })
+}
+
+/// The failure of an admin transaction, on execution or dry run
+#[derive(Debug, Error)]
+pub enum AdminCallError {
+    #[error("Transaction {digest} aborted in module `{module}` with code {code}")]
+    MoveAbort {
+        digest: String,
+        module: String,
+        code: u64,
+        /// The meaning of the abort code, if the GuessAI module aborted
+        reason: Option<GuessAiAbortCode>,
+    },
+    #[error("Transaction {digest} failed: {error}")]
+    ExecutionFailed { digest: String, error: String },
+}
+
+impl AdminCallError {
+    /// Maps the execution error of a transaction to a `MoveAbort` if the contract aborted,
+    /// or an `ExecutionFailed` otherwise
+    fn from_execution_failure(digest: String, error: &str) -> Self {
+        match parse_move_abort(error) {
+            Some((module, code)) => Self::MoveAbort {
+                digest,
+                module: module.to_string(),
+                code,
+                reason: (module == bindings::MODULE_NAME)
+                    .then(|| GuessAiAbortCode::from_code(code)),
+            },
+            None => Self::ExecutionFailed {
+                digest,
+                error: error.to_string(),
+            },
+        }
     }
 }
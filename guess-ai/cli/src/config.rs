@@ -0,0 +1,229 @@
+use std::path::Path;
+
+use config::Config;
+use guess_ai::{
+    config::{ConfigProblem, ConfigValidator},
+    network::{Network, NetworkProfile},
+    secrets::{KeystoreConfig, Secret, SecretProviders},
+};
+use serde::Deserialize;
+use sui_sdk::types::base_types::SuiAddress;
+
+/// Configuration for the GuessAI admin CLI.
+///
+/// It is read from the `guess_ai` section of the engine's configuration file, so that
+/// both binaries share a single file. Only the fields the CLI needs are deserialized.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GuessAiCliConfig {
+    /// Package identifier for the smart contract, taken from the network profile if one is
+    /// selected
+    #[serde(default)]
+    pub guess_ai_package_id: String,
+
+    /// Database identifier for the smart contract, taken from the network profile if one is
+    /// selected
+    #[serde(default)]
+    pub guess_ai_db: String,
+
+    /// Identifier of the GuessAI manager object, authorizing the admin calls, taken from the
+    /// network profile if one is selected
+    #[serde(default)]
+    pub guess_ai_manager_id: String,
+
+    /// Sui's config path
+    pub sui_config_path: String,
+
+    /// Optional timeout duration for requests in seconds
+    pub request_timeout: Option<u64>,
+
+    /// Maximum number of concurrent requests to the Sui RPC node
+    pub max_concurrent_requests: Option<usize>,
+
+    /// File path of the audit log of the admin actions taken through the CLI
+    #[serde(default = "default_cli_audit_log_path")]
+    pub cli_audit_log_path: String,
+
+    /// The multisig administering the game, if any
+    #[serde(default)]
+    pub multisig: Option<MultisigConfig>,
+
+    /// The engine's encrypted keystore, managed with `guess-ai-cli keystore`
+    #[serde(default)]
+    pub keystore: Option<KeystoreConfig>,
+}
+
+/// An n-of-m multisig, whose members must jointly sign the admin transactions
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultisigConfig {
+    /// Public keys of the members, base64 encoded with their scheme flag (as listed by
+    /// `sui keytool list`)
+    pub public_keys: Vec<String>,
+
+    /// Weights of the members, in the order of `public_keys`. Every member weighs 1 if empty
+    #[serde(default)]
+    pub weights: Vec<u8>,
+
+    /// Total weight of the signatures needed to execute a transaction
+    pub threshold: u16,
+}
+
+fn default_cli_audit_log_path() -> String {
+    "./cli_audit_log.jsonl".to_string()
+}
+
+impl GuessAiCliConfig {
+    /// Creates a new `GuessAiCliConfig` instance from a configuration file path.
+    ///
+    /// Like the engine's configuration, values are loaded from the file and from environment
+    /// variables prefixed with `GUESS_AI__`, which take precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - Path to the configuration file
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if:
+    /// * The configuration file cannot be read or parsed
+    /// * The configuration values don't match the expected structure
+    /// * The "guess_ai" section is missing from the configuration
+    pub fn from_file_path<P: AsRef<Path>>(config_file_path: P) -> Self {
+        Self::try_from_file_path(config_file_path)
+            .expect("Failed to generate configuration instance")
+    }
+
+    /// Loads a `GuessAiCliConfig` instance like [`Self::from_file_path`], without validating
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "guess_ai" section doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+    ) -> Result<Self, config::ConfigError> {
+        Config::builder()
+            .add_source(config::File::with_name(
+                &config_file_path.as_ref().to_string_lossy(),
+            ))
+            .add_source(
+                config::Environment::with_prefix("GUESS_AI")
+                    .keep_prefix(true)
+                    .separator("__"),
+            )
+            .build()?
+            .get::<Self>("guess_ai")
+    }
+
+    /// Points the configuration at the deployment of the game on `network`, overriding the
+    /// package ID, the db ID and the manager ID. A profile without a manager ID, needed by
+    /// the admin calls, is recorded in `validator`
+    pub fn apply_network_profile(
+        &mut self,
+        network: Network,
+        profile: &NetworkProfile,
+        validator: &mut ConfigValidator,
+    ) {
+        self.guess_ai_package_id = profile.package_id.to_string();
+        self.guess_ai_db = profile.db_id.to_string();
+        match profile.manager_id {
+            Some(manager_id) => self.guess_ai_manager_id = manager_id.to_string(),
+            None => validator.report(
+                &format!("networks.{network}.manager_id"),
+                ConfigProblem::Missing,
+            ),
+        }
+    }
+
+    /// Checks the configuration values used by the admin calls, recording every problem
+    /// found in `validator`
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.object_id("guess_ai.guess_ai_package_id", &self.guess_ai_package_id);
+        validator.object_id("guess_ai.guess_ai_db", &self.guess_ai_db);
+        validator.object_id("guess_ai.guess_ai_manager_id", &self.guess_ai_manager_id);
+        validator.readable_file("guess_ai.sui_config_path", &self.sui_config_path);
+        validator.writable_location("guess_ai.cli_audit_log_path", &self.cli_audit_log_path);
+        if let Some(multisig) = &self.multisig {
+            validator.non_zero("guess_ai.multisig.threshold", multisig.threshold.into());
+        }
+    }
+}
+
+/// Configuration for the admin HTTP server of the CLI (`guess-ai-cli serve`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct CliServerConfig {
+    /// Bind address of the server
+    pub service_bind_address: String,
+
+    /// Token every request must carry as a bearer token, given inline or read from a secret
+    /// provider
+    pub admin_token: Secret,
+
+    /// Addresses allowed to administer the game. If set, every request must also carry
+    /// a Sui personal-message signature from one of them
+    #[serde(default)]
+    pub admin_addresses: Vec<SuiAddress>,
+}
+
+impl CliServerConfig {
+    /// Creates a new `CliServerConfig` instance from a configuration file path.
+    ///
+    /// Values are loaded from the `cli_server` section of the file, and from environment
+    /// variables prefixed with `CLI_SERVER__`, which take precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - Path to the configuration file
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if:
+    /// * The configuration file cannot be read or parsed
+    /// * The configuration values don't match the expected structure
+    /// * The "cli_server" section is missing from the configuration
+    pub fn from_file_path<P: AsRef<Path>>(config_file_path: P) -> Self {
+        Self::try_from_file_path(config_file_path)
+            .expect("Failed to generate configuration instance")
+    }
+
+    /// Loads a `CliServerConfig` instance like [`Self::from_file_path`], without validating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "cli_server" section doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+    ) -> Result<Self, config::ConfigError> {
+        Config::builder()
+            .add_source(config::File::with_name(
+                &config_file_path.as_ref().to_string_lossy(),
+            ))
+            .add_source(
+                config::Environment::with_prefix("CLI_SERVER")
+                    .keep_prefix(true)
+                    .separator("__"),
+            )
+            .build()?
+            .get::<Self>("cli_server")
+    }
+
+    /// Reads the secrets which reference a provider, recording the ones which cannot be read
+    /// in `validator`
+    pub fn resolve_secrets(
+        &mut self,
+        providers: &SecretProviders,
+        validator: &mut ConfigValidator,
+    ) {
+        validator.resolve("cli_server.admin_token", &mut self.admin_token, providers);
+    }
+
+    /// Checks the configuration values, recording every problem found in `validator`
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.bind_address(
+            "cli_server.service_bind_address",
+            &self.service_bind_address,
+        );
+        validator.non_empty_secret("cli_server.admin_token", &self.admin_token);
+    }
+}
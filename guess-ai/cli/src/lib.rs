@@ -1,2 +1,6 @@
+pub mod auth;
 pub mod cli;
+pub mod config;
+pub mod multisig;
 pub mod server;
+pub mod simulator;
@@ -0,0 +1,668 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use dotenv::dotenv;
+use fastcrypto::encoding::{Base64, Encoding};
+use guess_ai::{
+    audit::{read_entries, AuditLog, AuditQuery},
+    config::ConfigValidator,
+    network::{Network, NetworkProfile},
+    secrets::{Keystore, KeystoreConfig, Secret, SecretProviders},
+};
+use guess_ai_cli::{
+    cli::{AdminAction, SuiClientContext, TransactionOutput},
+    config::{CliServerConfig, GuessAiCliConfig},
+    multisig::{multisig_public_key, read_signatures, MultisigProposal},
+    server::run_server,
+    simulator::{
+        calibrate, fee_curve, fee_curve_rows, monte_carlo, render, run_rows, summarize,
+        summary_rows, FeeParameters, OutputFormat, PlayerModel,
+    },
+};
+use sui_sdk::{
+    types::base_types::{ObjectID, SuiAddress},
+    wallet_context::WalletContext,
+};
+use tracing_subscriber::EnvFilter;
+
+/// Administers the GuessAI game contract
+#[derive(Parser)]
+struct Args {
+    /// Path to the configuration file, shared with the engine
+    #[arg(short, long)]
+    config_path: String,
+
+    /// Only dry-run the transaction, printing its effects without executing it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print the output as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Gas budget of the transaction, in MIST
+    #[arg(long, global = true)]
+    gas_budget: Option<u64>,
+
+    /// The network to administer, whose object IDs are read from the `networks.<network>`
+    /// section of the configuration file
+    #[arg(long, global = true)]
+    network: Option<Network>,
+
+    /// Allow the admin transactions on mainnet, which are otherwise refused
+    #[arg(long, global = true)]
+    allow_mainnet: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Admin(AdminAction),
+
+    /// Prints the state of the game
+    Status,
+
+    /// Serves the admin calls over HTTP, as configured in the `cli_server` section
+    Serve,
+
+    /// Exports the entries of an audit log as JSON lines, after verifying its hash chain
+    AuditExport {
+        /// The audit log to export, the CLI's own if unset (pass the engine's
+        /// `audit_log_path` to export the engine's)
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Only entries of this action, e.g. `set_starting_fee`
+        #[arg(long)]
+        action: Option<String>,
+
+        /// Only entries requested by this actor
+        #[arg(long)]
+        actor: Option<String>,
+
+        /// Only entries recorded at or after this time, in seconds since the Unix epoch
+        #[arg(long)]
+        since: Option<u64>,
+
+        /// Only entries recorded at or before this time, in seconds since the Unix epoch
+        #[arg(long)]
+        until: Option<u64>,
+
+        /// File to write the entries to, stdout if unset
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Simulates the fee curve, pool growth and protocol revenue of a game, to tune the fee
+    /// parameters before setting them on-chain
+    Simulate(SimulateArgs),
+
+    /// Administers the game through the multisig configured in `guess_ai.multisig`
+    Multisig {
+        #[command(subcommand)]
+        command: MultisigCommand,
+    },
+
+    /// Manages the engine's encrypted keystore, configured in `guess_ai.keystore`
+    Keystore {
+        #[command(subcommand)]
+        command: KeystoreCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeystoreCommand {
+    /// Lists the names of the secrets in the keystore
+    List,
+
+    /// Adds or replaces a secret, read from the first line of stdin. The keystore is
+    /// created if it doesn't exist
+    Set {
+        /// The name of the secret, as referenced by `{ provider = "keystore", name = ... }`
+        name: String,
+    },
+
+    /// Removes a secret
+    Remove {
+        /// The name of the secret
+        name: String,
+    },
+}
+
+#[derive(clap::Args)]
+struct SimulateArgs {
+    /// Fee of the first guess of a game, in MIST (the on-chain value if unset)
+    #[arg(long)]
+    starting_fee: Option<u64>,
+
+    /// Fee increase applied every fee update, in per mille (the on-chain value if unset)
+    #[arg(long)]
+    fee_rate_increase_per_guess_per_mille: Option<u64>,
+
+    /// Number of guesses between fee updates (the on-chain value if unset)
+    #[arg(long)]
+    update_fee_every_n_guesses: Option<u64>,
+
+    /// Share of each fee kept by the protocol, in per mille (the on-chain value if unset)
+    #[arg(long)]
+    protocol_fee_per_mille: Option<u64>,
+
+    /// Number of guesses of the fee curve, and guess horizon of the Monte Carlo runs
+    #[arg(long, default_value_t = 1_000)]
+    guesses: u64,
+
+    /// Treasury pool balance at the start of the game, in MIST
+    #[arg(long, default_value_t = 0)]
+    initial_pool: u64,
+
+    /// Number of Monte Carlo runs (only the fee curve is computed if 0)
+    #[arg(long, default_value_t = 0)]
+    runs: u64,
+
+    /// Mean number of guesses per hour at the starting fee [default: 10, or calibrated]
+    #[arg(long)]
+    arrival_rate: Option<f64>,
+
+    /// Probability that a guess wins [default: 0.001, or calibrated]
+    #[arg(long)]
+    win_probability: Option<f64>,
+
+    /// Increase of the win probability at every fee update, in per mille
+    #[arg(long, default_value_t = 0)]
+    win_probability_increase_per_mille: u64,
+
+    /// How much the arrival rate drops as the fee grows, as the exponent of
+    /// `starting_fee / fee`
+    #[arg(long, default_value_t = 0.0)]
+    price_elasticity: f64,
+
+    /// Seed of the Monte Carlo runs
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Calibrate the arrival rate and win probability on the historical `NewGuessEvent`s
+    #[arg(long)]
+    calibrate: bool,
+
+    /// Maximum number of historical guesses to calibrate on, the oldest first
+    #[arg(long)]
+    calibration_limit: Option<usize>,
+
+    /// Output format. In CSV, the fee curve is output, or the runs in Monte Carlo mode
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// File to write the output to, stdout if unset
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum MultisigCommand {
+    /// Prints the multisig address, which must hold the manager object and gas coins
+    Address,
+
+    /// Builds an admin transaction sent from the multisig address, without signing it
+    Propose {
+        /// File to write the proposal to, stdout if unset
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: AdminAction,
+    },
+
+    /// Signs a proposal with keys of the wallet's keystore, printing the partial signatures.
+    /// The call made by the proposal's transaction is decoded and printed first, to stderr
+    Sign {
+        /// The proposal file
+        proposal: PathBuf,
+
+        /// Address of a key to sign with, the active address if unset (can be repeated)
+        #[arg(long = "address")]
+        addresses: Vec<SuiAddress>,
+    },
+
+    /// Combines the partial signatures of the members, and executes the proposal
+    Submit {
+        /// The proposal file
+        proposal: PathBuf,
+
+        /// A base64 encoded partial signature (can be repeated)
+        #[arg(long = "signature")]
+        signatures: Vec<String>,
+
+        /// A file of partial signatures, one per line (can be repeated)
+        #[arg(long = "signature-file")]
+        signature_files: Vec<PathBuf>,
+
+        /// Address of a key of the wallet's keystore to also sign with (can be repeated)
+        #[arg(long = "sign-with")]
+        sign_with: Vec<SuiAddress>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    let LoadedConfig {
+        config,
+        server_config,
+    } = load_config(&args)?;
+    if let Command::AuditExport {
+        path,
+        action,
+        actor,
+        since,
+        until,
+        output,
+    } = args.command
+    {
+        let query = AuditQuery {
+            action,
+            actor,
+            since,
+            until,
+        };
+        let path = path.unwrap_or_else(|| PathBuf::from(&config.cli_audit_log_path));
+        return export_audit_log(&path, &query, output.as_deref());
+    }
+
+    if let Command::Keystore { command } = args.command {
+        return manage_keystore(config.keystore.as_ref(), command);
+    }
+
+    let multisig = config
+        .multisig
+        .as_ref()
+        .map(multisig_public_key)
+        .transpose()?;
+    let wallet_context = WalletContext::new(
+        Path::new(&config.sui_config_path),
+        config.request_timeout.map(Duration::from_secs),
+        config.max_concurrent_requests.map(|t| t as u64),
+    )?;
+    let mut client = SuiClientContext::new(
+        ObjectID::from_str(&config.guess_ai_db)?,
+        ObjectID::from_str(&config.guess_ai_manager_id)?,
+        ObjectID::from_str(&config.guess_ai_package_id)?,
+        wallet_context,
+    )
+    .with_dry_run(args.dry_run)
+    .with_audit_log(AuditLog::open(&config.cli_audit_log_path)?);
+    client
+        .verify_network(args.network, args.allow_mainnet)
+        .await?;
+
+    let action = match args.command {
+        Command::Admin(action) => action,
+        Command::Multisig { command } => {
+            let multisig = multisig.ok_or_else(|| {
+                anyhow!("No multisig is configured, see the `guess_ai.multisig` section")
+            })?;
+            let mut client = client.with_multisig(multisig.clone());
+            return match command {
+                MultisigCommand::Address => {
+                    println!("{}", SuiAddress::from(&multisig));
+                    Ok(())
+                }
+                MultisigCommand::Propose { output, action } => {
+                    let actor = format!("cli:{}", client.active_address()?);
+                    let transaction = client
+                        .execute_admin_action(&actor, &action, args.gas_budget)
+                        .await?;
+                    let proposal = MultisigProposal {
+                        action: action.name().to_string(),
+                        parameters: action.parameters(),
+                        sender: SuiAddress::from(&multisig),
+                        digest: transaction.digest,
+                        tx_bytes: transaction
+                            .tx_bytes
+                            .ok_or_else(|| anyhow!("Dry runs cannot be proposed"))?,
+                    };
+                    let proposal = serde_json::to_string_pretty(&proposal)?;
+                    match output {
+                        Some(output) => std::fs::write(output, proposal)?,
+                        None => println!("{proposal}"),
+                    }
+                    Ok(())
+                }
+                MultisigCommand::Sign {
+                    proposal,
+                    addresses,
+                } => {
+                    let proposal = MultisigProposal::read(proposal)?;
+                    let (_, call, action) = client.verify_multisig_proposal(&proposal)?;
+                    eprintln!("Signing transaction {}: {call}", proposal.digest);
+                    eprintln!("  {} {}", action.name(), action.parameters());
+                    let addresses = if addresses.is_empty() {
+                        vec![None]
+                    } else {
+                        addresses.into_iter().map(Some).collect()
+                    };
+                    for address in addresses {
+                        let signature = client.sign_multisig_proposal(&proposal, address)?;
+                        println!("{}", Base64::encode(signature.as_ref()));
+                    }
+                    Ok(())
+                }
+                MultisigCommand::Submit {
+                    proposal,
+                    signatures,
+                    signature_files,
+                    sign_with,
+                } => {
+                    let proposal = MultisigProposal::read(proposal)?;
+                    let mut signatures = read_signatures(&signatures, &signature_files)?;
+                    for address in sign_with {
+                        signatures.push(client.sign_multisig_proposal(&proposal, Some(address))?);
+                    }
+                    let output = client
+                        .submit_multisig_proposal(&proposal, signatures)
+                        .await?;
+                    print_output(&output, args.json)
+                }
+            };
+        }
+        Command::Simulate(simulate_args) => return simulate(&client, simulate_args).await,
+        Command::Status => {
+            let game_state = client.game_state().await?;
+            if args.json {
+                println!("{}", serde_json::to_string(&game_state)?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&game_state)?);
+            }
+            return Ok(());
+        }
+        Command::Serve => {
+            let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+            tokio::spawn(async move {
+                tokio::signal::ctrl_c()
+                    .await
+                    .expect("Failed to listen for ctrl-c");
+                shutdown_tx
+                    .send(true)
+                    .expect("Failed to send shutdown signal");
+            });
+            let server_config =
+                server_config.expect("The server config is loaded for the serve command");
+            return run_server(client, server_config, shutdown_rx).await;
+        }
+        Command::AuditExport { .. } => unreachable!("Audit exports are handled above"),
+        Command::Keystore { .. } => unreachable!("Keystore commands are handled above"),
+    };
+    let actor = format!("cli:{}", client.active_address()?);
+    let output = client
+        .execute_admin_action(&actor, &action, args.gas_budget)
+        .await?;
+    print_output(&output, args.json)
+}
+
+/// The configuration sections used by the selected command
+struct LoadedConfig {
+    config: GuessAiCliConfig,
+    server_config: Option<CliServerConfig>,
+}
+
+/// Loads and validates the configuration sections used by the selected command.
+///
+/// The audit log and keystore commands don't touch the chain, so only the loading of their
+/// configuration is checked. Every problem found, across all the sections, is reported at
+/// once.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` listing every problem found, with the path of its field
+fn load_config(args: &Args) -> Result<LoadedConfig> {
+    let mut validator = ConfigValidator::default();
+    let mut config = validator.load(
+        "guess_ai",
+        GuessAiCliConfig::try_from_file_path(&args.config_path),
+    );
+    let offline = matches!(
+        args.command,
+        Command::AuditExport { .. } | Command::Keystore { .. }
+    );
+    if let Some(config) = config.as_mut().filter(|_| !offline) {
+        if let Some(network) = args.network {
+            if let Some(profile) = validator.load(
+                &format!("networks.{network}"),
+                NetworkProfile::try_from_file_path(&args.config_path, network),
+            ) {
+                config.apply_network_profile(network, &profile, &mut validator);
+            }
+        }
+        config.validate(&mut validator);
+    }
+
+    let server_config = if matches!(args.command, Command::Serve) {
+        let mut server_config = validator.load(
+            "cli_server",
+            CliServerConfig::try_from_file_path(&args.config_path),
+        );
+        if let Some(server_config) = &mut server_config {
+            let keystore = config.as_ref().and_then(|config| config.keystore.as_ref());
+            match SecretProviders::new(keystore) {
+                Ok(providers) => server_config.resolve_secrets(&providers, &mut validator),
+                // The secrets are left unresolved, the invalid keystore is reported instead
+                Err(e) => validator.report("guess_ai.keystore", e),
+            }
+            server_config.validate(&mut validator);
+        }
+        server_config
+    } else {
+        None
+    };
+
+    validator.finish()?;
+    Ok(LoadedConfig {
+        config: config.expect("The config is loaded if it is valid"),
+        server_config,
+    })
+}
+
+/// Prints the outcome of an admin transaction
+fn print_output(output: &TransactionOutput, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(output)?);
+    } else if output.tx_bytes.is_some() {
+        println!(
+            "Transaction {} awaits the multisig signatures",
+            output.digest
+        );
+    } else if output.dry_run {
+        println!("Dry run of transaction {} succeeded", output.digest);
+    } else {
+        println!("Transaction {} executed", output.digest);
+    }
+    Ok(())
+}
+
+/// The default mean number of guesses per hour of the simulations
+const DEFAULT_ARRIVAL_RATE_PER_HOUR: f64 = 10.0;
+
+/// The default probability that a guess wins, in the simulations
+const DEFAULT_WIN_PROBABILITY: f64 = 0.001;
+
+/// Simulates a game, with the fee parameters overridden by the arguments
+async fn simulate(client: &SuiClientContext, args: SimulateArgs) -> Result<()> {
+    let parameters = match (
+        args.starting_fee,
+        args.fee_rate_increase_per_guess_per_mille,
+        args.update_fee_every_n_guesses,
+        args.protocol_fee_per_mille,
+    ) {
+        (
+            Some(starting_fee),
+            Some(fee_rate),
+            Some(update_fee_every_n_guesses),
+            Some(protocol_fee),
+        ) => FeeParameters {
+            starting_fee,
+            fee_rate_increase_per_guess_per_mille: fee_rate,
+            update_fee_every_n_guesses,
+            protocol_fee_per_mille: protocol_fee,
+        },
+        _ => {
+            let on_chain = FeeParameters::from_game_state(&client.game_state().await?);
+            FeeParameters {
+                starting_fee: args.starting_fee.unwrap_or(on_chain.starting_fee),
+                fee_rate_increase_per_guess_per_mille: args
+                    .fee_rate_increase_per_guess_per_mille
+                    .unwrap_or(on_chain.fee_rate_increase_per_guess_per_mille),
+                update_fee_every_n_guesses: args
+                    .update_fee_every_n_guesses
+                    .unwrap_or(on_chain.update_fee_every_n_guesses),
+                protocol_fee_per_mille: args
+                    .protocol_fee_per_mille
+                    .unwrap_or(on_chain.protocol_fee_per_mille),
+            }
+        }
+    };
+
+    let mut report = String::new();
+    let calibration = if args.calibrate {
+        let calibration = calibrate(
+            &client.guess_history(args.calibration_limit).await?,
+            &client.payout_history().await?,
+        );
+        let arrival_rate = calibration
+            .arrival_rate_per_hour
+            .map_or("n/a".to_string(), |rate| format!("{rate:.4}"));
+        let win_probability = calibration
+            .win_probability
+            .map_or("n/a".to_string(), |probability| format!("{probability:.6}"));
+        let summary = format!(
+            "Calibrated on {} guesses over {:.2} hours ({} games won): \
+             arrival rate {arrival_rate} guesses/hour, win probability {win_probability}",
+            calibration.guesses, calibration.hours, calibration.games_won,
+        );
+        match args.format {
+            OutputFormat::Table => report.push_str(&format!("{summary}\n\n")),
+            OutputFormat::Csv => eprintln!("{summary}"),
+        }
+        Some(calibration)
+    } else {
+        None
+    };
+    let model = PlayerModel {
+        arrival_rate_per_hour: args
+            .arrival_rate
+            .or(calibration
+                .as_ref()
+                .and_then(|calibration| calibration.arrival_rate_per_hour))
+            .unwrap_or(DEFAULT_ARRIVAL_RATE_PER_HOUR),
+        win_probability: args
+            .win_probability
+            .or(calibration
+                .as_ref()
+                .and_then(|calibration| calibration.win_probability))
+            .unwrap_or(DEFAULT_WIN_PROBABILITY),
+        win_probability_increase_per_mille: args.win_probability_increase_per_mille,
+        price_elasticity: args.price_elasticity,
+    };
+
+    let tiers = fee_curve(&parameters, args.guesses, args.initial_pool);
+    let outcomes = monte_carlo(
+        &parameters,
+        &model,
+        args.guesses,
+        args.initial_pool,
+        args.runs,
+        args.seed,
+    );
+    match args.format {
+        OutputFormat::Table => {
+            let (headers, rows) = fee_curve_rows(&tiers);
+            report.push_str(&format!("Fee curve over {} guesses\n", args.guesses));
+            report.push_str(&render(args.format, &headers, &rows));
+            if !outcomes.is_empty() {
+                let (headers, rows) = summary_rows(&summarize(&outcomes));
+                report.push_str(&format!(
+                    "\nMonte Carlo over {} runs ({:.4} guesses/hour, win probability {:.6})\n",
+                    args.runs, model.arrival_rate_per_hour, model.win_probability
+                ));
+                report.push_str(&render(args.format, &headers, &rows));
+            }
+        }
+        OutputFormat::Csv => {
+            let (headers, rows) = if outcomes.is_empty() {
+                fee_curve_rows(&tiers)
+            } else {
+                run_rows(&outcomes)
+            };
+            report.push_str(&render(args.format, &headers, &rows));
+        }
+    }
+    match args.output {
+        Some(output) => std::fs::write(output, report)?,
+        None => print!("{report}"),
+    }
+    Ok(())
+}
+
+/// Writes the entries of the audit log at `path` passing the query as JSON lines
+fn export_audit_log(path: &Path, query: &AuditQuery, output: Option<&Path>) -> Result<()> {
+    let mut writer: BufWriter<Box<dyn Write>> = BufWriter::new(match output {
+        Some(output) => Box::new(File::create(output)?),
+        None => Box::new(io::stdout()),
+    });
+    for entry in read_entries(path)?
+        .into_iter()
+        .filter(|entry| query.matches(entry))
+    {
+        serde_json::to_writer(&mut writer, &entry)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Lists, adds or removes the secrets of the keystore
+fn manage_keystore(config: Option<&KeystoreConfig>, command: KeystoreCommand) -> Result<()> {
+    let mut config = config
+        .cloned()
+        .ok_or_else(|| anyhow!("No keystore is configured, see the `guess_ai.keystore` section"))?;
+    config.passphrase.resolve(&SecretProviders::default())?;
+    let mut keystore = Keystore::unlock(&config, matches!(command, KeystoreCommand::Set { .. }))?;
+    match command {
+        KeystoreCommand::List => {
+            for name in keystore.names() {
+                println!("{name}");
+            }
+            return Ok(());
+        }
+        KeystoreCommand::Set { name } => {
+            let mut value = String::new();
+            io::stdin().read_line(&mut value)?;
+            let value = value.trim_end_matches(['\r', '\n']);
+            if value.is_empty() {
+                return Err(anyhow!("The secret read from stdin is empty"));
+            }
+            keystore.insert(name, Secret::new(value));
+        }
+        KeystoreCommand::Remove { name } => {
+            if !keystore.remove(&name) {
+                return Err(anyhow!("The keystore has no secret named {name}"));
+            }
+        }
+    }
+    keystore.save()?;
+    Ok(())
+}
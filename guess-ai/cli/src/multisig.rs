@@ -0,0 +1,400 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use fastcrypto::encoding::{Base64, Encoding, Hex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    crypto::{EncodeDecodeBase64, PublicKey, Signature, SuiSignature},
+    multisig::{MultiSig, MultiSigPublicKey},
+    signature::GenericSignature,
+    transaction::{
+        Argument, CallArg, Command, ObjectArg, TransactionData, TransactionDataAPI, TransactionKind,
+    },
+};
+use thiserror::Error;
+
+use crate::config::MultisigConfig;
+
+type Result<T> = std::result::Result<T, MultisigError>;
+
+/// An admin transaction built for the multisig address, awaiting the signatures of its members.
+///
+/// Only `tx_bytes` is signed: the other fields are a description of the transaction, which is
+/// checked against it (see `verified_transaction_data` and `MoveCall::decode`) rather than
+/// trusted. It is written to a file by `guess-ai-cli multisig propose`, passed around to the members to
+/// sign (with `guess-ai-cli multisig sign`, or `sui keytool sign --data <tx_bytes>`), and finally
+/// submitted with their signatures by `guess-ai-cli multisig submit`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultisigProposal {
+    /// The admin action, e.g. `set_starting_fee`
+    pub action: String,
+
+    /// The parameters of the action
+    pub parameters: Value,
+
+    /// The multisig address the transaction is sent from
+    pub sender: SuiAddress,
+
+    /// The digest of the transaction
+    pub digest: String,
+
+    /// The unsigned transaction, BCS encoded then base64 encoded
+    pub tx_bytes: String,
+}
+
+impl MultisigProposal {
+    /// Reads a proposal from a JSON file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Decodes the unsigned transaction of the proposal
+    pub fn transaction_data(&self) -> Result<TransactionData> {
+        let bytes =
+            Base64::decode(&self.tx_bytes).map_err(|_| MultisigError::MalformedTransaction)?;
+        bcs::from_bytes(&bytes).map_err(|_| MultisigError::MalformedTransaction)
+    }
+
+    /// Decodes the unsigned transaction of the proposal, checking that it is the transaction
+    /// of the proposal's digest, sent from the proposal's sender.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MultisigError` if the transaction cannot be decoded, or its digest or sender
+    /// differ from the proposal's
+    pub fn verified_transaction_data(&self) -> Result<TransactionData> {
+        let tx = self.transaction_data()?;
+        let digest = tx.digest().to_string();
+        if digest != self.digest {
+            return Err(MultisigError::DigestMismatch {
+                proposal: self.digest.clone(),
+                transaction: digest,
+            });
+        }
+        if tx.sender() != self.sender {
+            return Err(MultisigError::SenderMismatch {
+                proposal: self.sender,
+                transaction: tx.sender(),
+            });
+        }
+        Ok(tx)
+    }
+}
+
+/// An argument of a Move call, read from the inputs of its transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallArgument {
+    /// An object, by identifier
+    Object(ObjectID),
+
+    /// A pure value, BCS encoded
+    Pure(Vec<u8>),
+}
+
+impl fmt::Display for CallArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Object(id) => write!(f, "object {id}"),
+            Self::Pure(bytes) => write!(f, "0x{}", Hex::encode(bytes)),
+        }
+    }
+}
+
+/// The Move call made by an admin transaction, which is what the multisig members sign for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveCall {
+    /// The package of the called function
+    pub package: ObjectID,
+
+    /// The module of the called function
+    pub module: String,
+
+    /// The called function
+    pub function: String,
+
+    /// The arguments of the call
+    pub arguments: Vec<CallArgument>,
+}
+
+impl MoveCall {
+    /// Decodes the Move call of a transaction, which must be a programmable transaction made
+    /// of this single call, with only inputs as arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MultisigError::UnexpectedTransaction` if the transaction is anything else
+    pub fn decode(tx: &TransactionData) -> Result<Self> {
+        let TransactionKind::ProgrammableTransaction(ptb) = tx.kind() else {
+            return Err(MultisigError::UnexpectedTransaction(
+                "not a programmable transaction".to_string(),
+            ));
+        };
+        let [Command::MoveCall(call)] = ptb.commands.as_slice() else {
+            return Err(MultisigError::UnexpectedTransaction(format!(
+                "{} commands instead of a single Move call",
+                ptb.commands.len()
+            )));
+        };
+        let arguments = call
+            .arguments
+            .iter()
+            .map(|argument| {
+                let input = match argument {
+                    Argument::Input(index) => ptb.inputs.get(usize::from(*index)),
+                    _ => None,
+                };
+                match input {
+                    Some(CallArg::Pure(bytes)) => Ok(CallArgument::Pure(bytes.clone())),
+                    Some(CallArg::Object(
+                        ObjectArg::ImmOrOwnedObject((id, _, _))
+                        | ObjectArg::SharedObject { id, .. },
+                    )) => Ok(CallArgument::Object(*id)),
+                    _ => Err(MultisigError::UnexpectedTransaction(format!(
+                        "unexpected argument {argument:?} of the Move call"
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            package: call.package,
+            module: call.module.to_string(),
+            function: call.function.to_string(),
+            arguments,
+        })
+    }
+}
+
+impl fmt::Display for MoveCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}(", self.package, self.module, self.function)?;
+        for (i, argument) in self.arguments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{argument}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Encodes an unsigned transaction the way `sui keytool sign --data` expects it
+pub fn encode_transaction_data(tx: &TransactionData) -> Result<String> {
+    Ok(Base64::encode(
+        bcs::to_bytes(tx).map_err(|_| MultisigError::MalformedTransaction)?,
+    ))
+}
+
+/// Builds the multisig public key of the configured members.
+///
+/// # Errors
+///
+/// Returns a `MultisigError` if a public key cannot be decoded, or the weights and threshold
+/// do not make a valid multisig (e.g. a threshold above the total weight)
+pub fn multisig_public_key(config: &MultisigConfig) -> Result<MultiSigPublicKey> {
+    let public_keys = config
+        .public_keys
+        .iter()
+        .map(|public_key| {
+            PublicKey::decode_base64(public_key)
+                .map_err(|_| MultisigError::MalformedPublicKey(public_key.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let weights = if config.weights.is_empty() {
+        vec![1; public_keys.len()]
+    } else {
+        config.weights.clone()
+    };
+    MultiSigPublicKey::new(public_keys, weights, config.threshold)
+        .map_err(|e| MultisigError::InvalidMultisig(e.to_string()))
+}
+
+/// Parses the partial signatures held in `signatures` and in the lines of the
+/// `signature_files`, each a base64 encoded Sui signature.
+pub fn read_signatures<P: AsRef<Path>>(
+    signatures: &[String],
+    signature_files: &[P],
+) -> Result<Vec<Signature>> {
+    let mut encoded = signatures.to_vec();
+    for path in signature_files {
+        encoded.extend(
+            fs::read_to_string(path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+    encoded
+        .iter()
+        .map(|signature| {
+            Signature::from_str(signature)
+                .map_err(|_| MultisigError::MalformedSignature(signature.clone()))
+        })
+        .collect()
+}
+
+/// The address of the key which made a signature
+pub fn signer(signature: &Signature) -> Result<SuiAddress> {
+    let public_key = PublicKey::try_from_bytes(signature.scheme(), signature.public_key_bytes())
+        .map_err(|_| MultisigError::MalformedSignerPublicKey)?;
+    Ok(SuiAddress::from(&public_key))
+}
+
+/// Combines the partial signatures of the members into a multisig.
+///
+/// Signatures from the same member are only counted once.
+///
+/// # Errors
+///
+/// Returns a `MultisigError` if a signature is not from a member, or the signers' total
+/// weight is below the threshold
+pub fn combine_signatures(
+    multisig_public_key: &MultiSigPublicKey,
+    signatures: Vec<Signature>,
+) -> Result<GenericSignature> {
+    let members = multisig_public_key
+        .pubkeys()
+        .iter()
+        .map(|(public_key, weight)| (SuiAddress::from(public_key), *weight))
+        .collect::<Vec<_>>();
+    let mut signers = Vec::new();
+    let mut partial_signatures = Vec::new();
+    for signature in signatures {
+        let signer = signer(&signature)?;
+        if !members.iter().any(|(member, _)| *member == signer) {
+            return Err(MultisigError::NotAMember(signer));
+        }
+        if !signers.contains(&signer) {
+            signers.push(signer);
+            partial_signatures.push(GenericSignature::Signature(signature));
+        }
+    }
+    let weight: u16 = members
+        .iter()
+        .filter(|(member, _)| signers.contains(member))
+        .map(|(_, weight)| u16::from(*weight))
+        .sum();
+    if weight < *multisig_public_key.threshold() {
+        return Err(MultisigError::BelowThreshold {
+            weight,
+            threshold: *multisig_public_key.threshold(),
+        });
+    }
+    let multisig = MultiSig::combine(partial_signatures, multisig_public_key.clone())
+        .map_err(|e| MultisigError::InvalidMultisig(e.to_string()))?;
+    Ok(GenericSignature::MultiSig(multisig))
+}
+
+#[derive(Debug, Error)]
+pub enum MultisigError {
+    #[error("Failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse multisig proposal: {0}")]
+    MalformedProposal(#[from] serde_json::Error),
+    #[error("Malformed transaction bytes")]
+    MalformedTransaction,
+    #[error("The proposal's digest is {proposal}, but its transaction's is {transaction}")]
+    DigestMismatch {
+        proposal: String,
+        transaction: String,
+    },
+    #[error("The proposal is sent from {proposal}, but its transaction from {transaction}")]
+    SenderMismatch {
+        proposal: SuiAddress,
+        transaction: SuiAddress,
+    },
+    #[error("Unexpected admin transaction: {0}")]
+    UnexpectedTransaction(String),
+    #[error("Malformed public key {0}")]
+    MalformedPublicKey(String),
+    #[error("Malformed signature {0}")]
+    MalformedSignature(String),
+    #[error("Malformed public key of a signer")]
+    MalformedSignerPublicKey,
+    #[error("Invalid multisig: {0}")]
+    InvalidMultisig(String),
+    #[error("{0} is not a member of the multisig")]
+    NotAMember(SuiAddress),
+    #[error("The signatures weigh {weight}, below the multisig threshold of {threshold}")]
+    BelowThreshold { weight: u16, threshold: u16 },
+}
+
+#[cfg(test)]
+mod tests {
+    use shared_crypto::intent::{Intent, IntentMessage};
+    use sui_sdk::types::crypto::{get_key_pair, AccountKeyPair, SuiKeyPair};
+
+    use super::*;
+
+    fn key_pairs(count: usize) -> Vec<SuiKeyPair> {
+        (0..count)
+            .map(|_| SuiKeyPair::Ed25519(get_key_pair::<AccountKeyPair>().1))
+            .collect()
+    }
+
+    fn multisig(members: &[SuiKeyPair], weights: Vec<u8>, threshold: u16) -> MultiSigPublicKey {
+        MultiSigPublicKey::new(
+            members.iter().map(SuiKeyPair::public).collect(),
+            weights,
+            threshold,
+        )
+        .unwrap()
+    }
+
+    fn sign(key_pair: &SuiKeyPair) -> Signature {
+        let message = IntentMessage::new(Intent::sui_transaction(), b"proposal".to_vec());
+        Signature::new_secure(&message, key_pair)
+    }
+
+    #[test]
+    fn signatures_reaching_the_threshold_are_combined() {
+        let members = key_pairs(3);
+        let multisig = multisig(&members, vec![1, 1, 2], 2);
+
+        assert!(combine_signatures(&multisig, vec![sign(&members[0]), sign(&members[1])]).is_ok());
+        assert!(combine_signatures(&multisig, vec![sign(&members[2])]).is_ok());
+    }
+
+    #[test]
+    fn signatures_below_the_threshold_are_refused() {
+        let members = key_pairs(3);
+        let multisig = multisig(&members, vec![1, 1, 1], 2);
+
+        assert!(matches!(
+            combine_signatures(&multisig, vec![sign(&members[0])]),
+            Err(MultisigError::BelowThreshold {
+                weight: 1,
+                threshold: 2
+            })
+        ));
+        assert!(matches!(
+            combine_signatures(&multisig, vec![]),
+            Err(MultisigError::BelowThreshold { weight: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_signers_are_counted_once() {
+        let members = key_pairs(2);
+        let multisig = multisig(&members, vec![1, 1], 2);
+
+        assert!(matches!(
+            combine_signatures(&multisig, vec![sign(&members[0]), sign(&members[0])]),
+            Err(MultisigError::BelowThreshold { weight: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn signatures_of_non_members_are_refused() {
+        let members = key_pairs(2);
+        let outsider = key_pairs(1).remove(0);
+        let multisig = multisig(&members, vec![1, 1], 1);
+
+        let outsider_address = SuiAddress::from(&outsider.public());
+        assert!(matches!(
+            combine_signatures(&multisig, vec![sign(&members[0]), sign(&outsider)]),
+            Err(MultisigError::NotAMember(address)) if address == outsider_address
+        ));
+    }
+}
@@ -1,17 +1,38 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::post,
-    Router,
+    body::{to_bytes, Body},
+    extract::{rejection::JsonRejection, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
 };
-use std::sync::Arc;
+use guess_ai::{
+    audit::{AuditEntry, AuditQuery},
+    http_server::constant_time_eq,
+    network::NetworkError,
+};
+use guess_ai_common::game_state::GameState;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use sui_sdk::types::base_types::SuiAddress;
 use tokio::{
     net::TcpListener,
     sync::{watch::Receiver, RwLock},
 };
-use tracing::{error, instrument};
+use tracing::{error, info, instrument, warn};
 
-use crate::cli::SuiClientContext;
+use crate::{
+    auth::verify_personal_message_signature,
+    cli::{AdminAction, AdminCallError, SuiClientContext, TransactionOutput},
+    config::CliServerConfig,
+};
 
 const SET_FEE_RATE_INCREASE_ROUTE: &str = "/set_fee_rate_increase";
 const SET_GAME_INACTIVE_ROUTE: &str = "/set_game_inactive";
@@ -19,6 +40,36 @@ const SET_AGENT_ADDRESS_ROUTE: &str = "/set_agent_address";
 const SET_STARTING_FEE_ROUTE: &str = "/set_starting_fee";
 const SET_UPDATE_FEE_EVERY_N_GUESSES_ROUTE: &str = "/set_update_fee_every_n_guesses";
 const SET_PROTOCOL_FEE_PER_MILLE_ROUTE: &str = "/set_protocol_fee_per_mille";
+const STATUS_ROUTE: &str = "/status";
+const AUDIT_ROUTE: &str = "/audit";
+
+/// The actor recorded in the audit log for requests authenticated by the admin token alone
+const ADMIN_TOKEN_ACTOR: &str = "admin-token";
+
+/// The prefix of the authorization header value
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The header carrying the address of the admin who signed the request
+const ADMIN_ADDRESS_HEADER: &str = "x-admin-address";
+
+/// The header carrying the time the request was signed at, in seconds since the Unix epoch
+const ADMIN_TIMESTAMP_HEADER: &str = "x-admin-timestamp";
+
+/// The header carrying the unique identifier of the request, which cannot be reused
+const ADMIN_NONCE_HEADER: &str = "x-admin-nonce";
+
+/// The bounds of the length of a request nonce, e.g. a UUID
+const NONCE_LENGTH: std::ops::RangeInclusive<usize> = 16..=128;
+
+/// The header carrying the base64 encoded Sui personal-message signature of the request
+const ADMIN_SIGNATURE_HEADER: &str = "x-admin-signature";
+
+/// How far the signing time of a request may be from the server's time, in seconds,
+/// bounding the window in which a captured request can be replayed
+const MAX_SIGNATURE_AGE_SECS: u64 = 300;
+
+/// The maximum size of a request body, in bytes
+const MAX_BODY_SIZE: usize = 64 * 1024;
 
 #[derive(Clone)]
 pub struct GuessAiCliState {
@@ -26,14 +77,96 @@ pub struct GuessAiCliState {
     /// Wrapped in `Arc<RwLock>` to allow multiple handlers to safely access and modify the client
     /// state concurrently.
     pub client: Arc<RwLock<SuiClientContext>>,
+
+    /// The bearer token every request must carry
+    admin_token: Arc<str>,
+
+    /// The addresses allowed to sign requests. Signatures are not required if empty
+    admin_addresses: Arc<[SuiAddress]>,
+
+    /// The nonces of the signed requests accepted recently
+    seen_nonces: Arc<Mutex<SeenNonces>>,
+}
+
+/// The nonces of the signed requests accepted within the signature window, so that a
+/// captured request cannot be replayed while its timestamp is still accepted
+#[derive(Debug, Default)]
+struct SeenNonces {
+    /// The signing time of each accepted request, by signer and nonce
+    nonces: HashMap<(SuiAddress, String), u64>,
+}
+
+impl SeenNonces {
+    /// Records the nonce of a request signed at `signed_at`, returning whether it was unused.
+    /// The nonces of requests out of the signature window are forgotten, as these requests
+    /// are refused for their timestamp anyway
+    fn insert(&mut self, address: SuiAddress, nonce: &str, signed_at: u64, now: u64) -> bool {
+        self.nonces
+            .retain(|_, signed_at| now.abs_diff(*signed_at) <= MAX_SIGNATURE_AGE_SECS);
+        match self.nonces.entry((address, nonce.to_string())) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(signed_at);
+                true
+            }
+        }
+    }
+}
+
+/// Who requested an admin call, as recorded in the audit log
+#[derive(Clone, Debug)]
+struct Actor(String);
+
+#[derive(Debug, Deserialize)]
+pub struct SetFeeRateIncreaseRequest {
+    /// The fee increase applied every fee update, in per mille
+    pub fee_rate_increase_per_guess_per_mille: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAgentAddressRequest {
+    /// The address of the agent allowed to withdraw funds and submit attestations
+    pub agent_address: SuiAddress,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetStartingFeeRequest {
+    /// The fee of the first guess of a game, in MIST
+    pub starting_fee: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetUpdateFeeEveryNGuessesRequest {
+    /// The number of guesses between fee updates
+    pub update_fee_every_n_guesses: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetProtocolFeePerMilleRequest {
+    /// The share of each fee kept by the protocol, in per mille
+    pub protocol_fee_per_mille: u64,
 }
 
+/// Runs the admin server until the shutdown signal is received.
+///
+/// # Arguments
+/// * `client` - The Sui client context the admin calls are made with
+/// * `config` - The configuration of the server, including its credentials
+/// * `shutdown_receiver` - The receiver for the shutdown signal
 pub async fn run_server(
     client: SuiClientContext,
-    tcp_listener: TcpListener,
+    config: CliServerConfig,
     mut shutdown_receiver: Receiver<bool>,
 ) -> anyhow::Result<()> {
-    let cli_router = create_router(client);
+    let tcp_listener = TcpListener::bind(&config.service_bind_address).await?;
+    info!(
+        target = "guess-ai-cli",
+        event = "server-started",
+        signatures_required = !config.admin_addresses.is_empty(),
+        "Serving admin calls on {}",
+        config.service_bind_address
+    );
+    let cli_router = create_router(client, config);
     let server = axum::serve(tcp_listener, cli_router.into_make_service()).with_graceful_shutdown(
         async move {
             shutdown_receiver
@@ -55,7 +188,17 @@ pub async fn run_server(
 /// - Setting the starting fee
 /// - Setting the fee update interval in guesses
 /// - Setting the protocol fee per milli
-fn create_router(client: SuiClientContext) -> Router {
+/// - Reading the state of the game
+/// - Querying the audit log of the admin calls
+///
+/// Every route is authenticated, see `authenticate`.
+fn create_router(client: SuiClientContext, config: CliServerConfig) -> Router {
+    let state = GuessAiCliState {
+        client: Arc::new(RwLock::new(client)),
+        admin_token: config.admin_token.expose_secret().into(),
+        admin_addresses: config.admin_addresses.into(),
+        seen_nonces: Arc::default(),
+    };
     Router::new()
         .route(
             SET_FEE_RATE_INCREASE_ROUTE,
@@ -72,30 +215,173 @@ fn create_router(client: SuiClientContext) -> Router {
             SET_PROTOCOL_FEE_PER_MILLE_ROUTE,
             post(set_protocol_fee_per_mille_handler),
         )
-        .with_state(GuessAiCliState {
-            client: Arc::new(RwLock::new(client)),
-        })
+        .route(STATUS_ROUTE, get(status_handler))
+        .route(AUDIT_ROUTE, get(audit_handler))
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state)
+}
+
+/// Authenticates every request.
+///
+/// The request must carry the admin token as a bearer token. If admin addresses are
+/// configured, it must also be signed by one of them: the `x-admin-signature` header holds
+/// a Sui personal-message signature, by the address in `x-admin-address`, of the message
+/// `{timestamp}:{nonce}:{path}:{body}`, `timestamp` being the value of `x-admin-timestamp`,
+/// `nonce` the value of `x-admin-nonce` and `path` the request path with its query string.
+/// The nonce, of 16 to 128 alphanumeric characters, dashes or underscores (e.g. a UUID), may
+/// only be used once by each address.
+///
+/// The signer's address, or `admin-token` if signatures are not required, is recorded as
+/// the actor of the call in the audit log.
+async fn authenticate(
+    State(state): State<GuessAiCliState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AdminApiError> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .ok_or_else(|| AdminApiError::unauthorized("Invalid or missing admin token"))?;
+    if !constant_time_eq(state.admin_token.as_bytes(), token.as_bytes()) {
+        return Err(AdminApiError::unauthorized(
+            "Invalid or missing admin token",
+        ));
+    }
+    if state.admin_addresses.is_empty() {
+        request
+            .extensions_mut()
+            .insert(Actor(ADMIN_TOKEN_ACTOR.to_string()));
+        return Ok(next.run(request).await);
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|e| AdminApiError::bad_request(format!("Failed to read request body: {e}")))?;
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or(parts.uri.path(), |path| path.as_str());
+    let address = verify_request_signature(
+        &parts.headers,
+        path,
+        &body,
+        &state.admin_addresses,
+        &state.seen_nonces,
+    )?;
+    info!(
+        target = "guess-ai-cli",
+        event = "admin-request-signed",
+        admin_address = %address,
+        path,
+        "Authenticated signed admin request"
+    );
+    let mut request = Request::from_parts(parts, Body::from(body));
+    request.extensions_mut().insert(Actor(address.to_string()));
+    Ok(next.run(request).await)
+}
+
+/// Verifies the admin signature of a request, returning the address which signed it.
+///
+/// The nonce of the request is recorded in `seen_nonces` once the signature is verified, and
+/// the request is refused if it was already used
+fn verify_request_signature(
+    headers: &HeaderMap,
+    path: &str,
+    body: &[u8],
+    admin_addresses: &[SuiAddress],
+    seen_nonces: &Mutex<SeenNonces>,
+) -> Result<SuiAddress, AdminApiError> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AdminApiError::unauthorized(format!("Missing {name} header")))
+    };
+    let address = SuiAddress::from_str(header(ADMIN_ADDRESS_HEADER)?)
+        .map_err(|_| AdminApiError::unauthorized("Malformed admin address"))?;
+    if !admin_addresses.contains(&address) {
+        return Err(AdminApiError::unauthorized(format!(
+            "{address} is not an admin address"
+        )));
+    }
+    let timestamp = header(ADMIN_TIMESTAMP_HEADER)?;
+    let signed_at = timestamp
+        .parse::<u64>()
+        .map_err(|_| AdminApiError::unauthorized("Malformed admin timestamp"))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    if now.abs_diff(signed_at) > MAX_SIGNATURE_AGE_SECS {
+        return Err(AdminApiError::unauthorized(
+            "Admin signature timestamp is too far from the server time",
+        ));
+    }
+
+    let nonce = header(ADMIN_NONCE_HEADER)?;
+    if !NONCE_LENGTH.contains(&nonce.len())
+        || !nonce
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+    {
+        return Err(AdminApiError::unauthorized("Malformed admin nonce"));
+    }
+
+    let mut message = format!("{timestamp}:{nonce}:{path}:").into_bytes();
+    message.extend_from_slice(body);
+    verify_personal_message_signature(header(ADMIN_SIGNATURE_HEADER)?, &message, address).map_err(
+        |e| {
+            warn!(
+                target = "guess-ai-cli",
+                event = "admin-signature-rejected",
+                admin_address = %address,
+                "Rejected admin signature: {e}"
+            );
+            AdminApiError::unauthorized(format!("Invalid admin signature: {e}"))
+        },
+    )?;
+    let unused = seen_nonces
+        .lock()
+        .expect("The nonces lock is not poisoned")
+        .insert(address, nonce, signed_at, now);
+    if !unused {
+        warn!(
+            target = "guess-ai-cli",
+            event = "admin-request-replayed",
+            admin_address = %address,
+            "Rejected admin request reusing nonce {nonce}"
+        );
+        return Err(AdminApiError::unauthorized("Admin nonce was already used"));
+    }
+    Ok(address)
 }
 
 #[instrument(
     level = "info",
     skip(state),
-    fields(
-        route = "set_fee_rate_increase_handler",
-        fee_rate_increase = fee_rate_increase
-    )
+    fields(route = "set_fee_rate_increase_handler")
 )]
 async fn set_fee_rate_increase_handler(
     State(state): State<GuessAiCliState>,
-    Path(fee_rate_increase): Path<u64>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+    request: Result<Json<SetFeeRateIncreaseRequest>, JsonRejection>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
+    let Json(request) = request?;
     let mut client = state.client.write().await;
-    let tx = client.set_fee_rate_increase_per_guess_per_mille(fee_rate_increase, None, None, None);
-    tx.await.map_err(|e| {
-        error!("Error setting fee rate increase: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(
+            &actor,
+            &AdminAction::SetFeeRateIncrease {
+                fee_rate_increase_per_guess_per_mille: request
+                    .fee_rate_increase_per_guess_per_mille,
+            },
+            None,
+        )
+        .await?;
+    Ok(Json(output))
 }
 
 #[instrument(
@@ -105,120 +391,482 @@ async fn set_fee_rate_increase_handler(
 )]
 async fn set_game_inactive_handler(
     State(state): State<GuessAiCliState>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
     let mut client = state.client.write().await;
-    let tx = client.set_game_inactive(None, None, None);
-    tx.await.map_err(|e| {
-        tracing::error!("Error setting game inactive: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(&actor, &AdminAction::SetGameInactive, None)
+        .await?;
+    Ok(Json(output))
 }
 
 #[instrument(
     level = "info",
     skip(state),
-    fields(
-        route = "set_agent_address_handler",
-        agent_address = agent_address
-    )
+    fields(route = "set_agent_address_handler")
 )]
 async fn set_agent_address_handler(
     State(state): State<GuessAiCliState>,
-    Path(agent_address): Path<String>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+    request: Result<Json<SetAgentAddressRequest>, JsonRejection>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
+    let Json(request) = request?;
     let mut client = state.client.write().await;
-    let tx = client.set_agent_address(agent_address, None, None, None);
-    tx.await.map_err(|e| {
-        error!("Error setting agent address: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(
+            &actor,
+            &AdminAction::SetAgentAddress {
+                agent_address: request.agent_address.to_string(),
+            },
+            None,
+        )
+        .await?;
+    Ok(Json(output))
 }
 
 #[instrument(
     level = "info",
     skip(state),
-    fields(
-        route = "set_starting_fee_handler",
-        starting_fee = starting_fee
-    )
+    fields(route = "set_starting_fee_handler")
 )]
 async fn set_starting_fee_handler(
     State(state): State<GuessAiCliState>,
-    Path(starting_fee): Path<u64>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+    request: Result<Json<SetStartingFeeRequest>, JsonRejection>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
+    let Json(request) = request?;
     let mut client = state.client.write().await;
-    let tx = client.set_starting_fee(starting_fee, None, None, None);
-    tx.await.map_err(|e| {
-        error!("Error setting starting fee: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(
+            &actor,
+            &AdminAction::SetStartingFee {
+                starting_fee: request.starting_fee,
+            },
+            None,
+        )
+        .await?;
+    Ok(Json(output))
 }
 
 /// Sets the fee update interval in guesses.
 ///
-/// # Arguments
+/// # Body
 /// * `update_fee_every_n_guesses` - The number of guesses between fee updates
 ///
 /// # Returns
-/// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-///
-/// # Example
-/// ```rust,ignore
-/// let tx_digest = client.set_update_fee_every_n_guesses(10, None, None, None).await?;
-/// ```
+/// * `Json<TransactionOutput>` - The digest and effects of the transaction
 #[instrument(
     level = "info",
     skip(state),
-    fields(
-        route = "set_update_fee_every_n_guesses_handler",
-        update_fee_every_n_guesses = update_fee_every_n_guesses
-    )
+    fields(route = "set_update_fee_every_n_guesses_handler")
 )]
 async fn set_update_fee_every_n_guesses_handler(
     State(state): State<GuessAiCliState>,
-    Path(update_fee_every_n_guesses): Path<u64>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+    request: Result<Json<SetUpdateFeeEveryNGuessesRequest>, JsonRejection>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
+    let Json(request) = request?;
     let mut client = state.client.write().await;
-    let tx = client.set_update_fee_every_n_guesses(update_fee_every_n_guesses, None, None, None);
-    tx.await.map_err(|e| {
-        error!("Error setting update fee every n guesses: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(
+            &actor,
+            &AdminAction::SetUpdateFeeEveryNGuesses {
+                update_fee_every_n_guesses: request.update_fee_every_n_guesses,
+            },
+            None,
+        )
+        .await?;
+    Ok(Json(output))
 }
 
 /// Sets the protocol fee per mille.
 ///
-/// # Arguments
+/// # Body
 /// * `protocol_fee_per_mille` - The protocol fee rate in per mille (parts per thousand) for the GuessAI game.
 ///
 /// # Returns
-/// * `Result<String>` - The transaction digest if successful, or an error if the transaction fails
-///
-/// # Example
-/// ```rust,ignore
-/// let tx_digest = client.set_protocol_fee_per_mille(50, None, None, None).await?;
-/// ```
+/// * `Json<TransactionOutput>` - The digest and effects of the transaction
 #[instrument(
     level = "info",
     skip(state),
-    fields(
-        route = "set_protocol_fee_per_mille_handler",
-        protocol_fee_per_mille = protocol_fee_per_mille
-    )
+    fields(route = "set_protocol_fee_per_mille_handler")
 )]
 async fn set_protocol_fee_per_mille_handler(
     State(state): State<GuessAiCliState>,
-    Path(protocol_fee_per_mille): Path<u64>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    Extension(Actor(actor)): Extension<Actor>,
+    request: Result<Json<SetProtocolFeePerMilleRequest>, JsonRejection>,
+) -> Result<Json<TransactionOutput>, AdminApiError> {
+    let Json(request) = request?;
     let mut client = state.client.write().await;
-    let tx = client.set_protocol_fee_per_mille(protocol_fee_per_mille, None, None, None);
-    tx.await.map_err(|e| {
-        error!("Error setting protocol fee per mille: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(StatusCode::OK)
+    let output = client
+        .execute_admin_action(
+            &actor,
+            &AdminAction::SetProtocolFeePerMille {
+                protocol_fee_per_mille: request.protocol_fee_per_mille,
+            },
+            None,
+        )
+        .await?;
+    Ok(Json(output))
+}
+
+/// Reads the state of the game from the GuessAI db object.
+///
+/// # Returns
+/// * `Json<GameState>` - The decoded state of the game
+#[instrument(level = "info", skip(state), fields(route = "status_handler"))]
+async fn status_handler(
+    State(state): State<GuessAiCliState>,
+) -> Result<Json<GameState>, AdminApiError> {
+    let client = state.client.read().await;
+    Ok(Json(client.game_state().await?))
+}
+
+/// Returns the entries of the audit log passing the filter, e.g. to tell who changed the fee and when.
+///
+/// # Query
+/// * `action` - Only entries of this action, e.g. `set_starting_fee`
+/// * `actor` - Only entries requested by this actor
+/// * `since` / `until` - Only entries recorded in this range, in seconds since the Unix epoch
+///
+/// # Returns
+/// * `Json<Vec<AuditEntry>>` - The matching entries, oldest first
+#[instrument(level = "info", skip(state), fields(route = "audit_handler"))]
+async fn audit_handler(
+    State(state): State<GuessAiCliState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AdminApiError> {
+    let client = state.client.read().await;
+    Ok(Json(client.query_audit_log(&query)?))
+}
+
+/// The Move abort of a failed admin transaction
+#[derive(Debug, Serialize)]
+struct MoveAbortDetails {
+    /// The module which aborted
+    module: String,
+
+    /// The abort code
+    code: u64,
+
+    /// The meaning of the abort code, if the GuessAI module aborted
+    reason: Option<String>,
+}
+
+/// A JSON error response of the admin server
+#[derive(Debug)]
+pub struct AdminApiError {
+    /// The HTTP status of the response
+    status: StatusCode,
+
+    /// The error type, e.g. `authentication_error`
+    error_type: &'static str,
+
+    /// A human readable description of the error
+    message: String,
+
+    /// The Move abort, if the transaction was aborted by the contract
+    abort: Option<MoveAbortDetails>,
+}
+
+impl AdminApiError {
+    fn new(status: StatusCode, error_type: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error_type,
+            message: message.into(),
+            abort: None,
+        }
+    }
+
+    fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "authentication_error", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "invalid_request_error", message)
+    }
+}
+
+impl From<JsonRejection> for AdminApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(
+            rejection.status(),
+            "invalid_request_error",
+            rejection.body_text(),
+        )
+    }
+}
+
+impl From<anyhow::Error> for AdminApiError {
+    fn from(error: anyhow::Error) -> Self {
+        if error.downcast_ref::<NetworkError>().is_some() {
+            return Self::new(StatusCode::FORBIDDEN, "network_error", error.to_string());
+        }
+        match error.downcast_ref::<AdminCallError>() {
+            Some(AdminCallError::MoveAbort {
+                module,
+                code,
+                reason,
+                ..
+            }) => Self {
+                abort: Some(MoveAbortDetails {
+                    module: module.clone(),
+                    code: *code,
+                    reason: reason.map(|reason| reason.to_string()),
+                }),
+                ..Self::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "move_abort",
+                    error.to_string(),
+                )
+            },
+            Some(AdminCallError::ExecutionFailed { .. }) => Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "execution_error",
+                error.to_string(),
+            ),
+            None => {
+                error!(
+                    target = "guess-ai-cli",
+                    event = "admin-call-error",
+                    "Admin call failed: {error:?}"
+                );
+                Self::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "api_error",
+                    error.to_string(),
+                )
+            }
+        }
+    }
+}
+
+impl IntoResponse for AdminApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "message": self.message,
+                "type": self.error_type,
+                "code": self.status.as_u16(),
+                "abort": self.abort,
+            }
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+    use sui_sdk::types::crypto::{
+        get_key_pair, AccountKeyPair, EncodeDecodeBase64, Signature, SuiKeyPair,
+    };
+
+    use guess_ai::client::GuessAiAbortCode;
+
+    use super::*;
+
+    const NONCE: &str = "0b6f0c9e-6f2a-4c1b-9d7e-2a1f3c5b7d9e";
+
+    fn new_key_pair() -> (SuiAddress, SuiKeyPair) {
+        let key_pair = SuiKeyPair::Ed25519(get_key_pair::<AccountKeyPair>().1);
+        (SuiAddress::from(&key_pair.public()), key_pair)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    /// The headers of a request to `path` with `body`, signed by `key_pair` at `signed_at`
+    /// on behalf of `address`
+    fn signed_headers(
+        key_pair: &SuiKeyPair,
+        address: SuiAddress,
+        signed_at: u64,
+        nonce: &str,
+        path: &str,
+        body: &[u8],
+    ) -> HeaderMap {
+        let mut message = format!("{signed_at}:{nonce}:{path}:").into_bytes();
+        message.extend_from_slice(body);
+        let message = IntentMessage::new(Intent::personal_message(), PersonalMessage { message });
+        let signature = Signature::new_secure(&message, key_pair).encode_base64();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ADMIN_ADDRESS_HEADER, address.to_string().parse().unwrap());
+        headers.insert(ADMIN_TIMESTAMP_HEADER, signed_at.into());
+        headers.insert(ADMIN_NONCE_HEADER, nonce.parse().unwrap());
+        headers.insert(ADMIN_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers
+    }
+
+    fn verify(
+        headers: &HeaderMap,
+        path: &str,
+        body: &[u8],
+        admin_addresses: &[SuiAddress],
+        seen_nonces: &Mutex<SeenNonces>,
+    ) -> Result<SuiAddress, StatusCode> {
+        verify_request_signature(headers, path, body, admin_addresses, seen_nonces)
+            .map_err(|error| error.status)
+    }
+
+    #[test]
+    fn request_signed_by_an_admin_is_accepted() {
+        let (address, key_pair) = new_key_pair();
+        let body = br#"{"starting_fee":1000}"#;
+        let headers = signed_headers(
+            &key_pair,
+            address,
+            now(),
+            NONCE,
+            SET_STARTING_FEE_ROUTE,
+            body,
+        );
+        assert_eq!(
+            verify(
+                &headers,
+                SET_STARTING_FEE_ROUTE,
+                body,
+                &[address],
+                &Mutex::default()
+            ),
+            Ok(address)
+        );
+    }
+
+    #[test]
+    fn request_signed_by_another_key_is_refused() {
+        let (address, _) = new_key_pair();
+        let (_, other_key_pair) = new_key_pair();
+        let headers = signed_headers(&other_key_pair, address, now(), NONCE, STATUS_ROUTE, b"");
+        assert_eq!(
+            verify(&headers, STATUS_ROUTE, b"", &[address], &Mutex::default()),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn request_signed_by_a_non_admin_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        let (admin_address, _) = new_key_pair();
+        let headers = signed_headers(&key_pair, address, now(), NONCE, STATUS_ROUTE, b"");
+        assert_eq!(
+            verify(
+                &headers,
+                STATUS_ROUTE,
+                b"",
+                &[admin_address],
+                &Mutex::default()
+            ),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn reused_nonce_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        let seen_nonces = Mutex::default();
+        let headers = signed_headers(&key_pair, address, now(), NONCE, STATUS_ROUTE, b"");
+        assert_eq!(
+            verify(&headers, STATUS_ROUTE, b"", &[address], &seen_nonces),
+            Ok(address)
+        );
+        assert_eq!(
+            verify(&headers, STATUS_ROUTE, b"", &[address], &seen_nonces),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn stale_timestamp_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        for signed_at in [
+            now() - MAX_SIGNATURE_AGE_SECS - 10,
+            now() + MAX_SIGNATURE_AGE_SECS + 10,
+        ] {
+            let headers = signed_headers(&key_pair, address, signed_at, NONCE, STATUS_ROUTE, b"");
+            assert_eq!(
+                verify(&headers, STATUS_ROUTE, b"", &[address], &Mutex::default()),
+                Err(StatusCode::UNAUTHORIZED)
+            );
+        }
+    }
+
+    #[test]
+    fn tampered_path_query_or_body_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        let path = "/audit?actor=0xabc";
+        let headers = signed_headers(&key_pair, address, now(), NONCE, path, b"{}");
+        for (path, body) in [
+            ("/audit?actor=0xdef", b"{}".as_slice()),
+            ("/status?actor=0xabc", b"{}"),
+            ("/audit", b"{}"),
+            (path, b"{\"fee\":1}"),
+        ] {
+            assert_eq!(
+                verify(&headers, path, body, &[address], &Mutex::default()),
+                Err(StatusCode::UNAUTHORIZED)
+            );
+        }
+        // The refused requests did not consume the nonce
+        assert_eq!(
+            verify(&headers, path, b"{}", &[address], &Mutex::default()),
+            Ok(address)
+        );
+    }
+
+    #[test]
+    fn malformed_nonce_is_refused() {
+        let (address, key_pair) = new_key_pair();
+        for nonce in ["short", "not a valid nonce with spaces"] {
+            let headers = signed_headers(&key_pair, address, now(), nonce, STATUS_ROUTE, b"");
+            assert_eq!(
+                verify(&headers, STATUS_ROUTE, b"", &[address], &Mutex::default()),
+                Err(StatusCode::UNAUTHORIZED)
+            );
+        }
+    }
+
+    #[test]
+    fn seen_nonces_refuse_a_replay() {
+        let (address, _) = new_key_pair();
+        let (other_address, _) = new_key_pair();
+        let mut seen_nonces = SeenNonces::default();
+        assert!(seen_nonces.insert(address, NONCE, 1_000, 1_000));
+        assert!(!seen_nonces.insert(address, NONCE, 1_000, 1_010));
+        assert!(!seen_nonces.insert(address, NONCE, 1_100, 1_100));
+        // Nonces are per address
+        assert!(seen_nonces.insert(other_address, NONCE, 1_000, 1_010));
+    }
+
+    #[test]
+    fn seen_nonces_forget_nonces_out_of_the_window() {
+        let (address, _) = new_key_pair();
+        let mut seen_nonces = SeenNonces::default();
+        assert!(seen_nonces.insert(address, NONCE, 1_000, 1_000));
+        assert!(seen_nonces.insert(address, "another-nonce-0001", 1_200, 1_200));
+        let expired = 1_000 + MAX_SIGNATURE_AGE_SECS + 1;
+        assert!(seen_nonces.insert(address, NONCE, expired, expired));
+        assert_eq!(seen_nonces.nonces.len(), 2);
+    }
+
+    #[test]
+    fn move_abort_errors_carry_the_abort_reason() {
+        let error = AdminApiError::from(anyhow::Error::new(AdminCallError::MoveAbort {
+            digest: "digest".to_string(),
+            module: "contract".to_string(),
+            code: 1,
+            reason: Some(GuessAiAbortCode::GameInactive),
+        }));
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.error_type, "move_abort");
+        let abort = error.abort.unwrap();
+        assert_eq!(abort.code, 1);
+        assert_eq!(abort.reason.as_deref(), Some("game is inactive"));
+    }
 }
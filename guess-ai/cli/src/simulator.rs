@@ -0,0 +1,644 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use clap::ValueEnum;
+use guess_ai_common::game_state::GameState;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+/// The denominator of the per mille rates
+const PER_MILLE: u128 = 1_000;
+
+/// The number of milliseconds in an hour
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// The fee parameters of the game, as set by the admin calls
+#[derive(Clone, Copy, Debug)]
+pub struct FeeParameters {
+    /// The fee of the first guess of a game, in MIST
+    pub starting_fee: u64,
+
+    /// The fee increase applied every `update_fee_every_n_guesses` guesses, in per mille
+    pub fee_rate_increase_per_guess_per_mille: u64,
+
+    /// The number of guesses between fee increases
+    pub update_fee_every_n_guesses: u64,
+
+    /// The share of each fee kept by the protocol, in per mille
+    pub protocol_fee_per_mille: u64,
+}
+
+impl FeeParameters {
+    /// The fee parameters currently set on-chain
+    pub fn from_game_state(game_state: &GameState) -> Self {
+        Self {
+            starting_fee: game_state.starting_fee,
+            fee_rate_increase_per_guess_per_mille: game_state.fee_rate_increase_per_guess_per_mille,
+            update_fee_every_n_guesses: game_state.update_fee_every_n_guesses,
+            protocol_fee_per_mille: game_state.protocol_fee_per_mille,
+        }
+    }
+
+    /// The fee after the `guess_count`-th guess of a game was paid at `fee`.
+    ///
+    /// Like the contract, the fee increases by `fee_rate_increase_per_guess_per_mille` once
+    /// every `update_fee_every_n_guesses` guesses.
+    pub fn next_fee(&self, fee: u64, guess_count: u64) -> u64 {
+        if guess_count.checked_rem(self.update_fee_every_n_guesses) != Some(0) {
+            return fee;
+        }
+        let increase =
+            u128::from(fee) * u128::from(self.fee_rate_increase_per_guess_per_mille) / PER_MILLE;
+        u64::try_from(u128::from(fee) + increase).unwrap_or(u64::MAX)
+    }
+
+    /// Splits a fee into its treasury and protocol shares
+    pub fn split_fee(&self, fee: u64) -> (u64, u64) {
+        let protocol_share = (u128::from(fee) * u128::from(self.protocol_fee_per_mille) / PER_MILLE)
+            .min(u128::from(fee)) as u64;
+        (fee - protocol_share, protocol_share)
+    }
+}
+
+/// A range of guesses paid at the same fee
+#[derive(Clone, Debug)]
+pub struct FeeTier {
+    /// The first guess of the tier, starting at 1
+    pub first_guess: u64,
+
+    /// The last guess of the tier
+    pub last_guess: u64,
+
+    /// The fee of the guesses of the tier, in MIST
+    pub fee: u64,
+
+    /// The treasury pool balance after the last guess of the tier, in MIST
+    pub treasury_pool_balance: u64,
+
+    /// The protocol revenue after the last guess of the tier, in MIST
+    pub protocol_revenue: u64,
+}
+
+/// Computes the fee curve of a game lasting `guesses` guesses, one tier per fee.
+///
+/// # Arguments
+/// * `parameters` - The fee parameters
+/// * `guesses` - The number of guesses of the game
+/// * `initial_pool` - The treasury pool balance at the start of the game, in MIST
+pub fn fee_curve(parameters: &FeeParameters, guesses: u64, initial_pool: u64) -> Vec<FeeTier> {
+    let mut tiers: Vec<FeeTier> = Vec::new();
+    let mut fee = parameters.starting_fee;
+    let mut treasury_pool_balance = initial_pool;
+    let mut protocol_revenue = 0u64;
+    for guess in 1..=guesses {
+        let (treasury_share, protocol_share) = parameters.split_fee(fee);
+        treasury_pool_balance = treasury_pool_balance.saturating_add(treasury_share);
+        protocol_revenue = protocol_revenue.saturating_add(protocol_share);
+        match tiers.last_mut() {
+            Some(tier) if tier.fee == fee => {
+                tier.last_guess = guess;
+                tier.treasury_pool_balance = treasury_pool_balance;
+                tier.protocol_revenue = protocol_revenue;
+            }
+            _ => tiers.push(FeeTier {
+                first_guess: guess,
+                last_guess: guess,
+                fee,
+                treasury_pool_balance,
+                protocol_revenue,
+            }),
+        }
+        fee = parameters.next_fee(fee, guess);
+    }
+    tiers
+}
+
+/// How players arrive and win, in the Monte Carlo runs
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerModel {
+    /// The mean number of guesses per hour at the starting fee. Guesses arrive as a Poisson
+    /// process, so the time between guesses is exponentially distributed
+    pub arrival_rate_per_hour: f64,
+
+    /// The probability that a guess wins, at the starting fee
+    pub win_probability: f64,
+
+    /// The increase of the win probability at every fee update, in per mille, as hints
+    /// narrow the secret down
+    pub win_probability_increase_per_mille: u64,
+
+    /// How much the arrival rate drops as the fee grows: at fee `f`, guesses arrive at
+    /// `arrival_rate_per_hour * (starting_fee / f) ^ price_elasticity`
+    pub price_elasticity: f64,
+}
+
+/// The outcome of a Monte Carlo run, a simulated game
+#[derive(Clone, Debug)]
+pub struct RunOutcome {
+    /// The index of the run, starting at 0
+    pub run: u64,
+
+    /// The number of guesses made
+    pub guesses: u64,
+
+    /// Whether the game was won within the guess horizon
+    pub won: bool,
+
+    /// How long the game lasted, in hours
+    pub hours: f64,
+
+    /// The fee of the last guess, in MIST
+    pub final_fee: u64,
+
+    /// The treasury pool balance at the end of the run (paid out if won), in MIST
+    pub treasury_pool_balance: u64,
+
+    /// The protocol revenue of the game, in MIST
+    pub protocol_revenue: u64,
+}
+
+/// Simulates a game of at most `max_guesses` guesses
+fn simulate_run(
+    run: u64,
+    parameters: &FeeParameters,
+    model: &PlayerModel,
+    max_guesses: u64,
+    initial_pool: u64,
+    rng: &mut StdRng,
+) -> RunOutcome {
+    let mut outcome = RunOutcome {
+        run,
+        guesses: 0,
+        won: false,
+        hours: 0.0,
+        final_fee: parameters.starting_fee,
+        treasury_pool_balance: initial_pool,
+        protocol_revenue: 0,
+    };
+    let mut fee = parameters.starting_fee;
+    let mut win_probability = model.win_probability;
+    for guess in 1..=max_guesses {
+        let arrival_rate = if fee == 0 || parameters.starting_fee == 0 {
+            model.arrival_rate_per_hour
+        } else {
+            model.arrival_rate_per_hour
+                * (parameters.starting_fee as f64 / fee as f64).powf(model.price_elasticity)
+        };
+        if arrival_rate <= 0.0 {
+            break;
+        }
+        // Inverse transform sampling of the exponential distribution
+        outcome.hours += -(1.0 - rng.gen::<f64>()).ln() / arrival_rate;
+
+        let (treasury_share, protocol_share) = parameters.split_fee(fee);
+        outcome.treasury_pool_balance =
+            outcome.treasury_pool_balance.saturating_add(treasury_share);
+        outcome.protocol_revenue = outcome.protocol_revenue.saturating_add(protocol_share);
+        outcome.guesses = guess;
+        outcome.final_fee = fee;
+        if rng.gen::<f64>() < win_probability {
+            outcome.won = true;
+            break;
+        }
+
+        let next_fee = parameters.next_fee(fee, guess);
+        if guess.checked_rem(parameters.update_fee_every_n_guesses) == Some(0) {
+            win_probability = (win_probability
+                * (1.0 + model.win_probability_increase_per_mille as f64 / PER_MILLE as f64))
+                .min(1.0);
+        }
+        fee = next_fee;
+    }
+    outcome
+}
+
+/// Runs `runs` simulated games of at most `max_guesses` guesses.
+///
+/// # Arguments
+/// * `parameters` - The fee parameters
+/// * `model` - How players arrive and win
+/// * `max_guesses` - The guess horizon of each game
+/// * `initial_pool` - The treasury pool balance at the start of each game, in MIST
+/// * `runs` - The number of games
+/// * `seed` - The seed of the random number generator, for reproducible runs
+pub fn monte_carlo(
+    parameters: &FeeParameters,
+    model: &PlayerModel,
+    max_guesses: u64,
+    initial_pool: u64,
+    runs: u64,
+    seed: u64,
+) -> Vec<RunOutcome> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..runs)
+        .map(|run| simulate_run(run, parameters, model, max_guesses, initial_pool, &mut rng))
+        .collect()
+}
+
+/// The distribution of a metric over the Monte Carlo runs
+#[derive(Clone, Debug)]
+pub struct MetricSummary {
+    /// The name of the metric
+    pub metric: &'static str,
+    pub mean: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+impl MetricSummary {
+    fn new(metric: &'static str, mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            if values.is_empty() {
+                return 0.0;
+            }
+            values[((values.len() - 1) as f64 * p).round() as usize]
+        };
+        Self {
+            metric,
+            mean: values.iter().sum::<f64>() / values.len().max(1) as f64,
+            p10: percentile(0.1),
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+        }
+    }
+}
+
+/// Summarizes the Monte Carlo runs
+pub fn summarize(outcomes: &[RunOutcome]) -> Vec<MetricSummary> {
+    let metric = |name, value: fn(&RunOutcome) -> f64| {
+        MetricSummary::new(name, outcomes.iter().map(value).collect())
+    };
+    vec![
+        metric("won", |outcome| f64::from(u8::from(outcome.won))),
+        metric("guesses", |outcome| outcome.guesses as f64),
+        metric("hours", |outcome| outcome.hours),
+        metric("final_fee", |outcome| outcome.final_fee as f64),
+        metric("treasury_pool_balance", |outcome| {
+            outcome.treasury_pool_balance as f64
+        }),
+        metric("protocol_revenue", |outcome| {
+            outcome.protocol_revenue as f64
+        }),
+    ]
+}
+
+/// A guess made on-chain, from a `NewGuessEvent`
+#[derive(Clone, Debug, Deserialize)]
+pub struct HistoricalGuess {
+    /// The fee paid for the guess, in MIST
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub fee: u64,
+
+    /// The number of guesses of the game, this one included
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub guess_count: u64,
+
+    /// The game the guess was made in
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub guess_game_id: u64,
+
+    /// When the guess was made, in milliseconds since the Unix epoch
+    #[serde(skip)]
+    pub timestamp_ms: Option<u64>,
+}
+
+/// Deserializes the string encoded `u64`s of the Move events
+fn deserialize_string_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+/// The player model estimated from historical guesses
+#[derive(Clone, Debug, Default)]
+pub struct Calibration {
+    /// The number of guesses calibrated on
+    pub guesses: usize,
+
+    /// The number of games which ended with a payout
+    pub games_won: usize,
+
+    /// The time between the first and the last guess, in hours
+    pub hours: f64,
+
+    /// The mean number of guesses per hour, if the guesses span some time
+    pub arrival_rate_per_hour: Option<f64>,
+
+    /// The share of guesses which won a game, if any game was won
+    pub win_probability: Option<f64>,
+}
+
+/// Estimates the player model from historical guesses and payouts.
+///
+/// The arrival rate is the number of guesses per hour between the first and the last
+/// guess, and the win probability the number of games won per guess, the maximum likelihood
+/// estimate of a geometric distribution. A game is won if the treasury pool was paid out
+/// after its first guess and before the first guess of the next game, so that games ended
+/// otherwise (e.g. deactivated by an admin) are not counted as won.
+///
+/// # Arguments
+/// * `history` - The guesses, see `SuiClientContext::guess_history`
+/// * `payouts_ms` - When the treasury pool was paid out, in milliseconds since the Unix
+///   epoch, see `SuiClientContext::payout_history`
+pub fn calibrate(history: &[HistoricalGuess], payouts_ms: &[u64]) -> Calibration {
+    let timestamps = history
+        .iter()
+        .filter_map(|guess| guess.timestamp_ms)
+        .collect::<Vec<_>>();
+    let hours = match (timestamps.iter().min(), timestamps.iter().max()) {
+        (Some(first), Some(last)) => (last - first) as f64 / MS_PER_HOUR,
+        _ => 0.0,
+    };
+    // The time of the first guess of each game
+    let mut game_starts = BTreeMap::new();
+    for guess in history {
+        if let Some(timestamp_ms) = guess.timestamp_ms {
+            game_starts
+                .entry(guess.guess_game_id)
+                .and_modify(|start: &mut u64| *start = (*start).min(timestamp_ms))
+                .or_insert(timestamp_ms);
+        }
+    }
+    let game_starts = game_starts.into_values().collect::<Vec<_>>();
+    let games_won = game_starts
+        .iter()
+        .enumerate()
+        .filter(|(i, start)| {
+            let end = game_starts.get(i + 1).copied().unwrap_or(u64::MAX);
+            payouts_ms
+                .iter()
+                .any(|payout| (**start..end).contains(payout))
+        })
+        .count();
+    Calibration {
+        guesses: history.len(),
+        games_won,
+        hours,
+        arrival_rate_per_hour: (hours > 0.0 && timestamps.len() > 1)
+            .then(|| (timestamps.len() - 1) as f64 / hours),
+        win_probability: (games_won > 0).then(|| games_won as f64 / history.len() as f64),
+    }
+}
+
+/// The format of the simulator's output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned, human readable tables
+    #[default]
+    Table,
+    /// Comma separated values, with a header row
+    Csv,
+}
+
+/// Renders rows of cells as an aligned table, or as CSV
+pub fn render(format: OutputFormat, headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut output = String::new();
+    match format {
+        OutputFormat::Csv => {
+            let _ = writeln!(output, "{}", headers.join(","));
+            for row in rows {
+                let _ = writeln!(output, "{}", row.join(","));
+            }
+        }
+        OutputFormat::Table => {
+            let widths = headers
+                .iter()
+                .enumerate()
+                .map(|(i, header)| {
+                    rows.iter()
+                        .map(|row| row.get(i).map_or(0, String::len))
+                        .max()
+                        .unwrap_or(0)
+                        .max(header.len())
+                })
+                .collect::<Vec<_>>();
+            let line = |cells: Vec<&str>| {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:>width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            let _ = writeln!(output, "{}", line(headers.to_vec()));
+            for row in rows {
+                let _ = writeln!(output, "{}", line(row.iter().map(String::as_str).collect()));
+            }
+        }
+    }
+    output
+}
+
+/// The rows of the fee curve
+pub fn fee_curve_rows(tiers: &[FeeTier]) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let headers = vec![
+        "first_guess",
+        "last_guess",
+        "fee",
+        "treasury_pool_balance",
+        "protocol_revenue",
+    ];
+    let rows = tiers
+        .iter()
+        .map(|tier| {
+            vec![
+                tier.first_guess.to_string(),
+                tier.last_guess.to_string(),
+                tier.fee.to_string(),
+                tier.treasury_pool_balance.to_string(),
+                tier.protocol_revenue.to_string(),
+            ]
+        })
+        .collect();
+    (headers, rows)
+}
+
+/// The rows of the Monte Carlo runs, one per run
+pub fn run_rows(outcomes: &[RunOutcome]) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let headers = vec![
+        "run",
+        "won",
+        "guesses",
+        "hours",
+        "final_fee",
+        "treasury_pool_balance",
+        "protocol_revenue",
+    ];
+    let rows = outcomes
+        .iter()
+        .map(|outcome| {
+            vec![
+                outcome.run.to_string(),
+                outcome.won.to_string(),
+                outcome.guesses.to_string(),
+                format!("{:.2}", outcome.hours),
+                outcome.final_fee.to_string(),
+                outcome.treasury_pool_balance.to_string(),
+                outcome.protocol_revenue.to_string(),
+            ]
+        })
+        .collect();
+    (headers, rows)
+}
+
+/// The rows of the summary of the Monte Carlo runs, one per metric
+pub fn summary_rows(summaries: &[MetricSummary]) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let headers = vec!["metric", "mean", "p10", "p50", "p90"];
+    let rows = summaries
+        .iter()
+        .map(|summary| {
+            vec![
+                summary.metric.to_string(),
+                format!("{:.2}", summary.mean),
+                format!("{:.2}", summary.p10),
+                format!("{:.2}", summary.p50),
+                format!("{:.2}", summary.p90),
+            ]
+        })
+        .collect();
+    (headers, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn parameters() -> FeeParameters {
+        FeeParameters {
+            starting_fee: 1_000,
+            fee_rate_increase_per_guess_per_mille: 100,
+            update_fee_every_n_guesses: 2,
+            protocol_fee_per_mille: 50,
+        }
+    }
+
+    fn guess(guess_game_id: u64, guess_count: u64, timestamp_ms: Option<u64>) -> HistoricalGuess {
+        HistoricalGuess {
+            fee: 1_000,
+            guess_count,
+            guess_game_id,
+            timestamp_ms,
+        }
+    }
+
+    #[test]
+    fn next_fee_increases_every_n_guesses() {
+        let parameters = parameters();
+        assert_eq!(parameters.next_fee(1_000, 1), 1_000);
+        assert_eq!(parameters.next_fee(1_000, 2), 1_100);
+        assert_eq!(parameters.next_fee(1_100, 3), 1_100);
+        assert_eq!(parameters.next_fee(1_100, 4), 1_210);
+    }
+
+    #[test]
+    fn next_fee_never_increases_without_an_update_period() {
+        let parameters = FeeParameters {
+            update_fee_every_n_guesses: 0,
+            ..parameters()
+        };
+        assert_eq!(parameters.next_fee(1_000, 0), 1_000);
+        assert_eq!(parameters.next_fee(1_000, 2), 1_000);
+    }
+
+    #[test]
+    fn next_fee_saturates() {
+        assert_eq!(parameters().next_fee(u64::MAX, 2), u64::MAX);
+    }
+
+    #[test]
+    fn split_fee_rounds_the_protocol_share_down() {
+        let parameters = parameters();
+        assert_eq!(parameters.split_fee(1_000), (950, 50));
+        assert_eq!(parameters.split_fee(1_210), (1_150, 60));
+        assert_eq!(parameters.split_fee(0), (0, 0));
+    }
+
+    #[test]
+    fn split_fee_never_exceeds_the_fee() {
+        let parameters = FeeParameters {
+            protocol_fee_per_mille: 2_000,
+            ..parameters()
+        };
+        assert_eq!(parameters.split_fee(1_000), (0, 1_000));
+    }
+
+    #[test]
+    fn fee_curve_has_one_tier_per_fee() {
+        let tiers = fee_curve(&parameters(), 5, 10_000);
+        let tiers = tiers
+            .iter()
+            .map(|tier| {
+                (
+                    tier.first_guess,
+                    tier.last_guess,
+                    tier.fee,
+                    tier.treasury_pool_balance,
+                    tier.protocol_revenue,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tiers,
+            vec![
+                (1, 2, 1_000, 11_900, 100),
+                (3, 4, 1_100, 13_990, 210),
+                (5, 5, 1_210, 15_140, 270),
+            ]
+        );
+    }
+
+    #[test]
+    fn fee_curve_of_no_guesses_is_empty() {
+        assert!(fee_curve(&parameters(), 0, 10_000).is_empty());
+    }
+
+    #[test]
+    fn calibrate_counts_only_games_ended_with_a_payout() {
+        let history = vec![
+            // Won: paid out before the next game started
+            guess(1, 1, Some(0)),
+            guess(1, 2, Some(HOUR_MS)),
+            // Deactivated without a payout
+            guess(2, 1, Some(2 * HOUR_MS)),
+            guess(2, 2, Some(3 * HOUR_MS)),
+            // Still running
+            guess(3, 1, Some(4 * HOUR_MS)),
+        ];
+        let calibration = calibrate(&history, &[HOUR_MS + 1]);
+        assert_eq!(calibration.guesses, 5);
+        assert_eq!(calibration.games_won, 1);
+        assert_eq!(calibration.hours, 4.0);
+        assert_eq!(calibration.arrival_rate_per_hour, Some(1.0));
+        assert_eq!(calibration.win_probability, Some(0.2));
+    }
+
+    #[test]
+    fn calibrate_counts_a_payout_of_the_latest_game() {
+        let history = vec![guess(1, 1, Some(0)), guess(2, 1, Some(HOUR_MS))];
+        assert_eq!(calibrate(&history, &[]).games_won, 0);
+        assert_eq!(calibrate(&history, &[2 * HOUR_MS]).games_won, 1);
+    }
+
+    #[test]
+    fn calibrate_ignores_payouts_before_the_first_guess() {
+        let history = vec![guess(1, 1, Some(HOUR_MS)), guess(2, 1, Some(2 * HOUR_MS))];
+        let calibration = calibrate(&history, &[0]);
+        assert_eq!(calibration.games_won, 0);
+        assert_eq!(calibration.win_probability, None);
+    }
+
+    #[test]
+    fn calibrate_without_timestamps_estimates_nothing() {
+        let history = vec![guess(1, 1, None), guess(2, 1, None)];
+        let calibration = calibrate(&history, &[0]);
+        assert_eq!(calibration.games_won, 0);
+        assert_eq!(calibration.hours, 0.0);
+        assert_eq!(calibration.arrival_rate_per_hour, None);
+        assert_eq!(calibration.win_probability, None);
+    }
+}
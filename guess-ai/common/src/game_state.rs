@@ -0,0 +1,235 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use sui_sdk::{
+    rpc_types::{SuiObjectDataOptions, SuiParsedData},
+    types::base_types::{ObjectID, SuiAddress},
+    SuiClient,
+};
+use thiserror::Error;
+
+/// The fields of the `GuessAiDb` shared object, as parsed by the full node.
+///
+/// The fields are decoded by name from the content of the object, which the full node parses
+/// with the layout of the on-chain `GuessAiDb` type, rather than from its BCS bytes, whose
+/// decoding would depend on the declaration order of the fields in the contract. A field
+/// renamed or removed in the contract then fails to decode, instead of shifting the others.
+///
+/// The full node renders `u64`s and `Balance`s as strings, and `UID`s as `{"id": ...}`.
+#[derive(Debug, Deserialize)]
+struct GuessAiDb {
+    /// The ID of the object
+    id: Uid,
+    /// The address of the agent allowed to withdraw funds and submit attestations
+    agent_address: SuiAddress,
+    /// The ID of the current game
+    #[serde(deserialize_with = "u64_from_str")]
+    guess_game_id: u64,
+    /// The number of guesses made in the current game
+    #[serde(deserialize_with = "u64_from_str")]
+    guess_count: u64,
+    /// The fee of the next guess, in MIST
+    #[serde(deserialize_with = "u64_from_str")]
+    fee: u64,
+    /// The fee of the first guess of a game, in MIST
+    #[serde(deserialize_with = "u64_from_str")]
+    starting_fee: u64,
+    /// The fee increase applied every `update_fee_every_n_guesses` guesses, in per mille
+    #[serde(deserialize_with = "u64_from_str")]
+    fee_rate_increase_per_guess_per_mille: u64,
+    /// The number of guesses between fee increases
+    #[serde(deserialize_with = "u64_from_str")]
+    update_fee_every_n_guesses: u64,
+    /// The share of each fee kept by the protocol, in per mille
+    #[serde(deserialize_with = "u64_from_str")]
+    protocol_fee_per_mille: u64,
+    /// The balance of the treasury pool, paid out to the winner
+    #[serde(deserialize_with = "u64_from_str")]
+    treasury_pool: u64,
+    /// The balance of the protocol fees collected
+    #[serde(deserialize_with = "u64_from_str")]
+    protocol_fee_pool: u64,
+    /// Whether the game accepts guesses
+    is_active: bool,
+}
+
+/// A `UID`, as rendered by the full node
+#[derive(Debug, Deserialize)]
+struct Uid {
+    id: ObjectID,
+}
+
+/// Deserializes a `u64` rendered as a string by the full node
+fn u64_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+/// The state of the game, as stored in the `guess_ai_db` shared object
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameState {
+    /// The ID of the `guess_ai_db` object
+    pub guess_ai_db: ObjectID,
+    /// The version of the object the state was read at
+    pub version: u64,
+    /// The address of the agent allowed to withdraw funds and submit attestations
+    pub agent_address: SuiAddress,
+    /// The ID of the current game
+    pub guess_game_id: u64,
+    /// The number of guesses made in the current game
+    pub guess_count: u64,
+    /// The fee of the next guess, in MIST
+    pub fee: u64,
+    /// The fee of the first guess of a game, in MIST
+    pub starting_fee: u64,
+    /// The fee increase applied every `update_fee_every_n_guesses` guesses, in per mille
+    pub fee_rate_increase_per_guess_per_mille: u64,
+    /// The number of guesses between fee increases
+    pub update_fee_every_n_guesses: u64,
+    /// The share of each fee kept by the protocol, in per mille
+    pub protocol_fee_per_mille: u64,
+    /// The balance of the treasury pool, paid out to the winner, in MIST
+    pub treasury_pool_balance: u64,
+    /// The balance of the protocol fees collected, in MIST
+    pub protocol_fee_pool_balance: u64,
+    /// Whether the game accepts guesses
+    pub is_active: bool,
+}
+
+impl GameState {
+    /// Decodes the state from the fields of the `guess_ai_db` object, as parsed by the full node
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GameStateError::DecodeError` if a field is missing or has an unexpected type
+    pub fn from_fields(fields: Value, version: u64) -> Result<Self> {
+        let db: GuessAiDb = serde_json::from_value(fields)?;
+        Ok(Self {
+            guess_ai_db: db.id.id,
+            version,
+            agent_address: db.agent_address,
+            guess_game_id: db.guess_game_id,
+            guess_count: db.guess_count,
+            fee: db.fee,
+            starting_fee: db.starting_fee,
+            fee_rate_increase_per_guess_per_mille: db.fee_rate_increase_per_guess_per_mille,
+            update_fee_every_n_guesses: db.update_fee_every_n_guesses,
+            protocol_fee_per_mille: db.protocol_fee_per_mille,
+            treasury_pool_balance: db.treasury_pool,
+            protocol_fee_pool_balance: db.protocol_fee_pool,
+            is_active: db.is_active,
+        })
+    }
+}
+
+/// Fetches and decodes the state of the game from the `guess_ai_db` shared object.
+///
+/// # Arguments
+///
+/// * `client` - The Sui client to read the object with
+/// * `guess_ai_db` - The ID of the `guess_ai_db` object
+///
+/// # Errors
+///
+/// Returns a `GameStateError` if the object cannot be fetched, is not a Move object,
+/// or cannot be decoded
+pub async fn read_game_state(client: &SuiClient, guess_ai_db: ObjectID) -> Result<GameState> {
+    let object = client
+        .read_api()
+        .get_object_with_options(guess_ai_db, SuiObjectDataOptions::new().with_content())
+        .await?;
+    let data = object
+        .data
+        .ok_or(GameStateError::ObjectNotFound(guess_ai_db))?;
+    match data.content {
+        Some(SuiParsedData::MoveObject(object)) => {
+            GameState::from_fields(object.fields.to_json_value(), data.version.value())
+        }
+        _ => Err(GameStateError::NotMoveObject(guess_ai_db)),
+    }
+}
+
+type Result<T> = std::result::Result<T, GameStateError>;
+
+#[derive(Debug, Error)]
+pub enum GameStateError {
+    #[error("Failed to fetch the game db object: {0}")]
+    RpcError(#[from] sui_sdk::error::Error),
+    #[error("Game db object {0} not found")]
+    ObjectNotFound(ObjectID),
+    #[error("Game db object {0} is not a Move object")]
+    NotMoveObject(ObjectID),
+    #[error("Failed to decode the game db object: {0}")]
+    DecodeError(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// The fields of a `GuessAiDb` object, as rendered by `sui_getObject` with `showContent`
+    fn guess_ai_db_fields() -> Value {
+        json!({
+            "id": {
+                "id": "0x6a0d3ce0d4b41cd7bd8e0a3e5b8e1e52b1c4a1e0e2b67f1e9e2f26f1b0dbb8a4"
+            },
+            "agent_address": "0x1d5d3a7e1f0b5c9f64b1b4e0c3c8d5a4d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7",
+            "guess_game_id": "2",
+            "guess_count": "17",
+            "fee": "1210000000",
+            "starting_fee": "1000000000",
+            "fee_rate_increase_per_guess_per_mille": "100",
+            "update_fee_every_n_guesses": "10",
+            "protocol_fee_per_mille": "50",
+            "treasury_pool": "16150000000",
+            "protocol_fee_pool": "850000000",
+            "is_active": true,
+            "public_key_bytes": [1, 2, 3],
+            "tdx_quote_v4": []
+        })
+    }
+
+    #[test]
+    fn fields_are_decoded_by_name() {
+        let game_state = GameState::from_fields(guess_ai_db_fields(), 42).unwrap();
+
+        assert_eq!(
+            game_state,
+            GameState {
+                guess_ai_db: ObjectID::from_hex_literal(
+                    "0x6a0d3ce0d4b41cd7bd8e0a3e5b8e1e52b1c4a1e0e2b67f1e9e2f26f1b0dbb8a4"
+                )
+                .unwrap(),
+                version: 42,
+                agent_address: SuiAddress::from_str(
+                    "0x1d5d3a7e1f0b5c9f64b1b4e0c3c8d5a4d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7"
+                )
+                .unwrap(),
+                guess_game_id: 2,
+                guess_count: 17,
+                fee: 1_210_000_000,
+                starting_fee: 1_000_000_000,
+                fee_rate_increase_per_guess_per_mille: 100,
+                update_fee_every_n_guesses: 10,
+                protocol_fee_per_mille: 50,
+                treasury_pool_balance: 16_150_000_000,
+                protocol_fee_pool_balance: 850_000_000,
+                is_active: true,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        let mut fields = guess_ai_db_fields();
+        fields.as_object_mut().unwrap().remove("treasury_pool");
+
+        let error = GameState::from_fields(fields, 42).unwrap_err();
+
+        assert!(error.to_string().contains("treasury_pool"));
+    }
+}
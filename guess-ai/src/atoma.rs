@@ -1,16 +1,31 @@
-use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use std::{
+    collections::HashMap,
+    future::Future,
+    time::{Duration, SystemTime},
+};
+
 use base64::engine::{general_purpose::STANDARD, Engine};
-use hkdf::Hkdf;
-use serde::Deserialize;
-use sha2::Sha256;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
-use tracing::{error, info, instrument};
+use tokio::{sync::RwLock, time::Instant};
+use tracing::{error, info, instrument, warn};
 use x25519_dalek::{PublicKey, StaticSecret};
 
-use crate::types::{ChatCompletionRequest, ChatCompletionResponse, ConfidentialComputeRequest};
+use crate::{
+    envelope::{self, CipherSuite, EnvelopeError, EnvelopeParams, NONCE_SIZE},
+    secrets::Secret,
+    tools::ToolRegistry,
+    types::{
+        ChatCompletionRequest, ChatCompletionResponse, CompletionUsage, ConfidentialComputeRequest,
+        CreateEmbeddingRequest, CreateEmbeddingResponse, ToolChoice, ToolChoiceMode,
+    },
+};
 
 #[cfg(feature = "confidential-compute")]
-use crate::types::ConfidentialComputeResponse;
+use crate::types::{ConfidentialComputeResponse, EmbeddingUsage, Usage};
+
+/// The base URL of the Atoma API
+const ATOMA_API_URL: &str = "https://api.atoma.network";
 
 /// The header key for the authorization header
 const AUTHORIZATION: &str = "Authorization";
@@ -24,23 +39,46 @@ const PUBLIC_KEY_SIZE: usize = 32;
 /// The maximum number of compute units to be used for the request
 const MAX_COMPUTE_UNITS: u64 = 8_192;
 
-/// The size of the nonce in bytes
-const NONCE_SIZE: usize = 12;
+/// The approximate number of prompt bytes per token, used to estimate compute units
+const APPROX_BYTES_PER_TOKEN: usize = 4;
 
 /// The size of the salt in bytes
 const SALT_SIZE: usize = 16;
 
+/// The maximum number of model turns in a tool calling loop
+const MAX_TOOL_CALLING_ITERATIONS: usize = 8;
+
+/// The header key for the retry-after header
+const RETRY_AFTER: &str = "Retry-After";
+
 /// The result type for the Atoma SDK
 type Result<T> = std::result::Result<T, AtomaSdkError>;
 
-/// The response structure for the nodes/models/retrieve endpoint
+/// The error body returned by the Atoma API for non-success responses
 #[derive(Debug, Deserialize)]
+struct AtomaErrorResponse {
+    /// The error details
+    error: AtomaErrorDetails,
+}
+
+/// The details of an error returned by the Atoma API
+#[derive(Debug, Deserialize)]
+struct AtomaErrorDetails {
+    /// The error code, e.g. `AUTH_ERROR` or `BALANCE_ERROR`
+    #[serde(default)]
+    code: Option<String>,
+
+    /// A human readable description of the error
+    message: String,
+}
+
+/// The response structure for the nodes/models/retrieve endpoint
+#[derive(Clone, Debug, Deserialize)]
 struct NodesModelsRetrieveResponse {
     /// The shared secret public key for the node, base64 encoded
     public_key: String,
 
     /// The small ID of the node
-    #[allow(unused)]
     node_small_id: u64,
 
     /// The stack entry digest for the node
@@ -49,30 +87,214 @@ struct NodesModelsRetrieveResponse {
 
     /// The small ID of the stack for the node
     stack_small_id: u64,
+
+    /// The confidential compute envelope versions supported by the node.
+    /// Nodes that predate envelope versioning don't advertise any
+    #[serde(default)]
+    envelope_versions: Option<Vec<u8>>,
+
+    /// The cipher suites supported by the node
+    #[serde(default)]
+    cipher_suites: Option<Vec<CipherSuite>>,
+}
+
+/// Transport configuration for the Atoma SDK
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AtomaTransportConfig {
+    /// Timeout for establishing a connection to the Atoma API, in seconds
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: u64,
+
+    /// Timeout for each individual request to the Atoma API, in seconds
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
+
+    /// Maximum number of retries for a failed request
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+
+    /// Backoff before the first retry, in milliseconds. It doubles on every subsequent retry
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff: u64,
+
+    /// Maximum backoff between retries, in milliseconds
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: u64,
+
+    /// How long a node retrieved for a model is reused before being retrieved again, in seconds
+    #[serde(default = "default_node_cache_ttl")]
+    pub node_cache_ttl: u64,
+
+    /// How long a node that failed is avoided, in seconds
+    #[serde(default = "default_node_failure_cooldown")]
+    pub node_failure_cooldown: u64,
+
+    /// The preferred cipher suite of the confidential compute envelope, used if the node
+    /// supports it
+    #[serde(default)]
+    pub cipher_suite: CipherSuite,
+}
+
+impl Default for AtomaTransportConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: default_connect_timeout(),
+            request_timeout: default_request_timeout(),
+            max_retries: default_max_retries(),
+            initial_backoff: default_initial_backoff(),
+            max_backoff: default_max_backoff(),
+            node_cache_ttl: default_node_cache_ttl(),
+            node_failure_cooldown: default_node_failure_cooldown(),
+            cipher_suite: CipherSuite::default(),
+        }
+    }
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_request_timeout() -> u64 {
+    120
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff() -> u64 {
+    250
+}
+
+fn default_max_backoff() -> u64 {
+    8_000
+}
+
+fn default_node_cache_ttl() -> u64 {
+    300
+}
+
+fn default_node_failure_cooldown() -> u64 {
+    60
 }
 
 /// AtomaSdk provides an interface for interacting with the Atoma API
 ///
 /// This struct holds the necessary credentials and configuration
 /// for making requests to the Atoma service.
+///
+/// For offline development, it can instead target a local OpenAI-compatible server
+/// (see `AtomaSdk::local`), in which case requests skip the confidential compute envelope.
 pub struct AtomaSdk {
     /// API key used for authentication with the Atoma service
-    api_key: String,
+    api_key: Secret,
+    /// The base URL of the inference backend
+    api_url: String,
+    /// Whether the backend is a local OpenAI-compatible server rather than the Atoma API
+    is_local: bool,
     /// The model identifier to be used for API requests
     model: String,
+    /// The HTTP client, shared by all requests so that connections are pooled
+    client: reqwest::Client,
+    /// The transport configuration
+    transport_config: AtomaTransportConfig,
+    /// The node serving each model, together with the instant it was retrieved at
+    nodes_cache: RwLock<HashMap<String, (NodesModelsRetrieveResponse, Instant)>>,
+    /// The nodes that recently failed, by small ID, together with the instant they failed at
+    failed_nodes: RwLock<HashMap<u64, Instant>>,
 }
 
 impl AtomaSdk {
     /// Constructor
-    pub fn new(api_key: String, model: String) -> Self {
-        Self { api_key, model }
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError::RequestNodePublicUrlError` if the HTTP client cannot be built,
+    /// or `AtomaSdkError::EnvelopeSelfTestError` if the confidential compute envelope doesn't
+    /// match its known-answer vectors.
+    pub fn new(
+        api_key: Secret,
+        model: String,
+        transport_config: AtomaTransportConfig,
+    ) -> Result<Self> {
+        Self::build(
+            api_key,
+            ATOMA_API_URL.to_string(),
+            false,
+            model,
+            transport_config,
+        )
+    }
+
+    /// Creates an SDK that targets a local OpenAI-compatible server, such as llama.cpp server,
+    /// instead of the Atoma API.
+    ///
+    /// Requests are sent in plain text to `{base_url}/v1/...`: the confidential compute
+    /// methods skip the encryption envelope and the response signature verification.
+    /// This is meant for offline development only, and the configuration refuses it on
+    /// mainnet (see [`crate::config::InferenceBackendConfig::Local`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError::RequestNodePublicUrlError` if the HTTP client cannot be built.
+    pub fn local(
+        base_url: String,
+        api_key: Option<Secret>,
+        model: String,
+        transport_config: AtomaTransportConfig,
+    ) -> Result<Self> {
+        Self::build(
+            api_key.unwrap_or_default(),
+            base_url.trim_end_matches('/').to_string(),
+            true,
+            model,
+            transport_config,
+        )
+    }
+
+    fn build(
+        api_key: Secret,
+        api_url: String,
+        is_local: bool,
+        model: String,
+        transport_config: AtomaTransportConfig,
+    ) -> Result<Self> {
+        #[cfg(feature = "confidential-compute")]
+        envelope::verify_known_answer_vectors()?;
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(transport_config.connect_timeout))
+            .build()?;
+        Ok(Self {
+            api_key,
+            api_url,
+            is_local,
+            model,
+            client,
+            transport_config,
+            nodes_cache: RwLock::new(HashMap::new()),
+            failed_nodes: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// The model identifier to be used for API requests, unless a request specifies another one
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Whether the SDK targets a local OpenAI-compatible server, without confidential compute
+    pub fn is_local(&self) -> bool {
+        self.is_local
     }
 
     /// Requests the public URL and associated information for a node from the Atoma API
     ///
     /// This method makes an authenticated GET request to the Atoma API to retrieve
     /// information about a specific node model, including its public key and
-    /// various identifiers.
+    /// various identifiers. Transient failures are retried with exponential backoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model for which a node should be retrieved
     ///
     /// # Returns
     ///
@@ -86,6 +308,8 @@ impl AtomaSdk {
     ///
     /// Returns `AtomaSdkError::RequestNodePublicUrlError` if:
     /// - The HTTP request fails
+    ///
+    /// Returns the `AtomaSdkError` variant matching the error body if:
     /// - The server returns a non-success status code
     ///
     /// Returns `AtomaSdkError::ParseResponseError` if:
@@ -95,46 +319,56 @@ impl AtomaSdk {
         name = "request_node_public_url",
         skip(self),
         fields(
-            model = self.model,
+            model = model,
         )
     )]
-    pub async fn request_node_public_url(&self) -> Result<NodesModelsRetrieveResponse> {
-        let client = reqwest::Client::new();
-        let response = client
-            .get(format!(
-                "https://api.atoma.network/v1/nodes/models/{}",
-                self.model
-            ))
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key))
-            .send()
-            .await?;
+    pub async fn request_node_public_url(
+        &self,
+        model: &str,
+    ) -> Result<NodesModelsRetrieveResponse> {
+        self.with_retries("/v1/nodes/models/{}", || async move {
+            let response = self
+                .client
+                .get(format!("{}/v1/nodes/models/{model}", self.api_url))
+                .header(
+                    AUTHORIZATION,
+                    format!("Bearer {}", self.api_key.expose_secret()),
+                )
+                .timeout(self.request_timeout())
+                .send()
+                .await?;
 
-        info!(
-            target = "atoma-client",
-            method = "GET",
-            handle = "/v1/nodes/models/{}",
-            model = self.model,
-            "Response: {:?}",
-            response
-        );
+            info!(
+                target = "atoma-client",
+                method = "GET",
+                handle = "/v1/nodes/models/{}",
+                model = model,
+                "Response: {:?}",
+                response
+            );
 
-        if !response.status().is_success() {
-            return Err(AtomaSdkError::RequestNodePublicUrlError(
-                response.error_for_status().unwrap_err(),
-            ));
-        }
+            if !response.status().is_success() {
+                return Err(AtomaSdkError::from_response(response).await);
+            }
 
-        Ok(response.json::<NodesModelsRetrieveResponse>().await?)
+            Ok(response.json::<NodesModelsRetrieveResponse>().await?)
+        })
+        .await
     }
 
     /// Sends an encrypted chat completion request to the Atoma API with end-to-end encryption
     ///
     /// This method provides a secure way to interact with the chat completion API by:
-    /// 1. Retrieving the node's public key
+    /// 1. Retrieving the node's public key (cached per model)
     /// 2. Establishing a shared secret using Diffie-Hellman key exchange
     /// 3. Encrypting the request with AES-GCM
     /// 4. Verifying the response's integrity and authenticity
     ///
+    /// If the node fails to serve the request, or returns a response that cannot be
+    /// decrypted or verified, the node is avoided for a cooldown period, so that the next
+    /// request goes to another node. The request itself is only retried (with a fresh nonce
+    /// and salt) when it was certainly not processed, see `AtomaSdkError::is_retryable`.
+    ///
     /// # Arguments
     ///
     /// * `client_private_key` - The client's X25519 private key for establishing the shared secret
@@ -146,7 +380,7 @@ impl AtomaSdk {
     ///
     /// # Errors
     ///
-    /// Returns `AtomaSdkError` if:
+    /// Returns `AtomaSdkError` if, after all retries:
     /// - Failed to retrieve node's public URL
     /// - Failed to decode the node's public key
     /// - Failed to encrypt the request
@@ -166,7 +400,7 @@ impl AtomaSdk {
         name = "confidential/chat/completions",
         skip_all,
         fields(
-            model = self.model,
+            model = request.model,
         )
     )]
     #[cfg(feature = "confidential-compute")]
@@ -175,12 +409,77 @@ impl AtomaSdk {
         client_private_key: &StaticSecret,
         request: ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse> {
-        let NodesModelsRetrieveResponse {
-            public_key,
-            stack_small_id,
-            ..
-        } = self.request_node_public_url().await?;
-        let node_public_key = STANDARD.decode(public_key)?;
+        if self.is_local {
+            return self
+                .send_plain_request("/v1/chat/completions", &request)
+                .await;
+        }
+        let request = &request;
+        self.with_retries("/v1/confidential/chat/completions", || async move {
+            let node = self.select_node(&request.model).await?;
+            match self
+                .send_confidential_chat_completions(client_private_key, &node, request.clone())
+                .await
+            {
+                Err(e) if e.is_node_failure() => {
+                    self.mark_node_failed(&request.model, node.node_small_id)
+                        .await;
+                    Err(e)
+                }
+                result => result,
+            }
+        })
+        .await
+    }
+
+    /// Sends a single encrypted chat completion request to the given node
+    #[cfg(feature = "confidential-compute")]
+    async fn send_confidential_chat_completions(
+        &self,
+        client_private_key: &StaticSecret,
+        node: &NodesModelsRetrieveResponse,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse> {
+        let num_compute_units = utils::estimate_compute_units(&request);
+        let (mut response_body, usage) = self
+            .send_confidential_request::<_, ChatCompletionResponse>(
+                client_private_key,
+                node,
+                "/v1/confidential/chat/completions",
+                request.model.clone(),
+                &request,
+                num_compute_units,
+            )
+            .await?;
+        // NOTE: The usage is only filled in after the response hash has been verified,
+        // as it is not part of the signed response body.
+        if response_body.usage.is_none() {
+            response_body.usage = usage.map(CompletionUsage::from);
+        }
+        Ok(response_body)
+    }
+
+    /// Encrypts `request` for the given node, sends it to the confidential endpoint `handle`,
+    /// then decrypts and verifies the response.
+    ///
+    /// Returns the verified response body, along with the usage reported by the node.
+    #[cfg(feature = "confidential-compute")]
+    async fn send_confidential_request<T, R>(
+        &self,
+        client_private_key: &StaticSecret,
+        node: &NodesModelsRetrieveResponse,
+        handle: &str,
+        model: String,
+        request: &T,
+        num_compute_units: u64,
+    ) -> Result<(R, Option<Usage>)>
+    where
+        T: Serialize,
+        R: Serialize + DeserializeOwned,
+    {
+        let node_public_key = STANDARD
+            .decode(&node.public_key)
+            .map_err(AtomaSdkError::DecodeNodePublicKeyError)?;
         let nonce = rand::random::<[u8; NONCE_SIZE]>();
         let salt = rand::random::<[u8; SALT_SIZE]>();
 
@@ -192,28 +491,37 @@ impl AtomaSdk {
                 ))
             })?;
         let node_public_key = PublicKey::from(node_public_key_bytes);
-        let confidential_compute_request = utils::encrypt_chat_completions_request(
+        let params = EnvelopeParams::negotiate(
+            node.envelope_versions.as_deref(),
+            node.cipher_suites.as_deref(),
+            self.transport_config.cipher_suite,
+        );
+        let confidential_compute_request = utils::encrypt_request(
             request,
+            &params,
             client_private_key,
             &node_public_key,
-            self.model.clone(),
+            model,
             nonce,
             salt,
-            stack_small_id,
+            node.stack_small_id,
+            num_compute_units,
         )?;
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://api.atoma.network/v1/confidential/chat/completions")
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key))
+        let response = self
+            .client
+            .post(format!("{}{handle}", self.api_url))
+            .header(
+                AUTHORIZATION,
+                format!("Bearer {}", self.api_key.expose_secret()),
+            )
+            .timeout(self.request_timeout())
             .json(&confidential_compute_request)
             .send()
             .await?;
 
         if !response.status().is_success() {
-            return Err(AtomaSdkError::RequestNodePublicUrlError(
-                response.error_for_status().unwrap_err(),
-            ));
+            return Err(AtomaSdkError::from_response(response).await);
         }
 
         let ConfidentialComputeResponse {
@@ -221,10 +529,17 @@ impl AtomaSdk {
             nonce,
             signature,
             response_hash,
-            ..
+            usage,
+            envelope_version,
         } = response.json::<ConfidentialComputeResponse>().await?;
-        let response_ciphertext = STANDARD.decode(ciphertext)?;
-        let nonce = STANDARD.decode(nonce)?;
+        if envelope_version.is_some_and(|version| Some(version) != params.wire_version()) {
+            return Err(AtomaSdkError::DecryptResponseError(format!(
+                "Node answered with envelope version {envelope_version:?}, expected {:?}",
+                params.wire_version()
+            )));
+        }
+        let response_ciphertext = STANDARD.decode(ciphertext).map_err(decode_response_error)?;
+        let nonce = STANDARD.decode(nonce).map_err(decode_response_error)?;
         let nonce = nonce.try_into().map_err(|n: Vec<u8>| {
             AtomaSdkError::InvalidNonceError(format!(
                 "Failed to decode nonce, length is not 12, it is: {}",
@@ -234,12 +549,14 @@ impl AtomaSdk {
         let response_hash = response_hash
             .as_ref()
             .map(|s| STANDARD.decode(s))
-            .transpose()?
+            .transpose()
+            .map_err(decode_response_error)?
             .map(|s| s.try_into())
             .transpose()
             .map_err(|_| AtomaSdkError::InvalidPayloadHashLengthError)?;
-        let response_body = utils::decrypt_chat_completions_response(
+        let response_body: R = utils::decrypt_response(
             response_ciphertext,
+            &params,
             client_private_key,
             &node_public_key,
             nonce,
@@ -250,11 +567,13 @@ impl AtomaSdk {
             response_hash,
             signature.as_ref().map(|s| s.as_str()),
         )?;
-        Ok(response_body)
+        Ok((response_body, usage))
     }
 
     /// Sends a chat completion request to the Atoma API
     ///
+    /// Transient failures are retried with exponential backoff.
+    ///
     /// # Arguments
     ///
     /// * `request` - The chat completion request to be encrypted and sent
@@ -266,13 +585,14 @@ impl AtomaSdk {
     /// # Errors
     ///
     /// Returns `AtomaSdkError` if:
-    /// - HTTP request failed
+    /// - HTTP request failed, after all retries
+    /// - The server returned a non-success status code, see `AtomaSdkError::from_response`
     #[instrument(
         level = "info",
         name = "chat/completions",
         skip_all,
         fields(
-            model = self.model,
+            model = request.model,
         )
     )]
     #[cfg(not(feature = "confidential-compute"))]
@@ -280,31 +600,450 @@ impl AtomaSdk {
         &self,
         request: ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse> {
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://api.atoma.network/v1/chat/completions")
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await?;
+        self.send_plain_request("/v1/chat/completions", &request)
+            .await
+    }
 
-        if !response.status().is_success() {
-            return Err(AtomaSdkError::RequestNodePublicUrlError(
-                response.error_for_status().unwrap_err(),
-            ));
+    /// Sends an encrypted embeddings request to the Atoma API with end-to-end encryption
+    ///
+    /// The request is encrypted, and the response decrypted and verified, exactly as for
+    /// `confidential_chat_completions`, including the node failover.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_private_key` - The client's X25519 private key for establishing the shared secret
+    /// * `request` - The embeddings request to be encrypted and sent
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the decrypted `CreateEmbeddingResponse` if successful.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError` if, after all retries:
+    /// - Failed to retrieve node's public URL
+    /// - Failed to encrypt the request
+    /// - HTTP request failed
+    /// - Failed to decrypt the response
+    /// - Response signature verification failed
+    #[instrument(
+        level = "info",
+        name = "confidential/embeddings",
+        skip_all,
+        fields(
+            model = request.model,
+        )
+    )]
+    #[cfg(feature = "confidential-compute")]
+    pub async fn confidential_embeddings(
+        &self,
+        client_private_key: &StaticSecret,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse> {
+        if self.is_local {
+            return self.send_plain_request("/v1/embeddings", &request).await;
         }
+        let request = &request;
+        let num_compute_units = utils::estimate_embedding_compute_units(request);
+        self.with_retries("/v1/confidential/embeddings", || async move {
+            let node = self.select_node(&request.model).await?;
+            match self
+                .send_confidential_request::<_, CreateEmbeddingResponse>(
+                    client_private_key,
+                    &node,
+                    "/v1/confidential/embeddings",
+                    request.model.clone(),
+                    request,
+                    num_compute_units,
+                )
+                .await
+            {
+                Ok((mut response_body, usage)) => {
+                    if response_body.usage.is_none() {
+                        response_body.usage = usage.map(EmbeddingUsage::from);
+                    }
+                    Ok(response_body)
+                }
+                Err(e) if e.is_node_failure() => {
+                    self.mark_node_failed(&request.model, node.node_small_id)
+                        .await;
+                    Err(e)
+                }
+                Err(e) => Err(e),
+            }
+        })
+        .await
+    }
 
-        Ok(response.json().await?)
+    /// Sends an embeddings request to the Atoma API
+    ///
+    /// Transient failures are retried with exponential backoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The embeddings request to be sent
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `CreateEmbeddingResponse` if successful.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError` if:
+    /// - HTTP request failed, after all retries
+    /// - The server returned a non-success status code, see `AtomaSdkError::from_response`
+    #[instrument(
+        level = "info",
+        name = "embeddings",
+        skip_all,
+        fields(
+            model = request.model,
+        )
+    )]
+    #[cfg(not(feature = "confidential-compute"))]
+    pub async fn embeddings(
+        &self,
+        request: CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse> {
+        self.send_plain_request("/v1/embeddings", &request).await
+    }
+
+    /// Sends a plain (non-encrypted) request to the endpoint `handle` of the inference backend,
+    /// retrying transient failures with exponential backoff
+    async fn send_plain_request<T, R>(&self, handle: &str, request: &T) -> Result<R>
+    where
+        T: Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        self.with_retries(handle, || async move {
+            let response = self
+                .authorize(self.client.post(format!("{}{handle}", self.api_url)))
+                .timeout(self.request_timeout())
+                .json(request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(AtomaSdkError::from_response(response).await);
+            }
+
+            Ok(response.json().await?)
+        })
+        .await
+    }
+
+    /// Adds the bearer authorization header to a request, unless no API key is set
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.api_key.expose_secret().is_empty() {
+            return request;
+        }
+        request.header(
+            AUTHORIZATION,
+            format!("Bearer {}", self.api_key.expose_secret()),
+        )
+    }
+
+    /// Sends an encrypted chat completion request, letting the model call the tools in `tools`.
+    ///
+    /// Whenever the model answers with tool calls, the registered Rust handlers are executed
+    /// and their results are fed back to the model, through the confidential compute endpoint,
+    /// until the model answers without tool calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_private_key` - The client's X25519 private key for establishing the shared secret
+    /// * `request` - The chat completion request. Its `tools` are replaced by the registry's tools
+    /// * `tools` - The registry of tool handlers the model may call
+    ///
+    /// # Returns
+    ///
+    /// Returns the final `ChatCompletionResponse`, whose usage is the sum of the usage of all
+    /// the model turns.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError` if:
+    /// - Any of the chat completion requests fails, see `confidential_chat_completions`
+    /// - The model is still calling tools after `MAX_TOOL_CALLING_ITERATIONS` turns
+    #[instrument(
+        level = "info",
+        name = "confidential/chat/completions/tools",
+        skip_all,
+        fields(
+            model = request.model,
+        )
+    )]
+    #[cfg(feature = "confidential-compute")]
+    pub async fn confidential_chat_completions_with_tools(
+        &self,
+        client_private_key: &StaticSecret,
+        request: ChatCompletionRequest,
+        tools: &ToolRegistry,
+    ) -> Result<ChatCompletionResponse> {
+        self.run_tool_calling_loop(request, tools, |request| {
+            self.confidential_chat_completions(client_private_key, request)
+        })
+        .await
+    }
+
+    /// Sends a chat completion request, letting the model call the tools in `tools`.
+    ///
+    /// Whenever the model answers with tool calls, the registered Rust handlers are executed
+    /// and their results are fed back to the model, until the model answers without tool calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The chat completion request. Its `tools` are replaced by the registry's tools
+    /// * `tools` - The registry of tool handlers the model may call
+    ///
+    /// # Returns
+    ///
+    /// Returns the final `ChatCompletionResponse`, whose usage is the sum of the usage of all
+    /// the model turns.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError` if:
+    /// - Any of the chat completion requests fails, see `chat_completions`
+    /// - The model is still calling tools after `MAX_TOOL_CALLING_ITERATIONS` turns
+    #[instrument(
+        level = "info",
+        name = "chat/completions/tools",
+        skip_all,
+        fields(
+            model = request.model,
+        )
+    )]
+    #[cfg(not(feature = "confidential-compute"))]
+    pub async fn chat_completions_with_tools(
+        &self,
+        request: ChatCompletionRequest,
+        tools: &ToolRegistry,
+    ) -> Result<ChatCompletionResponse> {
+        self.run_tool_calling_loop(request, tools, |request| self.chat_completions(request))
+            .await
+    }
+
+    /// Runs the tool calling loop, sending each model turn with `send`
+    async fn run_tool_calling_loop<F, Fut>(
+        &self,
+        mut request: ChatCompletionRequest,
+        tools: &ToolRegistry,
+        mut send: F,
+    ) -> Result<ChatCompletionResponse>
+    where
+        F: FnMut(ChatCompletionRequest) -> Fut,
+        Fut: Future<Output = Result<ChatCompletionResponse>>,
+    {
+        request.tools = Some(tools.tools());
+        if request.tool_choice.is_none() {
+            request.tool_choice = Some(ToolChoice::Mode(ToolChoiceMode::Auto));
+        }
+        let mut total_usage: Option<CompletionUsage> = None;
+        for iteration in 0..MAX_TOOL_CALLING_ITERATIONS {
+            let mut response = send(request.clone()).await?;
+            if let Some(usage) = &response.usage {
+                let total_usage = total_usage.get_or_insert_with(CompletionUsage::default);
+                total_usage.prompt_tokens += usage.prompt_tokens;
+                total_usage.completion_tokens += usage.completion_tokens;
+                total_usage.total_tokens += usage.total_tokens;
+            }
+            let tool_calls = response
+                .choices
+                .first()
+                .and_then(|choice| choice.message.tool_calls.clone())
+                .unwrap_or_default();
+            if tool_calls.is_empty() {
+                response.usage = total_usage;
+                return Ok(response);
+            }
+            info!(
+                target = "atoma-client",
+                event = "tool-calls",
+                iteration = iteration,
+                num_tool_calls = tool_calls.len(),
+                "Model requested tool calls"
+            );
+            request.messages.push(response.choices[0].message.clone());
+            for tool_call in &tool_calls {
+                request.messages.push(tools.execute(tool_call).await);
+            }
+        }
+        Err(AtomaSdkError::ToolCallingLimitExceeded(
+            MAX_TOOL_CALLING_ITERATIONS,
+        ))
+    }
+
+    /// Returns the node to use for the given model.
+    ///
+    /// The node is served from the cache while it is fresh and has not failed. Otherwise a
+    /// node is retrieved from the Atoma API, which is asked again (up to `max_retries` times,
+    /// backing off exponentially) if it returns a node that is still cooling down from a
+    /// failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AtomaSdkError::NoHealthyNodeError` if every node retrieved is cooling down
+    /// from a failure, or the error of the node retrieval
+    async fn select_node(&self, model: &str) -> Result<NodesModelsRetrieveResponse> {
+        let cached_node = self.nodes_cache.read().await.get(model).cloned();
+        if let Some((node, retrieved_at)) = cached_node {
+            let node_cache_ttl = Duration::from_secs(self.transport_config.node_cache_ttl);
+            if retrieved_at.elapsed() < node_cache_ttl
+                && !self.is_node_failed(node.node_small_id).await
+            {
+                return Ok(node);
+            }
+        }
+
+        let mut attempt = 0;
+        loop {
+            let node = self.request_node_public_url(model).await?;
+            if !self.is_node_failed(node.node_small_id).await {
+                self.nodes_cache
+                    .write()
+                    .await
+                    .insert(model.to_string(), (node.clone(), Instant::now()));
+                return Ok(node);
+            }
+            if attempt >= self.transport_config.max_retries {
+                return Err(AtomaSdkError::NoHealthyNodeError(model.to_string()));
+            }
+            let backoff = self.backoff(attempt);
+            attempt += 1;
+            warn!(
+                target = "atoma-client",
+                event = "node-failover",
+                model = model,
+                node_small_id = node.node_small_id,
+                attempt = attempt,
+                backoff_millis = backoff.as_millis() as u64,
+                "Retrieved node recently failed, requesting another node"
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    /// Checks whether a node failed within the last `node_failure_cooldown` seconds
+    async fn is_node_failed(&self, node_small_id: u64) -> bool {
+        let node_failure_cooldown =
+            Duration::from_secs(self.transport_config.node_failure_cooldown);
+        self.failed_nodes
+            .read()
+            .await
+            .get(&node_small_id)
+            .is_some_and(|failed_at| failed_at.elapsed() < node_failure_cooldown)
+    }
+
+    /// Marks a node as failed, and evicts it from the cache of the given model
+    async fn mark_node_failed(&self, model: &str, node_small_id: u64) {
+        warn!(
+            target = "atoma-client",
+            event = "node-failure",
+            model = model,
+            node_small_id = node_small_id,
+            "Node failed to serve request, avoiding it for {} seconds",
+            self.transport_config.node_failure_cooldown
+        );
+        self.failed_nodes
+            .write()
+            .await
+            .insert(node_small_id, Instant::now());
+        let mut nodes_cache = self.nodes_cache.write().await;
+        if nodes_cache
+            .get(model)
+            .is_some_and(|(node, _)| node.node_small_id == node_small_id)
+        {
+            nodes_cache.remove(model);
+        }
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error that is not retryable,
+    /// or `max_retries` retries have been made, backing off exponentially between attempts.
+    async fn with_retries<T, F, Fut>(&self, handle: &str, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Err(e) if e.is_retryable() && attempt < self.transport_config.max_retries => {
+                    let backoff = self
+                        .backoff(attempt)
+                        .max(e.retry_after().unwrap_or_default());
+                    attempt += 1;
+                    warn!(
+                        target = "atoma-client",
+                        event = "request-retry",
+                        handle = handle,
+                        attempt = attempt,
+                        backoff_millis = backoff.as_millis() as u64,
+                        "Request failed with error: {e}, retrying"
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Computes the backoff before the retry following `attempt`, with jitter
+    fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .transport_config
+            .initial_backoff
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.transport_config.max_backoff);
+        let jitter = rand::random::<u64>() % (backoff / 2 + 1);
+        Duration::from_millis(backoff / 2 + jitter)
+    }
+
+    /// The timeout for each individual request
+    fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.transport_config.request_timeout)
     }
 }
 
+/// Parses a `Retry-After` header value, either a number of seconds or an HTTP date. A date in
+/// the past means the request can be retried right away
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let retry_at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        retry_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+/// Maps a field of a confidential compute response that is not valid base64 to a decryption
+/// error: unlike the node public key, it is only decoded once the request was processed
+#[cfg(feature = "confidential-compute")]
+fn decode_response_error(error: base64::DecodeError) -> AtomaSdkError {
+    AtomaSdkError::DecryptResponseError(error.to_string())
+}
+
 #[derive(Debug, Error)]
 pub enum AtomaSdkError {
+    #[error("Atoma API error, with status `{status}` and code `{code:?}`: `{message}`")]
+    ApiError {
+        status: u16,
+        code: Option<String>,
+        message: String,
+    },
+
+    #[error("Failed to authenticate with the Atoma API: `{0}`")]
+    AuthenticationError(String),
+
     #[error("Failed to correctly parse public key: `{0}`")]
     CreatePublicKeyError(String),
 
     #[error("Failed to decode node public key: `{0}`")]
-    DecodeNodePublicKeyError(#[from] base64::DecodeError),
+    DecodeNodePublicKeyError(base64::DecodeError),
 
     #[error("Failed to decrypt response: `{0}`")]
     DecryptResponseError(String),
@@ -318,19 +1057,163 @@ pub enum AtomaSdkError {
     #[error("Invalid nonce length: `{0}`")]
     InvalidNonceError(String),
 
+    #[error("Insufficient balance or compute units: `{0}`")]
+    InsufficientBalanceError(String),
+
     #[error("Failed to expand key: `{0}`")]
     KeyExpansionFailed(#[from] hkdf::InvalidLength),
 
+    #[error("Confidential compute envelope self-test failed: `{0}`")]
+    EnvelopeSelfTestError(String),
+
+    #[error("Model is unavailable: `{0}`")]
+    ModelUnavailableError(String),
+
+    #[error("Node failed to serve the request, with status `{status}`: `{message}`")]
+    NodeFailureError { status: u16, message: String },
+
+    #[error("Every node retrieved for model `{0}` recently failed")]
+    NoHealthyNodeError(String),
+
     #[error("Failed to parse response: `{0}`")]
     ParseResponseError(#[from] serde_json::Error),
 
+    #[error("Rate limited by the Atoma API, retry after `{retry_after:?}`: `{message}`")]
+    RateLimitedError {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+
     #[error("Failed to request node public URL: `{0}`")]
     RequestNodePublicUrlError(#[from] reqwest::Error),
 
+    #[error("Model is still calling tools after `{0}` iterations")]
+    ToolCallingLimitExceeded(usize),
+
     #[error("Failed to verify response hash and signature: `{0}`")]
     VerifyResponseHashAndSignatureError(String),
 }
 
+impl From<EnvelopeError> for AtomaSdkError {
+    fn from(error: EnvelopeError) -> Self {
+        match error {
+            EnvelopeError::KeyExpansionFailed(e) => Self::KeyExpansionFailed(e),
+            EnvelopeError::SealError(e) => Self::EncryptRequestError(e),
+            EnvelopeError::OpenError(e) => Self::DecryptResponseError(e),
+            EnvelopeError::KnownAnswerMismatch(e) => Self::EnvelopeSelfTestError(e),
+        }
+    }
+}
+
+impl AtomaSdkError {
+    /// Builds the error matching a non-success response from the Atoma API.
+    ///
+    /// The error body (`{"error": {"code": ..., "message": ...}}`) is parsed when possible,
+    /// and the error is classified from its code, falling back to the HTTP status:
+    /// - `401`, `403` or `AUTH_ERROR` - `AuthenticationError`
+    /// - `402` or `BALANCE_ERROR` - `InsufficientBalanceError`
+    /// - `404`, `NOT_FOUND`, `MODEL_NOT_FOUND` or `UNAVAILABLE_STACKS` - `ModelUnavailableError`
+    /// - `429` or `TOO_MANY_REQUESTS` - `RateLimitedError`, with the `Retry-After` header, in
+    ///   seconds or as an HTTP date (see `parse_retry_after`)
+    /// - `5xx` - `NodeFailureError`
+    /// - anything else - `ApiError`
+    pub(crate) async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return Self::RequestNodePublicUrlError(e),
+        };
+        let (code, message) = match serde_json::from_str::<AtomaErrorResponse>(&body) {
+            Ok(AtomaErrorResponse {
+                error: AtomaErrorDetails { code, message },
+            }) => (code, message),
+            Err(_) => (None, body),
+        };
+        error!(
+            target = "atoma-client",
+            event = "atoma-api-error",
+            status = status.as_u16(),
+            code = ?code,
+            "Atoma API returned an error: {message}"
+        );
+
+        match (status.as_u16(), code.as_deref()) {
+            (_, Some("AUTH_ERROR")) | (401 | 403, _) => Self::AuthenticationError(message),
+            (_, Some("BALANCE_ERROR")) | (402, _) => Self::InsufficientBalanceError(message),
+            (_, Some("NOT_FOUND" | "MODEL_NOT_FOUND" | "UNAVAILABLE_STACKS")) | (404, _) => {
+                Self::ModelUnavailableError(message)
+            }
+            (_, Some("TOO_MANY_REQUESTS")) | (429, _) => Self::RateLimitedError {
+                message,
+                retry_after,
+            },
+            (status, _) if (500..600).contains(&status) => {
+                Self::NodeFailureError { status, message }
+            }
+            (status, _) => Self::ApiError {
+                status,
+                code,
+                message,
+            },
+        }
+    }
+
+    /// Whether the request that failed with this error may be retried.
+    ///
+    /// Only requests that were certainly not processed are retried: connection failures,
+    /// rate limiting, `503 Service Unavailable` responses, and nodes advertising a public key
+    /// the request could not be encrypted for (the retry goes to another node, as the node is
+    /// marked as failed). A timed out request, or a response that could not be decrypted or
+    /// verified, may have been processed (and paid for), so it is not resent; its node is only
+    /// avoided by the next requests, see `is_node_failure`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RequestNodePublicUrlError(e) => e.is_connect(),
+            Self::RateLimitedError { .. } => true,
+            Self::NodeFailureError { status, .. } => *status == 503,
+            Self::CreatePublicKeyError(_) | Self::DecodeNodePublicKeyError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this error is caused by the node that served the request, in which
+    /// case the request should be sent to another node.
+    pub fn is_node_failure(&self) -> bool {
+        match self {
+            Self::NodeFailureError { .. }
+            | Self::CreatePublicKeyError(_)
+            | Self::DecodeNodePublicKeyError(_)
+            | Self::DecryptResponseError(_)
+            | Self::InvalidPayloadHashLengthError
+            | Self::InvalidNonceError(_)
+            | Self::VerifyResponseHashAndSignatureError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this error requires the attention of an operator, e.g. the API key
+    /// was revoked or the account ran out of balance.
+    pub fn requires_operator_attention(&self) -> bool {
+        matches!(
+            self,
+            Self::AuthenticationError(_) | Self::InsufficientBalanceError(_)
+        )
+    }
+
+    /// How long to wait before retrying, as hinted by the Atoma API
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimitedError { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
 pub(crate) mod utils {
     use std::str::FromStr;
 
@@ -370,36 +1253,37 @@ pub(crate) mod utils {
         hasher.finalize()
     }
 
+    /// Encrypts a request body for the confidential compute endpoints
+    ///
+    /// The request key is derived from the X25519 shared secret between the client and
+    /// the node, using HKDF with SHA-256 and the given salt, as specified by the negotiated
+    /// envelope parameters (see `crate::envelope`).
     #[instrument(
         level = "info",
-        name = "encrypt_chat_completions_request",
+        name = "encrypt_request",
         skip_all,
         fields(
             model = model_name,
             stack_small_id = stack_small_id,
         )
     )]
-    pub(crate) fn encrypt_chat_completions_request(
-        request: ChatCompletionRequest,
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn encrypt_request<T: Serialize>(
+        request: &T,
+        params: &EnvelopeParams,
         client_private_key: &StaticSecret,
         node_public_key: &PublicKey,
         model_name: String,
         nonce: [u8; NONCE_SIZE],
         salt: [u8; SALT_SIZE],
         stack_small_id: u64,
+        num_compute_units: u64,
     ) -> Result<ConfidentialComputeRequest> {
-        let shared_secret = client_private_key.diffie_hellman(&node_public_key);
-
-        let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared_secret.as_bytes());
-        let mut symmetric_key = [0u8; 32];
-        hkdf.expand(b"", &mut symmetric_key)?;
-
-        let cipher = Aes256Gcm::new(&symmetric_key.into());
-        let ciphertext = cipher
-            .encrypt(&nonce.into(), serde_json::to_vec(&request)?.as_slice())
-            .map_err(|e| AtomaSdkError::EncryptRequestError(e.to_string()))?;
-        let payload_hash: [u8; PAYLOAD_HASH_SIZE] =
-            utils::blake2b_hash(serde_json::to_vec(&request)?.as_slice()).into();
+        let keys = envelope::derive_keys(params, client_private_key, node_public_key, &salt)?;
+        let plaintext = serde_json::to_vec(request)?;
+        let ciphertext =
+            envelope::seal(params.cipher_suite, &keys.request_key, &nonce, &plaintext)?;
+        let payload_hash: [u8; PAYLOAD_HASH_SIZE] = utils::blake2b_hash(&plaintext).into();
         Ok(ConfidentialComputeRequest {
             nonce: STANDARD.encode(nonce),
             salt: STANDARD.encode(salt),
@@ -410,27 +1294,65 @@ pub(crate) mod utils {
             ciphertext: STANDARD.encode(ciphertext),
             stream: Some(false),
             model_name,
-            num_compute_units: Some(MAX_COMPUTE_UNITS),
+            num_compute_units: Some(num_compute_units),
+            envelope_version: params.wire_version(),
+            cipher_suite: params.wire_cipher_suite(),
         })
     }
 
-    /// Decrypts an encrypted chat completion response using AES-GCM
+    /// Estimates the number of compute units needed for a chat completion request.
+    ///
+    /// The estimate is the approximate number of prompt tokens plus the maximum number of
+    /// completion tokens requested, capped at `MAX_COMPUTE_UNITS`. Requests that do not set
+    /// `max_tokens` reserve `MAX_COMPUTE_UNITS`.
+    pub(crate) fn estimate_compute_units(request: &ChatCompletionRequest) -> u64 {
+        let Some(max_tokens) = request.max_tokens else {
+            return MAX_COMPUTE_UNITS;
+        };
+        let prompt_tokens: usize = request
+            .messages
+            .iter()
+            .map(|message| {
+                message
+                    .content
+                    .as_deref()
+                    .map_or(0, str::len)
+                    .div_ceil(APPROX_BYTES_PER_TOKEN)
+            })
+            .sum();
+        (prompt_tokens as u64 + max_tokens.max(0) as u64).min(MAX_COMPUTE_UNITS)
+    }
+
+    /// Estimates the number of compute units needed for an embeddings request,
+    /// i.e. the approximate number of input tokens, capped at `MAX_COMPUTE_UNITS`.
+    pub(crate) fn estimate_embedding_compute_units(request: &CreateEmbeddingRequest) -> u64 {
+        let input_tokens: usize = request
+            .input
+            .texts()
+            .iter()
+            .map(|text| text.len().div_ceil(APPROX_BYTES_PER_TOKEN))
+            .sum();
+        (input_tokens as u64).clamp(1, MAX_COMPUTE_UNITS)
+    }
+
+    /// Decrypts an encrypted confidential compute response
     ///
     /// This function performs the following steps:
     /// 1. Derives a shared secret using Diffie-Hellman key exchange
-    /// 2. Generates a symmetric key using HKDF with SHA-256
-    /// 3. Decrypts the ciphertext using AES-GCM
-    /// 4. Deserializes the plaintext into a ChatCompletionResponse
+    /// 2. Generates the response key using HKDF with SHA-256
+    /// 3. Decrypts the ciphertext with the negotiated cipher suite
+    /// 4. Deserializes the plaintext into the expected response type
     ///
     /// # Arguments
     /// * `ciphertext` - The encrypted response data
+    /// * `params` - The envelope parameters negotiated for the request
     /// * `client_private_key` - The client's X25519 private key
     /// * `node_public_key` - The node's X25519 public key
-    /// * `nonce` - A 12-byte nonce used for AES-GCM encryption
+    /// * `nonce` - A 12-byte nonce used for the response encryption
     /// * `salt` - A 16-byte salt used for key derivation
     ///
     /// # Returns
-    /// * `Ok(ChatCompletionResponse)` - The decrypted and deserialized response
+    /// * `Ok(T)` - The decrypted and deserialized response
     /// * `Err(AtomaSdkError)` if:
     ///   - Key derivation fails
     ///   - Decryption fails
@@ -439,37 +1361,32 @@ pub(crate) mod utils {
     /// # Security
     /// This function implements several cryptographic security measures:
     /// - Perfect forward secrecy via Diffie-Hellman key exchange
-    /// - Key derivation using HKDF with SHA-256
-    /// - Authenticated encryption using AES-GCM
-    #[instrument(level = "info", name = "decrypt_chat_completions_response", skip_all)]
-    pub(crate) fn decrypt_chat_completions_response(
+    /// - Key derivation using HKDF with SHA-256, with a key separate from the request's
+    ///   from envelope version 2
+    /// - Authenticated encryption using AES-256-GCM or ChaCha20-Poly1305
+    #[instrument(level = "info", name = "decrypt_response", skip_all)]
+    pub(crate) fn decrypt_response<T: DeserializeOwned>(
         ciphertext: Vec<u8>,
+        params: &EnvelopeParams,
         client_private_key: &StaticSecret,
         node_public_key: &PublicKey,
         nonce: [u8; NONCE_SIZE],
         salt: [u8; SALT_SIZE],
-    ) -> Result<ChatCompletionResponse> {
-        let shared_secret = client_private_key.diffie_hellman(&node_public_key);
-
-        let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared_secret.as_bytes());
-        let mut symmetric_key = [0u8; 32];
-        hkdf.expand(b"", &mut symmetric_key)?;
-
-        let cipher = Aes256Gcm::new(&symmetric_key.into());
-        let plaintext = cipher
-            .decrypt(&nonce.into(), ciphertext.as_slice())
-            .map_err(|e| AtomaSdkError::DecryptResponseError(e.to_string()))?;
+    ) -> Result<T> {
+        let keys = envelope::derive_keys(params, client_private_key, node_public_key, &salt)?;
+        let plaintext =
+            envelope::open(params.cipher_suite, &keys.response_key, &nonce, &ciphertext)?;
         Ok(serde_json::from_slice(&plaintext)?)
     }
 
-    /// Verifies the integrity and authenticity of a chat completion response.
+    /// Verifies the integrity and authenticity of a confidential compute response.
     ///
     /// This function performs two critical security checks:
     /// 1. Verifies that the response hash matches the computed hash of the response body
     /// 2. Validates the cryptographic signature of the response
     ///
     /// # Arguments
-    /// * `response_body` - The decrypted chat completion response to verify
+    /// * `response_body` - The decrypted response to verify
     /// * `response_hash` - Optional Blake2b hash of the response body (32 bytes)
     /// * `signature` - Optional base64-encoded signature of the response hash
     ///
@@ -495,8 +1412,8 @@ pub(crate) mod utils {
             signature = ?signature,
         )
     )]
-    pub(crate) fn verify_response_hash_and_signature(
-        response_body: &ChatCompletionResponse,
+    pub(crate) fn verify_response_hash_and_signature<T: Serialize>(
+        response_body: &T,
         response_hash: Option<[u8; PAYLOAD_HASH_SIZE]>,
         signature: Option<&str>,
     ) -> Result<()> {
@@ -607,3 +1524,147 @@ pub(crate) mod utils {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_node_key_errors_fail_over() {
+        let errors = [
+            AtomaSdkError::CreatePublicKeyError("expected length is 32".to_string()),
+            AtomaSdkError::DecodeNodePublicKeyError(base64::DecodeError::InvalidPadding),
+        ];
+        for error in errors {
+            assert!(error.is_retryable(), "{error}");
+            assert!(error.is_node_failure(), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_processed_request_errors_are_not_retried() {
+        let errors = [
+            AtomaSdkError::DecryptResponseError("Invalid padding".to_string()),
+            AtomaSdkError::VerifyResponseHashAndSignatureError("mismatch".to_string()),
+            AtomaSdkError::NodeFailureError {
+                status: 500,
+                message: "internal error".to_string(),
+            },
+        ];
+        for error in errors {
+            assert!(!error.is_retryable(), "{error}");
+            assert!(error.is_node_failure(), "{error}");
+        }
+        let error = AtomaSdkError::NoHealthyNodeError("deepseek".to_string());
+        assert!(!error.is_retryable());
+        assert!(!error.is_node_failure());
+    }
+
+    /// Builds the error matching a response with the given status, `Retry-After` header and
+    /// body
+    async fn error_from(status: u16, retry_after: Option<&str>, body: &str) -> AtomaSdkError {
+        let mut response = axum::http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            response = response.header(RETRY_AFTER, retry_after);
+        }
+        AtomaSdkError::from_response(response.body(body.to_string()).unwrap().into()).await
+    }
+
+    fn atoma_error(code: &str) -> String {
+        json!({ "error": { "code": code, "message": "details" } }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_from_response_classification() {
+        type Check = fn(&AtomaSdkError) -> bool;
+        let cases: [(u16, String, Check); 10] = [
+            (
+                401,
+                "Unauthorized".to_string(),
+                |e| matches!(e, AtomaSdkError::AuthenticationError(m) if m == "Unauthorized"),
+            ),
+            (403, "Forbidden".to_string(), |e| {
+                matches!(e, AtomaSdkError::AuthenticationError(_))
+            }),
+            (
+                400,
+                atoma_error("AUTH_ERROR"),
+                |e| matches!(e, AtomaSdkError::AuthenticationError(m) if m == "details"),
+            ),
+            (402, "Payment Required".to_string(), |e| {
+                matches!(e, AtomaSdkError::InsufficientBalanceError(_))
+            }),
+            (404, "Not Found".to_string(), |e| {
+                matches!(e, AtomaSdkError::ModelUnavailableError(_))
+            }),
+            (
+                400,
+                atoma_error("MODEL_NOT_FOUND"),
+                |e| matches!(e, AtomaSdkError::ModelUnavailableError(m) if m == "details"),
+            ),
+            (429, "Too Many Requests".to_string(), |e| {
+                matches!(
+                    e,
+                    AtomaSdkError::RateLimitedError {
+                        retry_after: None,
+                        ..
+                    }
+                )
+            }),
+            (500, "Internal Server Error".to_string(), |e| {
+                matches!(e, AtomaSdkError::NodeFailureError { status: 500, .. })
+            }),
+            (503, atoma_error("UNAVAILABLE"), |e| {
+                let AtomaSdkError::NodeFailureError {
+                    status: 503,
+                    message,
+                } = e
+                else {
+                    return false;
+                };
+                message == "details"
+            }),
+            (400, atoma_error("INVALID_BODY"), |e| {
+                let AtomaSdkError::ApiError {
+                    status: 400, code, ..
+                } = e
+                else {
+                    return false;
+                };
+                code.as_deref() == Some("INVALID_BODY")
+            }),
+        ];
+        for (status, body, check) in cases {
+            let error = error_from(status, None, &body).await;
+            assert!(check(&error), "{status} {body}: {error:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_from_response_retry_after() {
+        let in_a_minute = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        let cases = [
+            (429, Some("7"), Some(Duration::from_secs(7))),
+            (503, Some("7"), None),
+            (
+                429,
+                Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+                Some(Duration::ZERO),
+            ),
+            (429, Some("soon"), None),
+            (429, None, None),
+        ];
+        for (status, retry_after, expected) in cases {
+            let error = error_from(status, retry_after, "").await;
+            assert_eq!(error.retry_after(), expected, "{status} {retry_after:?}");
+        }
+
+        let retry_after = error_from(429, Some(&in_a_minute), "")
+            .await
+            .retry_after()
+            .unwrap();
+        assert!(retry_after > Duration::from_secs(55) && retry_after <= Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,409 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::{info, instrument};
+
+/// The previous hash of the first entry of a log
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+type Result<T> = std::result::Result<T, AuditError>;
+
+/// Whether a privileged action succeeded
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The action succeeded
+    Success,
+    /// The action failed, with the given error
+    Failure { error: String },
+}
+
+impl AuditOutcome {
+    /// The outcome of an action which returned `result`
+    pub fn of<T, E: std::fmt::Display>(result: &std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Self::Success,
+            Err(e) => Self::Failure {
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+/// A privileged action, as recorded in the audit log
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// The position of the entry in the log, starting at 0
+    pub seq: u64,
+
+    /// When the action was recorded, in seconds since the Unix epoch
+    pub timestamp: u64,
+
+    /// Who requested the action, e.g. an admin address or `engine`
+    pub actor: String,
+
+    /// The action, e.g. `set_starting_fee`
+    pub action: String,
+
+    /// The parameters of the action
+    pub parameters: Value,
+
+    /// The digest of the transaction the action was executed with, if any
+    pub tx_digest: Option<String>,
+
+    /// Whether the action succeeded
+    pub outcome: AuditOutcome,
+
+    /// The hash of the previous entry
+    pub prev_hash: String,
+
+    /// The SHA-256 hash of the previous hash and the content of this entry, hex encoded
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Computes the hash chaining this entry to the previous one
+    fn compute_hash(&self) -> String {
+        let content = json!([
+            self.seq,
+            self.timestamp,
+            self.actor,
+            self.action,
+            self.parameters,
+            self.tx_digest,
+            self.outcome,
+        ]);
+        format!(
+            "{:x}",
+            Sha256::new()
+                .chain_update(self.prev_hash.as_bytes())
+                .chain_update(content.to_string().as_bytes())
+                .finalize()
+        )
+    }
+}
+
+/// A filter on the entries of the audit log, e.g. to tell who changed the fee and when
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    /// Only entries of this action
+    pub action: Option<String>,
+
+    /// Only entries requested by this actor
+    pub actor: Option<String>,
+
+    /// Only entries recorded at or after this time, in seconds since the Unix epoch
+    pub since: Option<u64>,
+
+    /// Only entries recorded at or before this time, in seconds since the Unix epoch
+    pub until: Option<u64>,
+}
+
+impl AuditQuery {
+    /// Whether the entry passes the filter
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.action
+            .as_ref()
+            .is_none_or(|action| *action == entry.action)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|actor| *actor == entry.actor)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// An append-only, hash-chained log of privileged actions, stored as JSON lines.
+///
+/// Every entry holds the hash of the previous one, so that editing or removing an entry
+/// breaks the chain of all the following ones, which `read_entries` detects. A log must
+/// have a single writer: the engine and the CLI each write their own.
+pub struct AuditLog {
+    /// The path of the log file
+    path: PathBuf,
+
+    /// The log file, opened for appending
+    file: File,
+
+    /// The hash of the last entry
+    last_hash: String,
+
+    /// The position of the next entry
+    next_seq: u64,
+}
+
+impl AuditLog {
+    /// Opens the audit log at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuditError` if the file cannot be read or created, or its hash chain is broken
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = read_entries(&path)?;
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (last_hash, next_seq) = entries
+            .last()
+            .map_or((GENESIS_HASH.to_string(), 0), |entry| {
+                (entry.hash.clone(), entry.seq + 1)
+            });
+        Ok(Self {
+            path,
+            file,
+            last_hash,
+            next_seq,
+        })
+    }
+
+    /// Records a privileged action, and syncs it to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who requested the action
+    /// * `action` - The action
+    /// * `parameters` - The parameters of the action
+    /// * `tx_digest` - The digest of the transaction the action was executed with, if any
+    /// * `outcome` - Whether the action succeeded
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AuditError` if the entry cannot be written
+    #[instrument(level = "info", skip(self, parameters, outcome))]
+    pub fn append(
+        &mut self,
+        actor: &str,
+        action: &str,
+        parameters: Value,
+        tx_digest: Option<String>,
+        outcome: AuditOutcome,
+    ) -> Result<AuditEntry> {
+        let mut entry = AuditEntry {
+            seq: self.next_seq,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            actor: actor.to_string(),
+            action: action.to_string(),
+            parameters,
+            tx_digest,
+            outcome,
+            prev_hash: self.last_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        self.last_hash = entry.hash.clone();
+        self.next_seq += 1;
+        info!(
+            target = "audit",
+            event = "audit-entry-recorded",
+            seq = entry.seq,
+            actor,
+            action,
+            "Recorded privileged action"
+        );
+        Ok(entry)
+    }
+
+    /// Returns the entries passing the filter, after verifying the hash chain
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        Ok(read_entries(&self.path)?
+            .into_iter()
+            .filter(|entry| query.matches(entry))
+            .collect())
+    }
+}
+
+/// Reads the entries of the audit log at `path`, verifying its hash chain.
+///
+/// A missing file is an empty log.
+///
+/// # Errors
+///
+/// Returns an `AuditError::BrokenChain` if an entry was altered, removed or reordered
+pub fn read_entries<P: AsRef<Path>>(path: P) -> Result<Vec<AuditEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut entries = Vec::new();
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for line in BufReader::new(file).lines() {
+        let entry: AuditEntry = serde_json::from_str(&line?)?;
+        let seq = entries.len() as u64;
+        if entry.seq != seq || entry.prev_hash != prev_hash || entry.hash != entry.compute_hash() {
+            return Err(AuditError::BrokenChain(seq));
+        }
+        prev_hash = entry.hash.clone();
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Failed to read/write the audit log: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to (de)serialize an audit entry: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("The hash chain of the audit log is broken at entry {0}")]
+    BrokenChain(u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn append(log: &mut AuditLog, actor: &str, action: &str) -> AuditEntry {
+        log.append(
+            actor,
+            action,
+            json!({ "fee": 1_000 }),
+            Some("digest".to_string()),
+            AuditOutcome::Success,
+        )
+        .unwrap()
+    }
+
+    /// Rewrites the log at `path` with the entries edited by `edit`
+    fn rewrite(path: &Path, edit: impl FnOnce(&mut Vec<AuditEntry>)) {
+        let mut entries = read_entries(path).unwrap();
+        edit(&mut entries);
+        let lines = entries
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect::<String>();
+        fs::write(path, lines).unwrap();
+    }
+
+    fn log_with_three_entries() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path).unwrap();
+        append(&mut log, "0xadmin", "set_starting_fee");
+        append(&mut log, "engine", "withdraw_funds_from_treasury_pool");
+        append(&mut log, "0xadmin", "set_protocol_fee_per_mille");
+        (dir, path)
+    }
+
+    #[test]
+    fn entries_are_chained() {
+        let (_dir, path) = log_with_three_entries();
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].prev_hash, GENESIS_HASH);
+        for (seq, pair) in entries.windows(2).enumerate() {
+            assert_eq!(pair[0].seq, seq as u64);
+            assert_eq!(pair[1].prev_hash, pair[0].hash);
+        }
+    }
+
+    #[test]
+    fn reopened_log_continues_the_chain() {
+        let (_dir, path) = log_with_three_entries();
+        let last = read_entries(&path).unwrap().pop().unwrap();
+        let mut log = AuditLog::open(&path).unwrap();
+        let entry = append(&mut log, "0xadmin", "set_starting_fee");
+        assert_eq!(entry.seq, 3);
+        assert_eq!(entry.prev_hash, last.hash);
+        assert_eq!(read_entries(&path).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn missing_log_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_entries(dir.path().join("audit.jsonl"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn edited_entry_breaks_the_chain() {
+        let (_dir, path) = log_with_three_entries();
+        rewrite(&path, |entries| entries[1].actor = "0xattacker".to_string());
+        assert!(matches!(
+            read_entries(&path),
+            Err(AuditError::BrokenChain(1))
+        ));
+    }
+
+    #[test]
+    fn rehashed_entry_breaks_the_chain_of_the_next_one() {
+        let (_dir, path) = log_with_three_entries();
+        rewrite(&path, |entries| {
+            entries[1].parameters = json!({ "fee": 0 });
+            entries[1].hash = entries[1].compute_hash();
+        });
+        assert!(matches!(
+            read_entries(&path),
+            Err(AuditError::BrokenChain(2))
+        ));
+    }
+
+    #[test]
+    fn removed_entry_breaks_the_chain() {
+        let (_dir, path) = log_with_three_entries();
+        rewrite(&path, |entries| {
+            entries.remove(1);
+        });
+        assert!(matches!(
+            read_entries(&path),
+            Err(AuditError::BrokenChain(1))
+        ));
+        assert!(matches!(
+            AuditLog::open(&path),
+            Err(AuditError::BrokenChain(1))
+        ));
+    }
+
+    #[test]
+    fn reordered_entries_break_the_chain() {
+        let (_dir, path) = log_with_three_entries();
+        rewrite(&path, |entries| entries.swap(1, 2));
+        assert!(matches!(
+            read_entries(&path),
+            Err(AuditError::BrokenChain(1))
+        ));
+    }
+
+    #[test]
+    fn query_filters_entries() {
+        let (_dir, path) = log_with_three_entries();
+        let log = AuditLog::open(&path).unwrap();
+        let entries = log
+            .query(&AuditQuery {
+                actor: Some("0xadmin".to_string()),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        let entries = log
+            .query(&AuditQuery {
+                action: Some("set_starting_fee".to_string()),
+                until: Some(0),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+}
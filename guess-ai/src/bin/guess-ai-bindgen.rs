@@ -0,0 +1,80 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use guess_ai::bindgen::{fetch_normalized_module, generate, parse_normalized_module};
+
+/// The Move module the bindings are generated for, unless another one is given
+const DEFAULT_MODULE_NAME: &str = "contract";
+
+/// Generates typed Rust bindings (entry function call builders and event structs)
+/// from the normalized ABI of the GuessAI Move module.
+///
+/// The bindings used by the engine and the CLI, `src/contract_bindings.rs`, are generated from
+/// the ABI snapshot `abi/contract.json`, and checked in CI with:
+///
+/// `guess-ai-bindgen --abi-path abi/contract.json --output src/contract_bindings.rs --check`
+///
+/// When the contract is upgraded, refresh the snapshot with the `result` of
+/// `sui_getNormalizedMoveModule` for the new package, and regenerate the bindings.
+#[derive(Parser)]
+struct Args {
+    /// Path to a normalized module, as returned by `sui_getNormalizedMoveModule`
+    #[arg(long, conflicts_with_all = ["rpc_url", "package_id"])]
+    abi_path: Option<PathBuf>,
+
+    /// Sui JSON-RPC endpoint to fetch the normalized module from
+    #[arg(long, requires = "package_id")]
+    rpc_url: Option<String>,
+
+    /// ID of the package declaring the module, when fetching it from `--rpc-url`
+    #[arg(long, requires = "rpc_url")]
+    package_id: Option<String>,
+
+    /// Name of the module, when fetching it from `--rpc-url`
+    #[arg(long, default_value = DEFAULT_MODULE_NAME)]
+    module: String,
+
+    /// File to write the bindings to, printed to stdout if unset
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Fail instead of writing if `--output` differs from the generated bindings,
+    /// to catch bindings drifting from the deployed contract
+    #[arg(long, requires = "output")]
+    check: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let module = match (&args.abi_path, &args.rpc_url, &args.package_id) {
+        (Some(abi_path), _, _) => parse_normalized_module(
+            &fs::read_to_string(abi_path)
+                .with_context(|| format!("Failed to read {}", abi_path.display()))?,
+        )?,
+        (None, Some(rpc_url), Some(package_id)) => {
+            fetch_normalized_module(rpc_url, package_id, &args.module).await?
+        }
+        _ => bail!("Either --abi-path, or --rpc-url and --package-id, must be given"),
+    };
+    let bindings = generate(&module)?;
+
+    match (&args.output, args.check) {
+        (Some(output), true) => {
+            let current = fs::read_to_string(output)
+                .with_context(|| format!("Failed to read {}", output.display()))?;
+            if current != bindings {
+                bail!(
+                    "{} is out of date with the `{}` module, regenerate it",
+                    output.display(),
+                    module.name
+                );
+            }
+        }
+        (Some(output), false) => fs::write(output, bindings)
+            .with_context(|| format!("Failed to write {}", output.display()))?,
+        (None, _) => print!("{bindings}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,625 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// The JSON-RPC method returning the normalized ABI of a Move module
+const GET_NORMALIZED_MOVE_MODULE_METHOD: &str = "sui_getNormalizedMoveModule";
+
+/// The header of every generated file
+const GENERATED_HEADER: &str =
+    "// @generated by guess-ai-bindgen. Do not edit by hand, regenerate instead.";
+
+/// The normalized ABI of a Move module, as returned by `sui_getNormalizedMoveModule`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedModule {
+    /// The address of the package of the module
+    pub address: String,
+
+    /// The name of the module
+    pub name: String,
+
+    /// The structs declared by the module
+    pub structs: BTreeMap<String, NormalizedStruct>,
+
+    /// The functions callable from outside the module
+    pub exposed_functions: BTreeMap<String, NormalizedFunction>,
+}
+
+/// A struct declared by a Move module
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedStruct {
+    /// The abilities of the struct
+    pub abilities: AbilitySet,
+
+    /// The type parameters of the struct
+    pub type_parameters: Vec<Value>,
+
+    /// The fields of the struct, in declaration order
+    pub fields: Vec<NormalizedField>,
+}
+
+/// The abilities of a Move type
+#[derive(Clone, Debug, Deserialize)]
+pub struct AbilitySet {
+    /// The abilities, e.g. `Copy` or `Key`
+    pub abilities: Vec<String>,
+}
+
+/// A field of a Move struct
+#[derive(Clone, Debug, Deserialize)]
+pub struct NormalizedField {
+    /// The name of the field
+    pub name: String,
+
+    /// The type of the field
+    #[serde(rename = "type")]
+    pub type_: NormalizedType,
+}
+
+/// A function exposed by a Move module
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedFunction {
+    /// Whether the function is an `entry` function
+    pub is_entry: bool,
+
+    /// The type parameters of the function
+    pub type_parameters: Vec<AbilitySet>,
+
+    /// The types of the parameters of the function
+    pub parameters: Vec<NormalizedType>,
+}
+
+/// A Move type
+#[derive(Clone, Debug, Deserialize)]
+pub enum NormalizedType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Struct(StructType),
+    Vector(Box<NormalizedType>),
+    TypeParameter(u16),
+    Reference(Box<NormalizedType>),
+    MutableReference(Box<NormalizedType>),
+}
+
+/// A Move struct type
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructType {
+    /// The address of the package declaring the struct
+    pub address: String,
+
+    /// The module declaring the struct
+    pub module: String,
+
+    /// The name of the struct
+    pub name: String,
+
+    /// The type arguments of the struct
+    pub type_arguments: Vec<NormalizedType>,
+}
+
+impl StructType {
+    /// Whether the struct is `address::module::name`, ignoring leading zeros of the address
+    fn is(&self, address: &str, module: &str, name: &str) -> bool {
+        normalize_address(&self.address) == normalize_address(address)
+            && self.module == module
+            && self.name == name
+    }
+}
+
+/// Fetches the normalized ABI of a Move module from a Sui JSON-RPC endpoint.
+///
+/// # Arguments
+///
+/// * `rpc_url` - The URL of the Sui JSON-RPC endpoint
+/// * `package_id` - The ID of the package declaring the module
+/// * `module` - The name of the module
+///
+/// # Errors
+///
+/// Returns a `BindgenError` if the request fails, or the endpoint returns an error or
+/// a module which cannot be parsed
+pub async fn fetch_normalized_module(
+    rpc_url: &str,
+    package_id: &str,
+    module: &str,
+) -> Result<NormalizedModule> {
+    let response: Value = reqwest::Client::new()
+        .post(rpc_url)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": GET_NORMALIZED_MOVE_MODULE_METHOD,
+            "params": [package_id, module],
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(BindgenError::RpcError(error.to_string()));
+    }
+    let result = response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| BindgenError::RpcError("Response has no result".to_string()))?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Parses a normalized module saved to a file, in the format returned by
+/// `sui_getNormalizedMoveModule`
+pub fn parse_normalized_module(content: &str) -> Result<NormalizedModule> {
+    Ok(serde_json::from_str(content)?)
+}
+
+/// Generates the Rust bindings of a Move module.
+///
+/// For every `entry` function, a struct holding its typed arguments is emitted, with a
+/// `build` method returning the programmable transaction block calling it. Object
+/// parameters are taken as `ObjectArg`s, and the `TxContext` parameter is omitted.
+/// For every struct without the `key` ability, such as events, a struct decodable from
+/// the BCS bytes of its Move counterpart is emitted. Generic functions and structs are
+/// skipped, as their instantiation cannot be known from the ABI.
+///
+/// # Errors
+///
+/// Returns a `BindgenError::UnsupportedType` if a parameter or field has a type with
+/// no Rust counterpart, such as `u256`
+pub fn generate(module: &NormalizedModule) -> Result<String> {
+    let mut out = String::new();
+    writeln!(out, "{GENERATED_HEADER}")?;
+    writeln!(out, "//! Bindings of the `{}` Move module.", module.name)?;
+    writeln!(out)?;
+    writeln!(out, "#![allow(unused_imports)]")?;
+    writeln!(out)?;
+    writeln!(out, "use serde::{{Deserialize, Serialize}};")?;
+    writeln!(out, "use sui_sdk::types::{{")?;
+    writeln!(out, "    base_types::{{ObjectID, SuiAddress}},")?;
+    writeln!(
+        out,
+        "    programmable_transaction_builder::ProgrammableTransactionBuilder,"
+    )?;
+    writeln!(
+        out,
+        "    transaction::{{ObjectArg, ProgrammableTransaction}},"
+    )?;
+    writeln!(out, "    Identifier,")?;
+    writeln!(out, "}};")?;
+    writeln!(out)?;
+    writeln!(out, "/// The name of the Move module")?;
+    writeln!(out, "pub const MODULE_NAME: &str = \"{}\";", module.name)?;
+
+    for (name, function) in &module.exposed_functions {
+        if !function.is_entry {
+            continue;
+        }
+        writeln!(out)?;
+        if !function.type_parameters.is_empty() {
+            writeln!(
+                out,
+                "// `{}::{name}` is generic, and is skipped",
+                module.name
+            )?;
+            continue;
+        }
+        generate_call(&mut out, module, name, function)?;
+    }
+
+    for (name, struct_) in &module.structs {
+        if struct_.abilities.abilities.iter().any(|a| a == "Key") {
+            continue;
+        }
+        writeln!(out)?;
+        if !struct_.type_parameters.is_empty() {
+            writeln!(
+                out,
+                "// `{}::{name}` is generic, and is skipped",
+                module.name
+            )?;
+            continue;
+        }
+        generate_struct(&mut out, module, name, struct_)?;
+    }
+    Ok(out)
+}
+
+/// Emits the call builder of an entry function
+fn generate_call(
+    out: &mut String,
+    module: &NormalizedModule,
+    name: &str,
+    function: &NormalizedFunction,
+) -> Result<()> {
+    let mut arguments = Vec::new();
+    for (index, parameter) in function.parameters.iter().enumerate() {
+        if is_tx_context(parameter) {
+            continue;
+        }
+        let argument = match object_name(parameter) {
+            Some(object) => CallArgument {
+                name: to_snake_case(object),
+                type_: "ObjectArg".to_string(),
+                is_object: true,
+            },
+            None => CallArgument {
+                name: format!("arg{index}"),
+                type_: rust_type(parameter, module)
+                    .map_err(|type_| BindgenError::UnsupportedType(format!("{name}: {type_}")))?,
+                is_object: false,
+            },
+        };
+        arguments.push(argument);
+    }
+    // Two parameters of the same object type get the index of the parameter as suffix
+    for index in 0..arguments.len() {
+        if arguments
+            .iter()
+            .filter(|a| a.name == arguments[index].name)
+            .count()
+            > 1
+        {
+            arguments[index].name = format!("{}_{index}", arguments[index].name);
+        }
+    }
+
+    let struct_name = to_camel_case(name);
+    writeln!(out, "/// Calls `{}::{name}`", module.name)?;
+    writeln!(out, "#[derive(Clone, Debug)]")?;
+    if arguments.is_empty() {
+        writeln!(out, "pub struct {struct_name} {{}}")?;
+    } else {
+        writeln!(out, "pub struct {struct_name} {{")?;
+        for argument in &arguments {
+            writeln!(out, "    pub {}: {},", argument.name, argument.type_)?;
+        }
+        writeln!(out, "}}")?;
+    }
+    writeln!(out)?;
+    writeln!(out, "impl {struct_name} {{")?;
+    writeln!(out, "    /// The name of the Move function")?;
+    writeln!(
+        out,
+        "    pub const FUNCTION_NAME: &'static str = \"{name}\";"
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    /// Builds the programmable transaction block calling `{}::{name}`",
+        module.name
+    )?;
+    writeln!(
+        out,
+        "    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {{"
+    )?;
+    writeln!(
+        out,
+        "        let mut builder = ProgrammableTransactionBuilder::new();"
+    )?;
+    if arguments.is_empty() {
+        writeln!(out, "        let arguments = vec![];")?;
+    } else {
+        writeln!(out, "        let arguments = vec![")?;
+        for argument in &arguments {
+            if argument.is_object {
+                writeln!(out, "            builder.obj(self.{})?,", argument.name)?;
+            } else {
+                writeln!(out, "            builder.pure(self.{})?,", argument.name)?;
+            }
+        }
+        writeln!(out, "        ];")?;
+    }
+    writeln!(out, "        builder.programmable_move_call(")?;
+    writeln!(out, "            package_id,")?;
+    writeln!(out, "            Identifier::new(MODULE_NAME)?,")?;
+    writeln!(out, "            Identifier::new(Self::FUNCTION_NAME)?,")?;
+    writeln!(out, "            vec![],")?;
+    writeln!(out, "            arguments,")?;
+    writeln!(out, "        );")?;
+    writeln!(out, "        Ok(builder.finish())")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Emits the struct decoding a Move struct, such as an event
+fn generate_struct(
+    out: &mut String,
+    module: &NormalizedModule,
+    name: &str,
+    struct_: &NormalizedStruct,
+) -> Result<()> {
+    writeln!(out, "/// `{}::{name}`", module.name)?;
+    writeln!(out, "#[derive(Clone, Debug, Serialize, Deserialize)]")?;
+    writeln!(out, "pub struct {name} {{")?;
+    for field in &struct_.fields {
+        let type_ = rust_type(&field.type_, module).map_err(|type_| {
+            BindgenError::UnsupportedType(format!("{name}.{}: {type_}", field.name))
+        })?;
+        writeln!(out, "    pub {}: {type_},", field_name(&field.name))?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "impl {name} {{")?;
+    writeln!(
+        out,
+        "    /// The name of the Move struct, as in the type of the events"
+    )?;
+    writeln!(out, "    pub const NAME: &'static str = \"{name}\";")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// An argument of a generated call builder
+struct CallArgument {
+    /// The name of the field holding the argument
+    name: String,
+
+    /// The Rust type of the argument
+    type_: String,
+
+    /// Whether the argument is an object, rather than a pure value
+    is_object: bool,
+}
+
+/// Maps a Move type passed by value, or a field type, to its Rust counterpart.
+///
+/// Returns the Move type as the error if it has no Rust counterpart.
+fn rust_type(
+    type_: &NormalizedType,
+    module: &NormalizedModule,
+) -> std::result::Result<String, String> {
+    Ok(match type_ {
+        NormalizedType::Bool => "bool".to_string(),
+        NormalizedType::U8 => "u8".to_string(),
+        NormalizedType::U16 => "u16".to_string(),
+        NormalizedType::U32 => "u32".to_string(),
+        NormalizedType::U64 => "u64".to_string(),
+        NormalizedType::U128 => "u128".to_string(),
+        NormalizedType::Address => "SuiAddress".to_string(),
+        NormalizedType::Vector(inner) => format!("Vec<{}>", rust_type(inner, module)?),
+        NormalizedType::Struct(struct_)
+            if struct_.is("0x1", "string", "String") || struct_.is("0x1", "ascii", "String") =>
+        {
+            "String".to_string()
+        }
+        NormalizedType::Struct(struct_)
+            if struct_.is("0x2", "object", "ID") || struct_.is("0x2", "object", "UID") =>
+        {
+            "ObjectID".to_string()
+        }
+        NormalizedType::Struct(struct_) if struct_.is("0x1", "option", "Option") => {
+            match struct_.type_arguments.as_slice() {
+                [inner] => format!("Option<{}>", rust_type(inner, module)?),
+                _ => return Err(format!("{type_:?}")),
+            }
+        }
+        // `Balance<T>` is a struct holding its value
+        NormalizedType::Struct(struct_) if struct_.is("0x2", "balance", "Balance") => {
+            "u64".to_string()
+        }
+        NormalizedType::Struct(struct_)
+            if normalize_address(&struct_.address) == normalize_address(&module.address)
+                && struct_.module == module.name
+                && struct_.type_arguments.is_empty() =>
+        {
+            struct_.name.clone()
+        }
+        _ => return Err(format!("{type_:?}")),
+    })
+}
+
+/// Returns the name of the struct of an object parameter: a reference to a struct, or a
+/// struct passed by value which has no pure counterpart, such as a `Coin`
+fn object_name(type_: &NormalizedType) -> Option<&str> {
+    match type_ {
+        NormalizedType::Reference(inner) | NormalizedType::MutableReference(inner) => {
+            match inner.as_ref() {
+                NormalizedType::Struct(struct_) => Some(&struct_.name),
+                _ => None,
+            }
+        }
+        NormalizedType::Struct(struct_)
+            if !struct_.is("0x1", "string", "String")
+                && !struct_.is("0x1", "ascii", "String")
+                && !struct_.is("0x1", "option", "Option")
+                && !struct_.is("0x2", "object", "ID") =>
+        {
+            Some(&struct_.name)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the parameter is the `TxContext`, which is provided by the runtime
+fn is_tx_context(type_: &NormalizedType) -> bool {
+    match type_ {
+        NormalizedType::Reference(inner) | NormalizedType::MutableReference(inner) => {
+            matches!(inner.as_ref(), NormalizedType::Struct(struct_) if struct_.is("0x2", "tx_context", "TxContext"))
+        }
+        _ => false,
+    }
+}
+
+/// Strips the `0x` prefix and leading zeros of an address
+fn normalize_address(address: &str) -> &str {
+    let address = address.trim_start_matches("0x").trim_start_matches('0');
+    if address.is_empty() {
+        "0"
+    } else {
+        address
+    }
+}
+
+/// Escapes a Move field name which is a Rust keyword, e.g. `type`
+fn field_name(name: &str) -> String {
+    match name {
+        "as" | "async" | "await" | "box" | "crate" | "dyn" | "enum" | "extern" | "fn" | "impl"
+        | "in" | "macro" | "match" | "mod" | "move" | "override" | "priv" | "pub" | "ref"
+        | "static" | "trait" | "type" | "typeof" | "unsafe" | "where" | "yield" => {
+            format!("r#{name}")
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Converts a `snake_case` Move function name to a `CamelCase` Rust type name
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Converts a `CamelCase` Move struct name to a `snake_case` Rust field name
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            let previous_is_lowercase = name
+                .chars()
+                .nth(index.wrapping_sub(1))
+                .is_some_and(|p| p.is_lowercase());
+            if index > 0 && previous_is_lowercase {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+type Result<T> = std::result::Result<T, BindgenError>;
+
+#[derive(Debug, Error)]
+pub enum BindgenError {
+    #[error("Failed to fetch the normalized module: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("RPC endpoint returned an error: {0}")]
+    RpcError(String),
+    #[error("Failed to parse the normalized module: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("Unsupported Move type in {0}")]
+    UnsupportedType(String),
+    #[error("Failed to write the bindings: {0}")]
+    FormatError(#[from] std::fmt::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module with an entry function taking an object, a pure value and the `TxContext`,
+    /// and an event
+    const MODULE: &str = r#"{
+        "address": "0x0000000000000000000000000000000000000000000000000000000000000abc",
+        "name": "game",
+        "structs": {
+            "Game": {
+                "abilities": { "abilities": ["Key"] },
+                "typeParameters": [],
+                "fields": []
+            },
+            "GuessEvent": {
+                "abilities": { "abilities": ["Copy", "Drop"] },
+                "typeParameters": [],
+                "fields": [
+                    { "name": "type", "type": "U8" },
+                    { "name": "guesser", "type": "Address" },
+                    { "name": "game_id", "type": { "Struct": { "address": "0x2", "module": "object", "name": "ID", "typeArguments": [] } } },
+                    { "name": "guess", "type": { "Struct": { "address": "0x1", "module": "string", "name": "String", "typeArguments": [] } } }
+                ]
+            }
+        },
+        "exposedFunctions": {
+            "guess": {
+                "isEntry": true,
+                "typeParameters": [],
+                "parameters": [
+                    { "MutableReference": { "Struct": { "address": "0xabc", "module": "game", "name": "Game", "typeArguments": [] } } },
+                    { "Vector": "U8" },
+                    { "MutableReference": { "Struct": { "address": "0x2", "module": "tx_context", "name": "TxContext", "typeArguments": [] } } }
+                ]
+            },
+            "view": {
+                "isEntry": false,
+                "typeParameters": [],
+                "parameters": []
+            },
+            "withdraw": {
+                "isEntry": true,
+                "typeParameters": [{ "abilities": [] }],
+                "parameters": []
+            }
+        }
+    }"#;
+
+    #[test]
+    fn generate_matches_the_checked_in_bindings() {
+        let module = parse_normalized_module(include_str!("../abi/contract.json")).unwrap();
+        assert_eq!(
+            generate(&module).unwrap(),
+            include_str!("contract_bindings.rs"),
+            "src/contract_bindings.rs is stale, regenerate it with guess-ai-bindgen"
+        );
+    }
+
+    #[test]
+    fn generate_emits_entry_functions() {
+        let bindings = generate(&parse_normalized_module(MODULE).unwrap()).unwrap();
+        assert!(bindings.contains("pub const MODULE_NAME: &str = \"game\";"));
+        assert!(bindings.contains("pub struct Guess {"));
+        assert!(bindings.contains("    pub game: ObjectArg,"));
+        assert!(bindings.contains("    pub arg1: Vec<u8>,"));
+        assert!(bindings.contains("pub const FUNCTION_NAME: &'static str = \"guess\";"));
+        assert!(!bindings.contains("TxContext"));
+        assert!(!bindings.contains("pub struct View"));
+    }
+
+    #[test]
+    fn generate_skips_generic_functions() {
+        let bindings = generate(&parse_normalized_module(MODULE).unwrap()).unwrap();
+        assert!(bindings.contains("// `game::withdraw` is generic, and is skipped"));
+        assert!(!bindings.contains("pub struct Withdraw"));
+    }
+
+    #[test]
+    fn generate_emits_structs_without_the_key_ability() {
+        let bindings = generate(&parse_normalized_module(MODULE).unwrap()).unwrap();
+        assert!(bindings.contains("pub struct GuessEvent {"));
+        assert!(bindings.contains("    pub r#type: u8,"));
+        assert!(bindings.contains("    pub guesser: SuiAddress,"));
+        assert!(bindings.contains("    pub game_id: ObjectID,"));
+        assert!(bindings.contains("    pub guess: String,"));
+        assert!(!bindings.contains("pub struct Game {"));
+    }
+
+    #[test]
+    fn generate_refuses_unsupported_types() {
+        let module = MODULE.replace(r#"{ "Vector": "U8" }"#, r#""U256""#);
+        assert!(matches!(
+            generate(&parse_normalized_module(&module).unwrap()),
+            Err(BindgenError::UnsupportedType(_))
+        ));
+    }
+}
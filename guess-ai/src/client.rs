@@ -1,26 +1,62 @@
-use std::str::FromStr;
+use std::time::Duration;
 
 use sui_sdk::{
-    json::SuiJsonValue,
+    rpc_types::{
+        SuiExecutionStatus, SuiObjectDataOptions, SuiTransactionBlockEffects,
+        SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+        SuiTransactionBlockResponseOptions,
+    },
     types::{
-        base_types::{ObjectID, ObjectIDParseError, SuiAddress},
+        base_types::{ObjectID, SequenceNumber, SuiAddress},
         error::SuiError,
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        quorum_driver_types::ExecuteTransactionRequestType,
+        transaction::{ObjectArg, TransactionData},
     },
     wallet_context::WalletContext,
+    SuiClient,
 };
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use x25519_dalek::PublicKey;
 
-use crate::GUESS_AI_MODULE_NAME;
+use crate::{
+    config::GasPoolConfig,
+    contract::{GuessAiCall, WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME},
+    game_state::{read_game_state, GameState, GameStateError},
+    gas::{BalanceStatus, GasPool},
+    network::{Network, NetworkError, NetworkGuard},
+    GUESS_AI_MODULE_NAME,
+};
+
+/// The gas budget used to dry-run a transaction, and the upper bound of the
+/// estimated gas budget, when no gas budget is provided by the caller
+const MAX_GAS_BUDGET: u64 = 50_000_000; // 0.05 SUI
+
+/// The gas budget of the transactions merging and splitting the coins of the gas pool
+const GAS_POOL_MAINTENANCE_GAS_BUDGET: u64 = 10_000_000; // 0.01 SUI
+
+/// The maximum number of coins fetched per page when refreshing the gas pool
+const GAS_COINS_PAGE_LIMIT: usize = 50;
+
+/// The safety margin added on top of the gas cost measured by the dry-run, in percent
+const GAS_BUDGET_SAFETY_MARGIN_PERCENT: u64 = 20;
 
-/// The gas budget for the node registration transaction
-const GAS_BUDGET: u64 = 50_000_000; // 0.05 SUI
+/// The maximum number of times a transaction is rebuilt and resubmitted after
+/// conflicting with a concurrent transaction on one of its input objects
+const MAX_OBJECT_VERSION_CONFLICT_RETRIES: usize = 3;
 
-/// The name of the function to withdraw funds from the treasury pool
-const WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME: &str = "withdraw_funds_from_treasury_pool";
+/// The delay before resubmitting a transaction after an object version conflict,
+/// doubled after each attempt
+const OBJECT_VERSION_CONFLICT_BACKOFF: Duration = Duration::from_millis(500);
 
-/// The name of the function to submit the node public key
-const RESUBMIT_TDX_ATTESTATION_FUNCTION_NAME: &str = "resubmit_tdx_attestation";
+/// Error messages reported by the Sui network when an input object of a transaction
+/// was modified or locked by a concurrent transaction
+const OBJECT_VERSION_CONFLICT_MARKERS: [&str; 3] = [
+    "ObjectVersionUnavailableForConsumption",
+    "ObjectLockConflict",
+    "is not available for consumption",
+];
 
 /// The result type for the Sui client
 type Result<T> = std::result::Result<T, SuiClientError>;
@@ -31,11 +67,21 @@ pub struct SuiClientContext {
     /// The ID of the Secret Guessing database object
     guess_ai_db: ObjectID,
 
+    /// The version at which the Secret Guessing database object became shared,
+    /// fetched on first use
+    guess_ai_db_initial_shared_version: Option<SequenceNumber>,
+
     /// The ID of the Secret Guessing package
     guess_ai_package_id: ObjectID,
 
     /// The wallet context for the current Sui client
     wallet_context: WalletContext,
+
+    /// The gas coins of the wallet, handed out to the transactions in turn
+    gas_pool: GasPool,
+
+    /// Guards the payouts on mainnet, see [`SuiClientContext::verify_network`]
+    network_guard: NetworkGuard,
 }
 
 impl SuiClientContext {
@@ -44,14 +90,54 @@ impl SuiClientContext {
         guess_ai_db: ObjectID,
         guess_ai_package_id: ObjectID,
         wallet_context: WalletContext,
+        gas_pool_config: GasPoolConfig,
     ) -> Self {
         Self {
             guess_ai_db,
+            guess_ai_db_initial_shared_version: None,
             guess_ai_package_id,
             wallet_context,
+            gas_pool: GasPool::new(gas_pool_config),
+            network_guard: NetworkGuard::default(),
         }
     }
 
+    /// Verifies that the wallet is connected to the selected network, from the chain
+    /// identifier of its RPC endpoint. Payouts are refused until the network is verified,
+    /// and on mainnet unless `allow_mainnet` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `selected` - The network selected with `--network`, if any
+    /// * `allow_mainnet` - Whether the payouts may be sent on mainnet
+    ///
+    /// # Returns
+    ///
+    /// Returns the guard of the verified network
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SuiClientError` if the chain identifier cannot be fetched, or the wallet is
+    /// connected to another network than the selected one
+    #[instrument(level = "info", skip(self))]
+    pub async fn verify_network(
+        &mut self,
+        selected: Option<Network>,
+        allow_mainnet: bool,
+    ) -> Result<NetworkGuard> {
+        let client = self.wallet_context.get_client().await?;
+        let chain_identifier = client.read_api().get_chain_identifier().await?;
+        self.network_guard = NetworkGuard::verify(selected, &chain_identifier, allow_mainnet)?;
+        Ok(self.network_guard)
+    }
+
+    /// Whether payouts may be sent, see [`SuiClientContext::verify_network`]
+    pub fn payouts_allowed(&self) -> bool {
+        self.network_guard
+            .check(WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME)
+            .is_ok()
+    }
+
     /// Submits a node's public key and TDX attestation to the GuessAI game smart contract.
     ///
     /// This method executes a Move call to register or update a node's public key and TDX attestation
@@ -62,7 +148,7 @@ impl SuiClientContext {
     /// * `public_key` - The x25519 public key of the node
     /// * `tdx_quote_bytes` - The TDX attestation quote bytes
     /// * `gas` - Optional ObjectID to use for gas payment. If None, the system will select an appropriate gas object
-    /// * `gas_budget` - Optional gas budget for the transaction. Defaults to 50,000,000 (0.05 SUI) if None
+    /// * `gas_budget` - Optional upper bound for the gas budget, which is otherwise estimated by a dry-run. Defaults to 50,000,000 (0.05 SUI) if None
     /// * `gas_price` - Optional gas price for the transaction. If None, the system will use the network's reference price
     ///
     /// # Returns
@@ -73,7 +159,7 @@ impl SuiClientContext {
     ///
     /// This function will return an error if:
     /// * The wallet context fails to get the active address
-    /// * The transaction execution fails
+    /// * The transaction execution fails, see [`SuiClientContext::execute_move_call`]
     #[instrument(
         level = "info",
         skip_all,
@@ -89,33 +175,13 @@ impl SuiClientContext {
         gas_budget: Option<u64>,
         gas_price: Option<u64>,
     ) -> Result<String> {
-        let client = self.wallet_context.get_client().await?;
-        let active_address = self.wallet_context.active_address()?;
-
-        let tx = client
-            .transaction_builder()
-            .move_call(
-                active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                RESUBMIT_TDX_ATTESTATION_FUNCTION_NAME,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::new(tdx_quote_bytes.into())?,
-                    SuiJsonValue::new(public_key.to_bytes().into())?,
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
-                gas_price,
-            )
-            .await?;
-
-        let tx = self.wallet_context.sign_transaction(&tx);
+        let call = GuessAiCall::ResubmitTdxAttestation {
+            tdx_quote_bytes,
+            public_key,
+        };
         let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
+            .execute_move_call(call, gas, gas_budget, gas_price)
+            .await?;
 
         Ok(response.digest.to_string())
     }
@@ -129,7 +195,7 @@ impl SuiClientContext {
     ///
     /// * `winner_address` - The Sui address of the winning player who will receive the funds
     /// * `gas` - Optional ObjectID to use for gas payment. If None, the system will select an appropriate gas object
-    /// * `gas_budget` - Optional gas budget for the transaction. Defaults to 50,000,000 (0.05 SUI) if None
+    /// * `gas_budget` - Optional upper bound for the gas budget, which is otherwise estimated by a dry-run. Defaults to 50,000,000 (0.05 SUI) if None
     /// * `gas_price` - Optional gas price for the transaction. If None, the system will use the network's reference price
     ///
     /// # Returns
@@ -140,8 +206,7 @@ impl SuiClientContext {
     ///
     /// This function will return an error if:
     /// * The wallet context fails to get the active address
-    /// * The object ID parsing fails
-    /// * The transaction execution fails
+    /// * The transaction execution fails, see [`SuiClientContext::execute_move_call`]
     #[instrument(
         level = "info",
         skip_all,
@@ -156,58 +221,620 @@ impl SuiClientContext {
         gas_budget: Option<u64>,
         gas_price: Option<u64>,
     ) -> Result<String> {
+        self.network_guard
+            .check(WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME)?;
+        let call = GuessAiCall::WithdrawFundsFromTreasuryPool { winner_address };
+        let response = self
+            .execute_move_call(call, gas, gas_budget, gas_price)
+            .await?;
+
+        info!(
+            target = "sui-client-withdraw-funds-from-treasury-pool",
+            tx_hash = %response.digest,
+            winner_address = %winner_address,
+            "Successfully withdrew funds from treasury pool for winner"
+        );
+
+        Ok(response.digest.to_string())
+    }
+
+    /// Reads the state of the game from the Secret Guessing database object.
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded `GameState`, or a `SuiClientError` if the object cannot be
+    /// fetched or decoded
+    #[instrument(level = "info", skip_all, fields(guess_ai_db = %self.guess_ai_db))]
+    pub async fn game_state(&self) -> Result<GameState> {
+        let client = self.wallet_context.get_client().await?;
+        Ok(read_game_state(&client, self.guess_ai_db).await?)
+    }
+
+    /// Returns the active address of the wallet, which the transactions are sent from
+    pub fn active_address(&mut self) -> Result<SuiAddress> {
+        Ok(self.wallet_context.active_address()?)
+    }
+
+    /// Executes a Move call to a function of the GuessAI game smart contract.
+    ///
+    /// The transaction is first dry-run, with `gas_budget` (or `MAX_GAS_BUDGET`) as its budget,
+    /// to surface Move aborts without spending gas and to measure its gas cost. It is then
+    /// rebuilt with the measured cost, plus a safety margin, as its budget, signed and executed,
+    /// and the status of its effects is checked. If an input object was modified or locked by
+    /// a concurrent transaction, the transaction is rebuilt against the latest object versions
+    /// and resubmitted, up to `MAX_OBJECT_VERSION_CONFLICT_RETRIES` times.
+    ///
+    /// Unless a gas object is given, the gas is paid with the next coin of the gas pool, and
+    /// the transaction is refused if the wallet balance is below the configured minimum. The
+    /// gas pool is maintained after each successful transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `call` - The contract function to call, with its arguments
+    /// * `gas` - Optional ObjectID to use for gas payment
+    /// * `gas_budget` - Optional upper bound for the gas budget
+    /// * `gas_price` - Optional gas price for the transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the response of the executed transaction, whose effects are successful
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// * The transaction cannot be built, dry-run or executed
+    /// * The contract aborts, in which case a `SuiClientError::MoveAbort` is returned
+    /// * The transaction fails for any other reason
+    /// * The estimated gas budget exceeds `gas_budget`
+    /// * The wallet balance is below the configured minimum
+    /// * The transaction still conflicts with concurrent transactions after all retries
+    pub async fn execute_move_call(
+        &mut self,
+        call: GuessAiCall,
+        gas: Option<ObjectID>,
+        gas_budget: Option<u64>,
+        gas_price: Option<u64>,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let function = call.function_name();
+        let mut backoff = OBJECT_VERSION_CONFLICT_BACKOFF;
+        for attempt in 0..=MAX_OBJECT_VERSION_CONFLICT_RETRIES {
+            match self
+                .try_execute_move_call(&call, gas, gas_budget, gas_price)
+                .await
+            {
+                Err(SuiClientError::ObjectVersionConflict(message))
+                    if attempt < MAX_OBJECT_VERSION_CONFLICT_RETRIES =>
+                {
+                    warn!(
+                        target = "sui-client",
+                        event = "object-version-conflict",
+                        function,
+                        attempt,
+                        "Transaction conflicted with a concurrent transaction, retrying: {message}"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Ok(response) => {
+                    // The pool was refreshed before the transaction, the maintenance is only
+                    // sent if it has dust to merge or coins it can split
+                    if self.gas_pool.needs_maintenance() {
+                        if let Err(e) = self.maintain_gas_pool().await {
+                            warn!(
+                                target = "sui-client",
+                                event = "gas-pool-maintenance-error",
+                                "Failed to maintain the gas pool: {e}"
+                            );
+                        }
+                    }
+                    return Ok(response);
+                }
+                result => return result,
+            }
+        }
+        unreachable!("The last attempt always returns")
+    }
+
+    /// Builds, dry-runs and executes a Move call once, see [`SuiClientContext::execute_move_call`]
+    async fn try_execute_move_call(
+        &mut self,
+        call: &GuessAiCall,
+        gas: Option<ObjectID>,
+        gas_budget: Option<u64>,
+        gas_price: Option<u64>,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let function = call.function_name();
         let client = self.wallet_context.get_client().await?;
         let active_address = self.wallet_context.active_address()?;
+        let max_gas_budget = gas_budget.unwrap_or(MAX_GAS_BUDGET);
+        let gas_price = match gas_price {
+            Some(gas_price) => gas_price,
+            None => client.read_api().get_reference_gas_price().await?,
+        };
 
-        let tx = client
-            .transaction_builder()
-            .move_call(
+        self.refresh_gas_pool(&client, active_address).await?;
+        self.check_gas_balance()?;
+
+        let guess_ai_db = self.guess_ai_db_object_arg(&client).await?;
+        let pt = call.build(self.guess_ai_package_id, guess_ai_db)?;
+        let gas_object = match gas
+            .is_none()
+            .then(|| self.gas_pool.next_coin(max_gas_budget))
+        {
+            Some(Some(gas_object)) => gas_object,
+            _ => {
+                client
+                    .transaction_builder()
+                    .select_gas(
+                        active_address,
+                        gas,
+                        max_gas_budget,
+                        vec![self.guess_ai_db],
+                        gas_price,
+                    )
+                    .await?
+            }
+        };
+        let build_transaction = |budget: u64| {
+            TransactionData::new_programmable(
                 active_address,
-                self.guess_ai_package_id,
-                GUESS_AI_MODULE_NAME,
-                WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME,
-                vec![],
-                vec![
-                    SuiJsonValue::from_object_id(self.guess_ai_db),
-                    SuiJsonValue::from_object_id(ObjectID::from_str(
-                        winner_address.to_string().as_str(),
-                    )?),
-                ],
-                gas,
-                gas_budget.unwrap_or(GAS_BUDGET),
+                vec![gas_object],
+                pt.clone(),
+                budget,
                 gas_price,
             )
-            .await?;
+        };
+
+        let dry_run = client
+            .read_api()
+            .dry_run_transaction_block(build_transaction(max_gas_budget))
+            .await
+            .map_err(|e| SuiClientError::from_rpc_error(function, e))?;
+        check_effects_status(function, &dry_run.effects)?;
+
+        let gas_cost = dry_run.effects.gas_cost_summary();
+        let measured = gas_cost.computation_cost + gas_cost.storage_cost;
+        let estimated_gas_budget = measured + measured * GAS_BUDGET_SAFETY_MARGIN_PERCENT / 100;
+        if estimated_gas_budget > max_gas_budget {
+            return Err(SuiClientError::GasBudgetExceeded {
+                estimated: estimated_gas_budget,
+                budget: max_gas_budget,
+            });
+        }
+
+        let tx = build_transaction(estimated_gas_budget);
+        self.sign_and_execute(&client, function, tx).await
+    }
 
+    /// Signs and executes a transaction, checking the status of its effects
+    async fn sign_and_execute(
+        &self,
+        client: &SuiClient,
+        function: &'static str,
+        tx: TransactionData,
+    ) -> Result<SuiTransactionBlockResponse> {
         info!(
-            target = "sui-client-withdraw-funds-from-treasury-pool",
+            target = "sui-client",
+            event = "execute-transaction",
+            function,
             tx_hash = %tx.digest(),
-            winner_address = %winner_address,
-            "Withdrew funds from treasury pool for winner"
+            "Executing transaction"
         );
-
         let tx = self.wallet_context.sign_transaction(&tx);
-        let response = self
-            .wallet_context
-            .execute_transaction_must_succeed(tx)
-            .await;
+        let response = client
+            .quorum_driver_api()
+            .execute_transaction_block(
+                tx,
+                SuiTransactionBlockResponseOptions::new().with_effects(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await
+            .map_err(|e| SuiClientError::from_rpc_error(function, e))?;
+        let effects = response
+            .effects
+            .as_ref()
+            .ok_or(SuiClientError::MissingEffects(function))?;
+        check_effects_status(function, effects)?;
 
-        info!(
-            target = "sui-client-withdraw-funds-from-treasury-pool",
-            tx_hash = %response.digest,
-            "Successfully withdrew funds from treasury pool for winner"
-        );
+        Ok(response)
+    }
 
-        Ok(response.digest.to_string())
+    /// Maintains the gas pool of the wallet.
+    ///
+    /// Dust coins are merged into the largest coin, and coins are split from the largest
+    /// coin until the pool holds the configured number of coins, as far as the balance
+    /// allows. An alert is logged if the balance is below the configured thresholds.
+    ///
+    /// # Returns
+    ///
+    /// Returns how the wallet balance compares to the configured thresholds
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the coins of the wallet cannot be fetched,
+    /// or a merge or split transaction fails
+    #[instrument(level = "info", skip_all)]
+    pub async fn maintain_gas_pool(&mut self) -> Result<BalanceStatus> {
+        let client = self.wallet_context.get_client().await?;
+        let active_address = self.wallet_context.active_address()?;
+        let gas_price = client.read_api().get_reference_gas_price().await?;
+
+        self.refresh_gas_pool(&client, active_address).await?;
+        if let Some((primary, dust)) = self.gas_pool.coins_to_merge() {
+            info!(
+                target = "sui-client",
+                event = "gas-pool-merge",
+                num_coins = dust.len(),
+                "Merging dust gas coins"
+            );
+            // All the gas payment coins are merged into the first one, which is
+            // then transferred back to the wallet
+            let mut builder = ProgrammableTransactionBuilder::new();
+            builder.pay_all_sui(active_address);
+            let tx = TransactionData::new_programmable(
+                active_address,
+                std::iter::once(primary).chain(dust).collect(),
+                builder.finish(),
+                GAS_POOL_MAINTENANCE_GAS_BUDGET,
+                gas_price,
+            );
+            self.sign_and_execute(&client, "merge_gas_coins", tx)
+                .await?;
+            self.refresh_gas_pool(&client, active_address).await?;
+        }
+        if let Some((coin, amounts)) = self.gas_pool.coins_to_split() {
+            info!(
+                target = "sui-client",
+                event = "gas-pool-split",
+                num_coins = amounts.len(),
+                "Splitting gas coins"
+            );
+            let mut builder = ProgrammableTransactionBuilder::new();
+            builder.pay_sui(vec![active_address; amounts.len()], amounts)?;
+            let tx = TransactionData::new_programmable(
+                active_address,
+                vec![coin],
+                builder.finish(),
+                GAS_POOL_MAINTENANCE_GAS_BUDGET,
+                gas_price,
+            );
+            self.sign_and_execute(&client, "split_gas_coins", tx)
+                .await?;
+            self.refresh_gas_pool(&client, active_address).await?;
+        }
+
+        let status = self.gas_pool.balance_status();
+        if status != BalanceStatus::Healthy {
+            warn!(
+                target = "sui-client",
+                event = "low-gas-balance",
+                balance = self.gas_pool.total_balance(),
+                ?status,
+                "Wallet SUI balance is low, top up the agent wallet"
+            );
+        }
+        Ok(status)
+    }
+
+    /// Replaces the coins of the gas pool with the SUI coins currently owned by the wallet
+    async fn refresh_gas_pool(&mut self, client: &SuiClient, owner: SuiAddress) -> Result<()> {
+        let mut coins = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client
+                .coin_read_api()
+                .get_coins(owner, None, cursor, Some(GAS_COINS_PAGE_LIMIT))
+                .await?;
+            coins.extend(page.data);
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        self.gas_pool.update(coins);
+        Ok(())
+    }
+
+    /// Refuses to go on if the wallet balance is below the configured minimum, and logs
+    /// an alert if it is below the low balance threshold
+    fn check_gas_balance(&self) -> Result<()> {
+        let balance = self.gas_pool.total_balance();
+        match self.gas_pool.balance_status() {
+            BalanceStatus::Healthy => Ok(()),
+            BalanceStatus::Low => {
+                warn!(
+                    target = "sui-client",
+                    event = "low-gas-balance",
+                    balance,
+                    "Wallet SUI balance is low, top up the agent wallet"
+                );
+                Ok(())
+            }
+            BalanceStatus::Critical => {
+                error!(
+                    target = "sui-client",
+                    event = "critical-gas-balance",
+                    balance,
+                    "Wallet SUI balance is below the minimum, refusing to execute transactions"
+                );
+                Err(SuiClientError::InsufficientGasBalance {
+                    balance,
+                    min_balance: self.gas_pool.min_balance(),
+                })
+            }
+        }
+    }
+
+    /// Returns the Secret Guessing database as a mutable shared object argument,
+    /// fetching the version at which it became shared on first use
+    async fn guess_ai_db_object_arg(&mut self, client: &SuiClient) -> Result<ObjectArg> {
+        let initial_shared_version = match self.guess_ai_db_initial_shared_version {
+            Some(version) => version,
+            None => {
+                let object = client
+                    .read_api()
+                    .get_object_with_options(
+                        self.guess_ai_db,
+                        SuiObjectDataOptions::new().with_owner(),
+                    )
+                    .await?;
+                let version = match object.data.and_then(|data| data.owner) {
+                    Some(Owner::Shared {
+                        initial_shared_version,
+                    }) => initial_shared_version,
+                    _ => return Err(SuiClientError::NotSharedObject(self.guess_ai_db)),
+                };
+                self.guess_ai_db_initial_shared_version = Some(version);
+                version
+            }
+        };
+        Ok(ObjectArg::SharedObject {
+            id: self.guess_ai_db,
+            initial_shared_version,
+            mutable: true,
+        })
+    }
+}
+
+/// Checks the execution status of the effects of a transaction calling `function`,
+/// mapping failures to typed errors
+fn check_effects_status(
+    function: &'static str,
+    effects: &SuiTransactionBlockEffects,
+) -> Result<()> {
+    match effects.status() {
+        SuiExecutionStatus::Success => Ok(()),
+        SuiExecutionStatus::Failure { error } => {
+            let error = SuiClientError::from_execution_failure(function, error);
+            error!(
+                target = "sui-client",
+                event = "transaction-failed",
+                function,
+                "Transaction failed: {error}"
+            );
+            Err(error)
+        }
     }
 }
 
+/// The abort codes of the GuessAI game smart contract, kept in sync with the `E*` constants
+/// of its Move module. The normalized ABI does not carry the constants, so a code added to
+/// the contract is reported as `Unknown` until it is added here
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuessAiAbortCode {
+    /// The sender is not the agent registered in the game database
+    NotAgent,
+    /// The game is inactive
+    GameInactive,
+    /// The treasury pool holds no funds to withdraw
+    EmptyTreasuryPool,
+    /// The TDX attestation quote is invalid
+    InvalidTdxQuote,
+    /// The node public key is not a valid x25519 public key
+    InvalidPublicKey,
+    /// An abort code unknown to this version of the client
+    Unknown(u64),
+}
+
+impl GuessAiAbortCode {
+    /// Maps a Move abort code of the contract to its variant
+    pub fn from_code(code: u64) -> Self {
+        match code {
+            0 => Self::NotAgent,
+            1 => Self::GameInactive,
+            2 => Self::EmptyTreasuryPool,
+            3 => Self::InvalidTdxQuote,
+            4 => Self::InvalidPublicKey,
+            code => Self::Unknown(code),
+        }
+    }
+
+    /// The Move abort code of the variant
+    pub fn code(self) -> u64 {
+        match self {
+            Self::NotAgent => 0,
+            Self::GameInactive => 1,
+            Self::EmptyTreasuryPool => 2,
+            Self::InvalidTdxQuote => 3,
+            Self::InvalidPublicKey => 4,
+            Self::Unknown(code) => code,
+        }
+    }
+}
+
+impl std::fmt::Display for GuessAiAbortCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAgent => write!(f, "sender is not the game agent"),
+            Self::GameInactive => write!(f, "game is inactive"),
+            Self::EmptyTreasuryPool => write!(f, "treasury pool is empty"),
+            Self::InvalidTdxQuote => write!(f, "invalid TDX attestation quote"),
+            Self::InvalidPublicKey => write!(f, "invalid node public key"),
+            Self::Unknown(code) => write!(f, "unknown abort code {code}"),
+        }
+    }
+}
+
+/// Extracts the module name and abort code from a Move abort execution error, e.g.
+/// `MoveAbort(MoveLocation { module: ModuleId { address: .., name: Identifier("contract") }, .. }, 1) in command 0`
+pub fn parse_move_abort(error: &str) -> Option<(&str, u64)> {
+    let abort = &error[error.find("MoveAbort(")?..];
+    let abort = abort
+        .find(") in command")
+        .map_or(abort, |end| &abort[..end]);
+    let code = abort
+        .rsplit(", ")
+        .next()?
+        .trim_end_matches(')')
+        .parse()
+        .ok()?;
+    let module = abort
+        .split("name: Identifier(\"")
+        .nth(1)?
+        .split('"')
+        .next()?;
+    Some((module, code))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum SuiClientError {
     #[error("Failed to get active address")]
     GetActiveAddressError(#[from] SuiError),
-    #[error("Failed to parse object ID")]
-    ParseObjectIDError(#[from] ObjectIDParseError),
-    #[error("Failed to withdraw funds from treasury pool")]
-    WithdrawFundsFromTreasuryPoolError(#[from] anyhow::Error),
+    #[error("Failed to build transaction: {0}")]
+    TransactionBuildError(#[from] anyhow::Error),
+    #[error("Wallet balance {balance} MIST is below the minimum balance {min_balance} MIST")]
+    InsufficientGasBalance { balance: u64, min_balance: u64 },
+    #[error("Failed to read the game state: {0}")]
+    GameStateError(#[from] GameStateError),
+    #[error("Object {0} is not a shared object")]
+    NotSharedObject(ObjectID),
+    #[error("Contract call `{function}` aborted: {code}")]
+    MoveAbort {
+        function: &'static str,
+        code: GuessAiAbortCode,
+    },
+    #[error("Contract call `{function}` failed: {error}")]
+    ExecutionFailed {
+        function: &'static str,
+        error: String,
+    },
+    #[error("Transaction conflicted with a concurrent transaction: {0}")]
+    ObjectVersionConflict(String),
+    #[error("Estimated gas budget {estimated} exceeds the gas budget {budget}")]
+    GasBudgetExceeded { estimated: u64, budget: u64 },
+    #[error("Transaction response for `{0}` has no effects")]
+    MissingEffects(&'static str),
+    #[error("Sui RPC error: {0}")]
+    RpcError(#[from] sui_sdk::error::Error),
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+}
+
+impl SuiClientError {
+    /// Maps the error of a failed transaction execution, aborts of the contract
+    /// module being mapped to their abort code
+    fn from_execution_failure(function: &'static str, error: &str) -> Self {
+        match parse_move_abort(error) {
+            Some((module, code)) if module == GUESS_AI_MODULE_NAME => Self::MoveAbort {
+                function,
+                code: GuessAiAbortCode::from_code(code),
+            },
+            _ if is_object_version_conflict(error) => {
+                Self::ObjectVersionConflict(error.to_string())
+            }
+            _ => Self::ExecutionFailed {
+                function,
+                error: error.to_string(),
+            },
+        }
+    }
+
+    /// Maps an RPC error, singling out object version conflicts which can be retried
+    fn from_rpc_error(function: &'static str, error: sui_sdk::error::Error) -> Self {
+        let message = error.to_string();
+        if is_object_version_conflict(&message) {
+            Self::ObjectVersionConflict(message)
+        } else if parse_move_abort(&message).is_some() {
+            Self::from_execution_failure(function, &message)
+        } else {
+            Self::RpcError(error)
+        }
+    }
+}
+
+/// Whether an error message reports a conflict on the version or lock of an input object
+fn is_object_version_conflict(message: &str) -> bool {
+    OBJECT_VERSION_CONFLICT_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The execution error of a transaction aborted by `module` with `code`
+    fn move_abort(module: &str, code: u64) -> String {
+        format!(
+            "MoveAbort(MoveLocation {{ module: ModuleId {{ address: \
+             0000000000000000000000000000000000000000000000000000000000000abc, \
+             name: Identifier(\"{module}\") }}, function: 3, instruction: 12, \
+             function_name: Some(\"withdraw_funds_from_treasury_pool\") }}, {code}) in command 0"
+        )
+    }
+
+    #[test]
+    fn parse_move_abort_of_the_contract_module() {
+        let error = move_abort(GUESS_AI_MODULE_NAME, 2);
+        assert_eq!(parse_move_abort(&error), Some((GUESS_AI_MODULE_NAME, 2)));
+        assert!(matches!(
+            SuiClientError::from_execution_failure("withdraw_funds_from_treasury_pool", &error),
+            SuiClientError::MoveAbort {
+                function: "withdraw_funds_from_treasury_pool",
+                code: GuessAiAbortCode::EmptyTreasuryPool,
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_move_abort_of_another_module() {
+        let error = move_abort("balance", 2);
+        assert_eq!(parse_move_abort(&error), Some(("balance", 2)));
+        assert!(matches!(
+            SuiClientError::from_execution_failure("withdraw_funds_from_treasury_pool", &error),
+            SuiClientError::ExecutionFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn parse_move_abort_of_a_failure_which_is_not_an_abort() {
+        let error = "InsufficientGas in command 0";
+        assert_eq!(parse_move_abort(error), None);
+        assert!(matches!(
+            SuiClientError::from_execution_failure("submit_node_public_key", error),
+            SuiClientError::ExecutionFailed { .. }
+        ));
+        assert!(matches!(
+            SuiClientError::from_execution_failure(
+                "submit_node_public_key",
+                "Object 0xabc is not available for consumption"
+            ),
+            SuiClientError::ObjectVersionConflict(_)
+        ));
+    }
+
+    #[test]
+    fn abort_codes_round_trip() {
+        for code in 0..8 {
+            assert_eq!(GuessAiAbortCode::from_code(code).code(), code);
+        }
+        assert_eq!(
+            GuessAiAbortCode::from_code(1),
+            GuessAiAbortCode::GameInactive
+        );
+        assert_eq!(
+            GuessAiAbortCode::from_code(42),
+            GuessAiAbortCode::Unknown(42)
+        );
+    }
 }
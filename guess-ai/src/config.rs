@@ -1,25 +1,41 @@
-use std::path::Path;
+use std::{cmp::Ordering, fmt, fs::File, net::ToSocketAddrs, path::Path, str::FromStr};
 
 use config::Config;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::ObjectID;
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    atoma::AtomaTransportConfig,
+    network::{Network, NetworkProfile},
+    payout::PayoutDecision,
+    secrets::{KeystoreConfig, Secret, SecretError, SecretProviders},
+};
 
 /// Configuration for the Secret Guessing application
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct GuessAiConfig {
-    /// API key for Atoma service authentication
-    pub atoma_api_key: String,
+    /// API key for Atoma service authentication. Not needed with a local inference backend
+    #[serde(default)]
+    pub atoma_api_key: Secret,
 
     /// Twitter consumer key
-    pub twitter_consumer_key: String,
+    pub twitter_consumer_key: Secret,
 
     /// Twitter consumer secret
-    pub twitter_consumer_secret: String,
+    pub twitter_consumer_secret: Secret,
 
     /// Twitter access token
-    pub twitter_access_token: String,
+    pub twitter_access_token: Secret,
 
     /// Twitter access token secret
-    pub twitter_access_token_secret: String,
+    pub twitter_access_token_secret: Secret,
+
+    /// The encrypted keystore the secrets can be read from, unlocked at boot
+    #[serde(default)]
+    pub keystore: Option<KeystoreConfig>,
 
     /// File path for storing cursor information
     pub cursor_path: String,
@@ -27,7 +43,8 @@ pub struct GuessAiConfig {
     /// The number of consecutive guesses to wait before providing a new hint
     pub hint_wait_count: u64,
 
-    /// HTTP address of the RPC node
+    /// HTTP address of the RPC node, taken from the network profile if one is selected
+    #[serde(default)]
     pub http_rpc_node_addr: String,
 
     /// The model to use for the Atoma service
@@ -36,10 +53,14 @@ pub struct GuessAiConfig {
     /// Limit for the number of events to fetch per request
     pub limit: Option<usize>,
 
-    /// Package identifier for the smart contract
+    /// Package identifier for the smart contract, taken from the network profile if one is
+    /// selected
+    #[serde(default)]
     pub guess_ai_package_id: String,
 
-    /// Database identifier for the smart contract
+    /// Database identifier for the smart contract, taken from the network profile if one is
+    /// selected
+    #[serde(default)]
     pub guess_ai_db: String,
 
     /// Optional timeout duration for requests in seconds
@@ -50,6 +71,348 @@ pub struct GuessAiConfig {
 
     /// Sui's config path
     pub sui_config_path: String,
+
+    /// Optional file path where inference usage totals are persisted
+    pub usage_path: Option<String>,
+
+    /// File path of the audit log of the privileged actions taken by the engine
+    #[serde(default = "default_audit_log_path")]
+    pub audit_log_path: String,
+
+    /// File path where an operator pause of the engine is persisted, so that it survives restarts
+    #[serde(default = "default_pause_state_path")]
+    pub pause_state_path: String,
+
+    /// File path where the payouts held for approval are persisted, so that they survive
+    /// restarts
+    #[serde(default = "default_payout_state_path")]
+    pub payout_state_path: String,
+
+    /// Whether the engine pauses while the game is inactive on-chain, and resumes once it is
+    /// reactivated
+    #[serde(default = "default_pause_when_game_inactive")]
+    pub pause_when_game_inactive: bool,
+
+    /// Log filter directives, e.g. `info,guess_ai=debug`, overriding `RUST_LOG` if set
+    #[serde(default)]
+    pub log_level: Option<String>,
+
+    /// The inference backend, the Atoma API unless a local backend is configured
+    #[serde(default)]
+    pub inference_backend: InferenceBackendConfig,
+
+    /// Transport configuration for the requests to the Atoma API
+    #[serde(default)]
+    pub atoma_transport: AtomaTransportConfig,
+
+    /// Compute budget for the inference requests of each game
+    #[serde(default)]
+    pub compute_budget: ComputeBudgetConfig,
+
+    /// Semantic similarity ("hot/cold") feedback for guesses
+    #[serde(default)]
+    pub similarity: SimilarityConfig,
+
+    /// Gas coin pool and balance monitoring of the agent wallet
+    #[serde(default)]
+    pub gas_pool: GasPoolConfig,
+
+    /// Approval policy for the payouts of large treasury pools
+    #[serde(default)]
+    pub payout_policy: PayoutPolicyConfig,
+}
+
+fn default_audit_log_path() -> String {
+    "./audit_log.jsonl".to_string()
+}
+
+fn default_pause_state_path() -> String {
+    "./pause_state.json".to_string()
+}
+
+fn default_payout_state_path() -> String {
+    "./payout_state.json".to_string()
+}
+
+fn default_pause_when_game_inactive() -> bool {
+    true
+}
+
+/// The inference backend the engine sends its requests to
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InferenceBackendConfig {
+    /// The Atoma API, through confidential compute when the feature is enabled
+    #[default]
+    Atoma,
+
+    /// A local OpenAI-compatible server, such as llama.cpp server or a stub, for offline
+    /// development. Requests skip the confidential compute envelope, so the backend is refused
+    /// unless a network other than mainnet is selected with `--network`, or `allow_plaintext`
+    /// is set.
+    ///
+    /// Only the inference is local: the engine still reads the game db object and subscribes
+    /// to the contract events over Sui RPC at startup, e.g. from a `sui start` localnet
+    Local {
+        /// Base URL of the server, without the `/v1` suffix, e.g. `http://localhost:8080`
+        base_url: String,
+
+        /// Optional API key, sent as a bearer token
+        #[serde(default)]
+        api_key: Option<Secret>,
+
+        /// Allows the plain text requests whatever the network, for development against a
+        /// wallet on mainnet or without `--network`
+        #[serde(default)]
+        allow_plaintext: bool,
+    },
+}
+
+impl InferenceBackendConfig {
+    /// Whether the backend may be used with `network` selected: the local backend sends the
+    /// confidential requests in plain text, and is only allowed on a network other than
+    /// mainnet, or with `allow_plaintext` set
+    pub fn is_allowed_on(&self, network: Option<Network>) -> bool {
+        match self {
+            Self::Atoma => true,
+            Self::Local {
+                allow_plaintext, ..
+            } => *allow_plaintext || network.is_some_and(|n| n != Network::Mainnet),
+        }
+    }
+}
+
+/// Compute budget configuration.
+///
+/// The compute cost of a game is compared against its treasury revenue, and once it
+/// reaches the configured thresholds the engine switches to a cheaper model and/or
+/// stops issuing hints.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ComputeBudgetConfig {
+    /// Price of each model, used to convert token usage into MIST
+    #[serde(default)]
+    pub model_prices: Vec<ModelPrice>,
+
+    /// Cheaper model to switch to once the budget is nearing exhaustion
+    pub fallback_model: Option<String>,
+
+    /// Compute cost, in per mille of the game's treasury revenue, above which
+    /// the `fallback_model` is used
+    #[serde(default = "default_fallback_threshold_per_mille")]
+    pub fallback_threshold_per_mille: u64,
+
+    /// Compute cost, in per mille of the game's treasury revenue, above which
+    /// no more hints are issued
+    #[serde(default = "default_stop_hints_threshold_per_mille")]
+    pub stop_hints_threshold_per_mille: u64,
+
+    /// The maximum number of completion tokens of a secret generation request
+    #[serde(default = "default_secret_max_tokens")]
+    pub secret_max_tokens: u32,
+
+    /// The maximum number of completion tokens of a judge request
+    #[serde(default = "default_judge_max_tokens")]
+    pub judge_max_tokens: u32,
+
+    /// The maximum number of completion tokens of a hint request, including the tool calls
+    /// the model makes before answering
+    #[serde(default = "default_hint_max_tokens")]
+    pub hint_max_tokens: u32,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            model_prices: Vec::new(),
+            fallback_model: None,
+            fallback_threshold_per_mille: default_fallback_threshold_per_mille(),
+            stop_hints_threshold_per_mille: default_stop_hints_threshold_per_mille(),
+            secret_max_tokens: default_secret_max_tokens(),
+            judge_max_tokens: default_judge_max_tokens(),
+            hint_max_tokens: default_hint_max_tokens(),
+        }
+    }
+}
+
+/// The price of a model
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ModelPrice {
+    /// The model identifier, as used in the inference requests
+    pub model: String,
+
+    /// Price in MIST per one million (prompt + completion) tokens
+    pub mist_per_million_tokens: u64,
+}
+
+fn default_fallback_threshold_per_mille() -> u64 {
+    500
+}
+
+fn default_stop_hints_threshold_per_mille() -> u64 {
+    800
+}
+
+fn default_secret_max_tokens() -> u32 {
+    256
+}
+
+fn default_judge_max_tokens() -> u32 {
+    512
+}
+
+fn default_hint_max_tokens() -> u32 {
+    1_024
+}
+
+/// Semantic similarity configuration.
+///
+/// When an embedding model is set, every guess is scored by the cosine similarity
+/// between its embedding and the secret's, and players get the bucket of the score.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SimilarityConfig {
+    /// The embedding model used to score guesses. Similarity feedback is disabled if unset
+    pub embedding_model: Option<String>,
+
+    /// Cosine similarity from which a guess is `cool`
+    #[serde(default = "default_cool_threshold")]
+    pub cool_threshold: f32,
+
+    /// Cosine similarity from which a guess is `warm`
+    #[serde(default = "default_warm_threshold")]
+    pub warm_threshold: f32,
+
+    /// Cosine similarity from which a guess is `hot`
+    #[serde(default = "default_hot_threshold")]
+    pub hot_threshold: f32,
+}
+
+impl Default for SimilarityConfig {
+    fn default() -> Self {
+        Self {
+            embedding_model: None,
+            cool_threshold: default_cool_threshold(),
+            warm_threshold: default_warm_threshold(),
+            hot_threshold: default_hot_threshold(),
+        }
+    }
+}
+
+fn default_cool_threshold() -> f32 {
+    0.3
+}
+
+fn default_warm_threshold() -> f32 {
+    0.5
+}
+
+fn default_hot_threshold() -> f32 {
+    0.7
+}
+
+/// Gas coin pool configuration.
+///
+/// The agent wallet's SUI is kept split across `pool_size` coins, so that concurrent
+/// transactions don't contend on a single gas coin, and its balance is checked before
+/// every transaction. All amounts are in MIST.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GasPoolConfig {
+    /// The number of gas coins to keep in the pool
+    #[serde(default = "default_gas_pool_size")]
+    pub pool_size: usize,
+
+    /// The balance of each coin split into the pool
+    #[serde(default = "default_gas_coin_balance")]
+    pub coin_balance: u64,
+
+    /// Coins with at most this balance are merged together
+    #[serde(default = "default_gas_dust_threshold")]
+    pub dust_threshold: u64,
+
+    /// Balance below which an alert is logged before every transaction
+    #[serde(default = "default_low_balance_threshold")]
+    pub low_balance_threshold: u64,
+
+    /// Balance below which transactions are refused
+    #[serde(default = "default_min_balance")]
+    pub min_balance: u64,
+}
+
+impl Default for GasPoolConfig {
+    fn default() -> Self {
+        Self {
+            pool_size: default_gas_pool_size(),
+            coin_balance: default_gas_coin_balance(),
+            dust_threshold: default_gas_dust_threshold(),
+            low_balance_threshold: default_low_balance_threshold(),
+            min_balance: default_min_balance(),
+        }
+    }
+}
+
+fn default_gas_pool_size() -> usize {
+    4
+}
+
+fn default_gas_coin_balance() -> u64 {
+    200_000_000 // 0.2 SUI
+}
+
+fn default_gas_dust_threshold() -> u64 {
+    50_000_000 // 0.05 SUI
+}
+
+fn default_low_balance_threshold() -> u64 {
+    1_000_000_000 // 1 SUI
+}
+
+fn default_min_balance() -> u64 {
+    100_000_000 // 0.1 SUI
+}
+
+/// Payout approval policy.
+///
+/// A correct guess whose payout reaches `approval_threshold` is not paid out right
+/// away: it is held with its evidence until an operator (through the admin endpoint)
+/// or a second automated judge approves or rejects it, or until `approval_timeout`
+/// elapses and `timeout_action` applies.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PayoutPolicyConfig {
+    /// Treasury pool balance, in MIST, from which payouts are held for approval.
+    /// Payouts are never held if unset
+    #[serde(default)]
+    pub approval_threshold: Option<u64>,
+
+    /// How long a held payout waits for a decision, in seconds
+    #[serde(default = "default_approval_timeout")]
+    pub approval_timeout: u64,
+
+    /// The decision applied to a held payout once `approval_timeout` elapses
+    #[serde(default = "default_timeout_action")]
+    pub timeout_action: PayoutDecision,
+
+    /// Model of a second automated judge, asked to confirm the guess before an
+    /// operator has to. Held payouts wait for an operator if unset
+    #[serde(default)]
+    pub second_judge_model: Option<String>,
+}
+
+impl Default for PayoutPolicyConfig {
+    fn default() -> Self {
+        Self {
+            approval_threshold: None,
+            approval_timeout: default_approval_timeout(),
+            timeout_action: default_timeout_action(),
+            second_judge_model: None,
+        }
+    }
+}
+
+fn default_approval_timeout() -> u64 {
+    3600 // 1 hour
+}
+
+fn default_timeout_action() -> PayoutDecision {
+    PayoutDecision::Reject
 }
 
 impl GuessAiConfig {
@@ -76,21 +439,581 @@ impl GuessAiConfig {
     /// * The configuration values don't match the expected structure
     /// * The "guess_ai" section is missing from the configuration
     pub fn from_file_path<P: AsRef<Path>>(config_file_path: P) -> Self {
-        let builder = Config::builder()
+        Self::try_from_file_path(config_file_path)
+            .expect("Failed to generate configuration instance")
+    }
+
+    /// Loads a `GuessAiConfig` instance like [`Self::from_file_path`], without validating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "guess_ai" section doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+    ) -> Result<Self, config::ConfigError> {
+        Config::builder()
             .add_source(config::File::with_name(
-                config_file_path.as_ref().to_str().unwrap(),
+                &config_file_path.as_ref().to_string_lossy(),
             ))
             .add_source(
                 config::Environment::with_prefix("GUESS_AI")
                     .keep_prefix(true)
                     .separator("__"),
+            )
+            .build()?
+            .get::<Self>("guess_ai")
+    }
+
+    /// Loads the configuration like [`Self::try_from_file_path`], applies the profile of the
+    /// selected network, reads its secrets (unlocking the keystore, if one is configured) and
+    /// validates it, recording every problem found in `validator`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - Path to the configuration file
+    /// * `network` - The network selected with `--network`, if any
+    /// * `validator` - Records the problems found
+    ///
+    /// # Returns
+    ///
+    /// The configuration if it could be loaded, valid or not, and the secret providers if the
+    /// keystore could be unlocked, to read the secrets of the other sections with
+    pub fn load<P: AsRef<Path>>(
+        config_file_path: P,
+        network: Option<Network>,
+        validator: &mut ConfigValidator,
+    ) -> (Option<Self>, Option<SecretProviders>) {
+        let config_file_path = config_file_path.as_ref();
+        let Some(mut config) =
+            validator.load("guess_ai", Self::try_from_file_path(config_file_path))
+        else {
+            return (None, None);
+        };
+        if let Some(network) = network {
+            if let Some(profile) = validator.load(
+                &format!("networks.{network}"),
+                NetworkProfile::try_from_file_path(config_file_path, network),
+            ) {
+                config.apply_network_profile(network, &profile);
+            }
+        }
+        let providers = match SecretProviders::new(config.keystore.as_ref()) {
+            Ok(providers) => {
+                config.resolve_secrets(&providers, validator);
+                Some(providers)
+            }
+            // The secrets are left unresolved, the invalid keystore is reported instead
+            Err(e) => {
+                validator.report("guess_ai.keystore", e);
+                None
+            }
+        };
+        config.validate(validator);
+        if !config.inference_backend.is_allowed_on(network) {
+            validator.report(
+                "guess_ai.inference_backend",
+                ConfigProblem::PlaintextBackend,
             );
+        }
+        (Some(config), providers)
+    }
 
-        let config = builder
-            .build()
-            .expect("Failed to generate atoma state configuration file");
-        config
-            .get::<Self>("guess_ai")
-            .expect("Failed to generate configuration instance")
+    /// Points the configuration at the deployment of the game on `network`, overriding the
+    /// RPC node address, the package ID and the db ID
+    pub fn apply_network_profile(&mut self, network: Network, profile: &NetworkProfile) {
+        self.http_rpc_node_addr = profile.rpc_url(network).to_string();
+        self.guess_ai_package_id = profile.package_id.to_string();
+        self.guess_ai_db = profile.db_id.to_string();
+    }
+
+    /// Reads the secrets which reference a provider, recording the ones which cannot be read
+    /// in `validator`
+    pub fn resolve_secrets(
+        &mut self,
+        providers: &SecretProviders,
+        validator: &mut ConfigValidator,
+    ) {
+        if let InferenceBackendConfig::Local {
+            api_key: Some(api_key),
+            ..
+        } = &mut self.inference_backend
+        {
+            validator.resolve("guess_ai.inference_backend.api_key", api_key, providers);
+        }
+        validator.resolve("guess_ai.atoma_api_key", &mut self.atoma_api_key, providers);
+        validator.resolve(
+            "guess_ai.twitter_consumer_key",
+            &mut self.twitter_consumer_key,
+            providers,
+        );
+        validator.resolve(
+            "guess_ai.twitter_consumer_secret",
+            &mut self.twitter_consumer_secret,
+            providers,
+        );
+        validator.resolve(
+            "guess_ai.twitter_access_token",
+            &mut self.twitter_access_token,
+            providers,
+        );
+        validator.resolve(
+            "guess_ai.twitter_access_token_secret",
+            &mut self.twitter_access_token_secret,
+            providers,
+        );
+    }
+
+    /// Checks the configuration values, recording every problem found in `validator`.
+    ///
+    /// This covers what deserialization cannot: empty credentials, malformed object IDs,
+    /// a zero hint cadence, token cap, gas coin balance or approval timeout, thresholds out
+    /// of order, invalid URLs, and files or directories which are unreachable.
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        if let InferenceBackendConfig::Local { base_url, .. } = &self.inference_backend {
+            validator.url("guess_ai.inference_backend.base_url", base_url);
+        } else {
+            validator.non_empty_secret("guess_ai.atoma_api_key", &self.atoma_api_key);
+        }
+        validator.non_empty_secret("guess_ai.twitter_consumer_key", &self.twitter_consumer_key);
+        validator.non_empty_secret(
+            "guess_ai.twitter_consumer_secret",
+            &self.twitter_consumer_secret,
+        );
+        validator.non_empty_secret("guess_ai.twitter_access_token", &self.twitter_access_token);
+        validator.non_empty_secret(
+            "guess_ai.twitter_access_token_secret",
+            &self.twitter_access_token_secret,
+        );
+        if let Some(keystore) = &self.keystore {
+            validator.readable_file("guess_ai.keystore.path", &keystore.path);
+        }
+        validator.non_empty("guess_ai.model", &self.model);
+        validator.non_zero("guess_ai.hint_wait_count", self.hint_wait_count);
+        validator.url("guess_ai.http_rpc_node_addr", &self.http_rpc_node_addr);
+        validator.object_id("guess_ai.guess_ai_package_id", &self.guess_ai_package_id);
+        validator.object_id("guess_ai.guess_ai_db", &self.guess_ai_db);
+        validator.readable_file("guess_ai.sui_config_path", &self.sui_config_path);
+        validator.writable_location("guess_ai.cursor_path", &self.cursor_path);
+        if let Some(usage_path) = &self.usage_path {
+            validator.writable_location("guess_ai.usage_path", usage_path);
+        }
+        validator.writable_location("guess_ai.audit_log_path", &self.audit_log_path);
+        validator.writable_location("guess_ai.pause_state_path", &self.pause_state_path);
+        validator.writable_location("guess_ai.payout_state_path", &self.payout_state_path);
+        for (field, max_tokens) in [
+            (
+                "guess_ai.compute_budget.secret_max_tokens",
+                self.compute_budget.secret_max_tokens,
+            ),
+            (
+                "guess_ai.compute_budget.judge_max_tokens",
+                self.compute_budget.judge_max_tokens,
+            ),
+            (
+                "guess_ai.compute_budget.hint_max_tokens",
+                self.compute_budget.hint_max_tokens,
+            ),
+        ] {
+            validator.non_zero(field, u64::from(max_tokens));
+        }
+        validator.less_than(
+            "guess_ai.similarity.cool_threshold",
+            self.similarity.cool_threshold,
+            "warm_threshold",
+            self.similarity.warm_threshold,
+        );
+        validator.less_than(
+            "guess_ai.similarity.warm_threshold",
+            self.similarity.warm_threshold,
+            "hot_threshold",
+            self.similarity.hot_threshold,
+        );
+        validator.non_zero("guess_ai.gas_pool.coin_balance", self.gas_pool.coin_balance);
+        validator.less_than(
+            "guess_ai.gas_pool.dust_threshold",
+            self.gas_pool.dust_threshold,
+            "coin_balance",
+            self.gas_pool.coin_balance,
+        );
+        validator.at_most(
+            "guess_ai.gas_pool.min_balance",
+            self.gas_pool.min_balance,
+            "low_balance_threshold",
+            self.gas_pool.low_balance_threshold,
+        );
+        validator.non_zero(
+            "guess_ai.payout_policy.approval_timeout",
+            self.payout_policy.approval_timeout,
+        );
+        if let Some(log_level) = &self.log_level {
+            validator.log_filter("guess_ai.log_level", log_level);
+        }
+    }
+}
+
+/// A problem found in a configuration value
+#[derive(Debug, Error)]
+pub enum ConfigProblem {
+    #[error("failed to load the section: {0}")]
+    Load(#[from] config::ConfigError),
+    #[error("must be set")]
+    Missing,
+    #[error("must not be empty")]
+    Empty,
+    #[error("must not be zero")]
+    Zero,
+    #[error("must be less than {bound_field} ({bound})")]
+    NotLessThan { bound_field: String, bound: String },
+    #[error("must be at most {bound_field} ({bound})")]
+    NotAtMost { bound_field: String, bound: String },
+    #[error("malformed object ID {0:?}")]
+    MalformedObjectId(String),
+    #[error("invalid URL {url:?}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+    #[error("invalid bind address {address:?}: {reason}")]
+    InvalidBindAddress { address: String, reason: String },
+    #[error("file {path:?} is unreachable: {reason}")]
+    UnreachableFile { path: String, reason: String },
+    #[error("directory of {path:?} does not exist")]
+    MissingDirectory { path: String },
+    #[error("{0}")]
+    Secret(#[from] SecretError),
+    #[error("invalid log filter {filter:?}: {reason}")]
+    InvalidLogFilter { filter: String, reason: String },
+    #[error("the local backend sends the confidential requests in plain text, select a network other than mainnet with --network, or set allow_plaintext = true")]
+    PlaintextBackend,
+}
+
+/// A problem found in the configuration, with the path of the offending field,
+/// e.g. `guess_ai.hint_wait_count`
+#[derive(Debug)]
+pub struct ConfigIssue {
+    /// The path of the field, or of the section if it could not be loaded
+    pub field: String,
+
+    /// The problem found
+    pub problem: ConfigProblem,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.problem)
+    }
+}
+
+/// Collects the problems found across the configuration sections, so that they are all
+/// reported at once rather than one per run.
+#[derive(Debug, Default)]
+pub struct ConfigValidator {
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidator {
+    /// Records a problem with the field at `field`
+    pub fn report(&mut self, field: &str, problem: impl Into<ConfigProblem>) {
+        self.issues.push(ConfigIssue {
+            field: field.to_string(),
+            problem: problem.into(),
+        });
+    }
+
+    /// Returns the loaded section, or records why it failed to load
+    pub fn load<T>(&mut self, section: &str, loaded: Result<T, config::ConfigError>) -> Option<T> {
+        loaded.map_err(|e| self.report(section, e)).ok()
+    }
+
+    /// Checks that the value is not empty
+    pub fn non_empty(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.report(field, ConfigProblem::Empty);
+        }
+    }
+
+    /// Checks that the secret, if it could be resolved, is not empty
+    pub fn non_empty_secret(&mut self, field: &str, secret: &Secret) {
+        if secret.is_resolved() {
+            self.non_empty(field, secret.expose_secret());
+        }
+    }
+
+    /// Reads the secret from its provider, recording why if it cannot be read
+    pub fn resolve(&mut self, field: &str, secret: &mut Secret, providers: &SecretProviders) {
+        if let Err(e) = secret.resolve(providers) {
+            self.report(field, e);
+        }
+    }
+
+    /// Checks that the value is not zero
+    pub fn non_zero(&mut self, field: &str, value: u64) {
+        if value == 0 {
+            self.report(field, ConfigProblem::Zero);
+        }
+    }
+
+    /// Checks that the value is less than the value of the sibling field `bound_field`
+    pub fn less_than<T: PartialOrd + fmt::Display>(
+        &mut self,
+        field: &str,
+        value: T,
+        bound_field: &str,
+        bound: T,
+    ) {
+        if value.partial_cmp(&bound) != Some(Ordering::Less) {
+            self.report(
+                field,
+                ConfigProblem::NotLessThan {
+                    bound_field: bound_field.to_string(),
+                    bound: bound.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Checks that the value is at most the value of the sibling field `bound_field`
+    pub fn at_most<T: PartialOrd + fmt::Display>(
+        &mut self,
+        field: &str,
+        value: T,
+        bound_field: &str,
+        bound: T,
+    ) {
+        if !matches!(
+            value.partial_cmp(&bound),
+            Some(Ordering::Less | Ordering::Equal)
+        ) {
+            self.report(
+                field,
+                ConfigProblem::NotAtMost {
+                    bound_field: bound_field.to_string(),
+                    bound: bound.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Checks that the value is a well-formed Sui object ID
+    pub fn object_id(&mut self, field: &str, value: &str) {
+        if ObjectID::from_str(value).is_err() {
+            self.report(field, ConfigProblem::MalformedObjectId(value.to_string()));
+        }
+    }
+
+    /// Checks that the value is a valid URL
+    pub fn url(&mut self, field: &str, value: &str) {
+        if let Err(e) = Url::parse(value) {
+            self.report(
+                field,
+                ConfigProblem::InvalidUrl {
+                    url: value.to_string(),
+                    reason: e.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Checks that the value is an address which can be bound to, e.g. `0.0.0.0:8080`
+    pub fn bind_address(&mut self, field: &str, value: &str) {
+        let reason = match value
+            .to_socket_addrs()
+            .map(|mut addresses| addresses.next())
+        {
+            Ok(Some(_)) => return,
+            Ok(None) => "it resolves to no address".to_string(),
+            Err(e) => e.to_string(),
+        };
+        self.report(
+            field,
+            ConfigProblem::InvalidBindAddress {
+                address: value.to_string(),
+                reason,
+            },
+        );
+    }
+
+    /// Checks that the value is valid log filter directives, e.g. `info,guess_ai=debug`
+    pub fn log_filter(&mut self, field: &str, value: &str) {
+        if let Err(e) = EnvFilter::try_new(value) {
+            self.report(
+                field,
+                ConfigProblem::InvalidLogFilter {
+                    filter: value.to_string(),
+                    reason: e.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Checks that the file at `path` exists and can be read
+    pub fn readable_file(&mut self, field: &str, path: &str) {
+        if let Err(e) = File::open(path) {
+            self.report(
+                field,
+                ConfigProblem::UnreachableFile {
+                    path: path.to_string(),
+                    reason: e.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Checks that the file at `path`, which the application creates if missing, is in an
+    /// existing directory
+    pub fn writable_location(&mut self, field: &str, path: &str) {
+        let directory = match Path::new(path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if !directory.is_dir() {
+            self.report(
+                field,
+                ConfigProblem::MissingDirectory {
+                    path: path.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Returns every problem found, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::Invalid` with the problems found, if any
+    pub fn finish(self) -> Result<(), ConfigError> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(self.issues))
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error(
+        "Invalid configuration, {} problem(s) found:\n{}",
+        .0.len(),
+        .0.iter().map(|issue| format!("  - {issue}")).collect::<Vec<_>>().join("\n")
+    )]
+    Invalid(Vec<ConfigIssue>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_server::HttpServerConfig;
+
+    /// Writes a configuration file whose paths point into `dir`, with the given values of the
+    /// fields under test
+    fn write_config(
+        dir: &tempfile::TempDir,
+        twitter_consumer_key: &str,
+        guess_ai_package_id: &str,
+        hint_wait_count: u64,
+        service_bind_address: &str,
+    ) -> std::path::PathBuf {
+        let dir_path = dir.path().display();
+        std::fs::write(dir.path().join("client.yaml"), "").unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+                [guess_ai]
+                atoma_api_key = "atoma-key"
+                twitter_consumer_key = "{twitter_consumer_key}"
+                twitter_consumer_secret = "consumer-secret"
+                twitter_access_token = "access-token"
+                twitter_access_token_secret = "access-token-secret"
+                cursor_path = "{dir_path}/cursor.toml"
+                hint_wait_count = {hint_wait_count}
+                model = "deepseek-ai/DeepSeek-V3"
+                http_rpc_node_addr = "https://fullnode.testnet.sui.io:443"
+                guess_ai_package_id = "{guess_ai_package_id}"
+                guess_ai_db = "0x2"
+                sui_config_path = "{dir_path}/client.yaml"
+                audit_log_path = "{dir_path}/audit.jsonl"
+                pause_state_path = "{dir_path}/pause.json"
+                payout_state_path = "{dir_path}/payouts.json"
+
+                [http_server]
+                service_bind_address = "{service_bind_address}"
+                "#
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    /// Loads and validates the `guess_ai` and `http_server` sections with a single validator,
+    /// as the engine does at boot
+    fn validate(path: &Path) -> Vec<ConfigIssue> {
+        let mut validator = ConfigValidator::default();
+        GuessAiConfig::load(path, None, &mut validator);
+        if let Some(http_server_config) =
+            validator.load("http_server", HttpServerConfig::try_from_file_path(path))
+        {
+            http_server_config.validate(&mut validator);
+        }
+        match validator.finish() {
+            Ok(()) => Vec::new(),
+            Err(ConfigError::Invalid(issues)) => issues,
+        }
+    }
+
+    #[test]
+    fn test_valid_config_has_no_issue() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(&dir, "consumer-key", "0x1", 5, "127.0.0.1:8080");
+        let issues = validate(&path);
+        assert!(issues.is_empty(), "{issues:?}");
+    }
+
+    #[test]
+    fn test_every_issue_is_reported_with_its_field_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(&dir, "", "not-an-object-id", 0, "not an address");
+
+        let issues = validate(&path);
+        let fields: Vec<_> = issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "guess_ai.twitter_consumer_key",
+                "guess_ai.hint_wait_count",
+                "guess_ai.guess_ai_package_id",
+                "http_server.service_bind_address",
+            ]
+        );
+        assert!(matches!(issues[0].problem, ConfigProblem::Empty));
+        assert!(matches!(issues[1].problem, ConfigProblem::Zero));
+        assert!(matches!(
+            &issues[2].problem,
+            ConfigProblem::MalformedObjectId(id) if id == "not-an-object-id"
+        ));
+        assert!(matches!(
+            &issues[3].problem,
+            ConfigProblem::InvalidBindAddress { address, .. } if address == "not an address"
+        ));
+
+        let error = ConfigError::Invalid(issues).to_string();
+        assert!(error.starts_with("Invalid configuration, 4 problem(s) found:"));
+        assert!(error.contains("  - guess_ai.twitter_consumer_key: must not be empty"));
+        assert!(error.contains("  - guess_ai.hint_wait_count: must not be zero"));
+    }
+
+    #[test]
+    fn test_unloadable_section_is_reported_with_its_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(&dir, "consumer-key", "0x1", 5, "127.0.0.1:8080");
+        let config = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("hint_wait_count = 5", "hint_wait_count = \"five\"");
+        std::fs::write(&path, config).unwrap();
+
+        let issues = validate(&path);
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert_eq!(issues[0].field, "guess_ai");
+        assert!(matches!(issues[0].problem, ConfigProblem::Load(_)));
     }
 }
@@ -0,0 +1,81 @@
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    transaction::{ObjectArg, ProgrammableTransaction},
+};
+use x25519_dalek::PublicKey;
+
+use crate::contract_bindings::{ResubmitTdxAttestation, WithdrawFundsFromTreasuryPool};
+
+/// The name of the function to withdraw funds from the treasury pool
+pub(crate) const WITHDRAW_FUNDS_FROM_TREASURY_POOL_FUNCTION_NAME: &str =
+    WithdrawFundsFromTreasuryPool::FUNCTION_NAME;
+
+/// A call to a function of the GuessAI game smart contract.
+///
+/// Each variant holds the typed arguments of its function, besides the game database
+/// object which every function takes as its first argument, so that a call with missing
+/// or mistyped arguments does not compile.
+#[derive(Clone, Debug)]
+pub enum GuessAiCall {
+    /// `resubmit_tdx_attestation(db: &mut GuessAiDb, tdx_quote_bytes: vector<u8>, public_key: vector<u8>)`
+    ResubmitTdxAttestation {
+        /// The TDX attestation quote bytes
+        tdx_quote_bytes: Vec<u8>,
+        /// The x25519 public key of the node
+        public_key: PublicKey,
+    },
+    /// `withdraw_funds_from_treasury_pool(db: &mut GuessAiDb, winner: address)`
+    WithdrawFundsFromTreasuryPool {
+        /// The address of the winner, receiving the funds of the treasury pool
+        winner_address: SuiAddress,
+    },
+}
+
+impl GuessAiCall {
+    /// The name of the contract function
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Self::ResubmitTdxAttestation { .. } => ResubmitTdxAttestation::FUNCTION_NAME,
+            Self::WithdrawFundsFromTreasuryPool { .. } => {
+                WithdrawFundsFromTreasuryPool::FUNCTION_NAME
+            }
+        }
+    }
+
+    /// Builds the programmable transaction block executing this call, with the generated
+    /// bindings of the contract (see `contract_bindings`).
+    ///
+    /// # Arguments
+    ///
+    /// * `package_id` - The ID of the GuessAI game package
+    /// * `guess_ai_db` - The game database, as a mutable shared object argument
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an argument fails to serialize
+    pub fn build(
+        &self,
+        package_id: ObjectID,
+        guess_ai_db: ObjectArg,
+    ) -> anyhow::Result<ProgrammableTransaction> {
+        match self {
+            Self::ResubmitTdxAttestation {
+                tdx_quote_bytes,
+                public_key,
+            } => ResubmitTdxAttestation {
+                guess_ai_db,
+                arg1: tdx_quote_bytes.clone(),
+                // Move `vector<u8>` arguments are length prefixed, unlike fixed size arrays
+                arg2: public_key.to_bytes().to_vec(),
+            }
+            .build(package_id),
+            Self::WithdrawFundsFromTreasuryPool { winner_address } => {
+                WithdrawFundsFromTreasuryPool {
+                    guess_ai_db,
+                    arg1: *winner_address,
+                }
+                .build(package_id)
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+// @generated by guess-ai-bindgen. Do not edit by hand, regenerate instead.
+//! Bindings of the `contract` Move module.
+
+#![allow(unused_imports)]
+
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{ObjectArg, ProgrammableTransaction},
+    Identifier,
+};
+
+/// The name of the Move module
+pub const MODULE_NAME: &str = "contract";
+
+/// Calls `contract::resubmit_tdx_attestation`
+#[derive(Clone, Debug)]
+pub struct ResubmitTdxAttestation {
+    pub guess_ai_db: ObjectArg,
+    pub arg1: Vec<u8>,
+    pub arg2: Vec<u8>,
+}
+
+impl ResubmitTdxAttestation {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "resubmit_tdx_attestation";
+
+    /// Builds the programmable transaction block calling `contract::resubmit_tdx_attestation`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.pure(self.arg1)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_agent_address`
+#[derive(Clone, Debug)]
+pub struct SetAgentAddress {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+    pub arg2: SuiAddress,
+}
+
+impl SetAgentAddress {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_agent_address";
+
+    /// Builds the programmable transaction block calling `contract::set_agent_address`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_fee_rate_increase_per_guess_per_mille`
+#[derive(Clone, Debug)]
+pub struct SetFeeRateIncreasePerGuessPerMille {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+    pub arg2: u64,
+}
+
+impl SetFeeRateIncreasePerGuessPerMille {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_fee_rate_increase_per_guess_per_mille";
+
+    /// Builds the programmable transaction block calling `contract::set_fee_rate_increase_per_guess_per_mille`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_game_inactive`
+#[derive(Clone, Debug)]
+pub struct SetGameInactive {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+}
+
+impl SetGameInactive {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_game_inactive";
+
+    /// Builds the programmable transaction block calling `contract::set_game_inactive`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_protocol_fee_per_mille`
+#[derive(Clone, Debug)]
+pub struct SetProtocolFeePerMille {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+    pub arg2: u64,
+}
+
+impl SetProtocolFeePerMille {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_protocol_fee_per_mille";
+
+    /// Builds the programmable transaction block calling `contract::set_protocol_fee_per_mille`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_starting_fee`
+#[derive(Clone, Debug)]
+pub struct SetStartingFee {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+    pub arg2: u64,
+}
+
+impl SetStartingFee {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_starting_fee";
+
+    /// Builds the programmable transaction block calling `contract::set_starting_fee`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::set_update_fee_every_n_guesses`
+#[derive(Clone, Debug)]
+pub struct SetUpdateFeeEveryNGuesses {
+    pub guess_ai_db: ObjectArg,
+    pub guess_ai_manager: ObjectArg,
+    pub arg2: u64,
+}
+
+impl SetUpdateFeeEveryNGuesses {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "set_update_fee_every_n_guesses";
+
+    /// Builds the programmable transaction block calling `contract::set_update_fee_every_n_guesses`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.obj(self.guess_ai_manager)?,
+            builder.pure(self.arg2)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
+
+/// Calls `contract::withdraw_funds_from_treasury_pool`
+#[derive(Clone, Debug)]
+pub struct WithdrawFundsFromTreasuryPool {
+    pub guess_ai_db: ObjectArg,
+    pub arg1: SuiAddress,
+}
+
+impl WithdrawFundsFromTreasuryPool {
+    /// The name of the Move function
+    pub const FUNCTION_NAME: &'static str = "withdraw_funds_from_treasury_pool";
+
+    /// Builds the programmable transaction block calling `contract::withdraw_funds_from_treasury_pool`
+    pub fn build(self, package_id: ObjectID) -> anyhow::Result<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(self.guess_ai_db)?,
+            builder.pure(self.arg1)?,
+        ];
+        builder.programmable_move_call(
+            package_id,
+            Identifier::new(MODULE_NAME)?,
+            Identifier::new(Self::FUNCTION_NAME)?,
+            vec![],
+            arguments,
+        );
+        Ok(builder.finish())
+    }
+}
@@ -1,10 +1,22 @@
 use crate::{
-    atoma::{self, AtomaSdk},
+    atoma::{self, AtomaSdk, AtomaSdkError},
+    audit::{AuditError, AuditLog, AuditOutcome},
     client::{SuiClientContext, SuiClientError},
     config::GuessAiConfig,
+    game_state::GameState,
     generate_secret::{generate_new_secret, GenerateSecretError},
     http_server::HttpServerError,
+    pause::{PauseControl, PauseError},
+    payout::{DecidedBy, JudgeVote, PayoutDecision, PayoutError, PayoutQueue},
+    similarity::{cosine_similarity, SimilarityBucket},
+    structured_output::{self, RepairAttempts, StructuredOutputError, StructuredResponse},
+    tools::{GameStateTool, ToolRegistry},
     twitter::TwitterClient,
+    types::{
+        ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse,
+        CreateEmbeddingRequest, EmbeddingInput,
+    },
+    usage::{BudgetDecision, InferencePurpose, UsageError, UsageTracker},
     GUESS_AI_MODULE_NAME,
 };
 use events::{
@@ -12,7 +24,7 @@ use events::{
     TDXQuoteResubmittedEvent,
 };
 use prompts::{GuessPromptResponse, HintPromptResponse};
-use serde_json::json;
+use serde_json::{json, Value};
 use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 use sui_sdk::{
     rpc_types::{EventFilter, EventPage},
@@ -23,18 +35,29 @@ use sui_sdk::{
     SuiClient, SuiClientBuilder,
 };
 use thiserror::Error;
-use tokio::sync::{watch::Receiver, RwLock};
-use tracing::{error, info, instrument, trace};
+use tokio::sync::{
+    watch::{self, Receiver},
+    RwLock,
+};
+use tracing::{error, info, instrument, trace, warn};
 use x25519_dalek::StaticSecret;
 
+/// The actor recorded in the audit log for the actions the engine takes on its own
+const ENGINE_AUDIT_ACTOR: &str = "engine";
+
 /// The duration to wait for new events in seconds, if there are no new events.
 const DURATION_TO_WAIT_FOR_NEW_EVENTS_IN_MILLIS: u64 = 100;
 
+/// The duration to wait between checks of the pause state, while the engine is paused
+const DURATION_TO_WAIT_FOR_PAUSE_IN_MILLIS: u64 = 1_000;
+
 pub type Result<T> = std::result::Result<T, GuessAiEngineError>;
 
 pub struct Answer {
     pub correct: bool,
     pub explanation: String,
+    /// How semantically close the guess is to the secret, if similarity feedback is enabled
+    pub similarity: Option<SimilarityBucket>,
 }
 
 pub type Answers = HashMap<u64, HashMap<u64, Answer>>;
@@ -66,15 +89,39 @@ pub struct GuessAiEngine {
     /// The list of hints generated by the AI model
     pub hints: Vec<String>,
 
+    /// The embedding of the secret, cached per guess game id
+    pub secret_embedding: Option<(u64, Vec<f32>)>,
+
     /// The list of answers (guess_cnt, explanation) generated by the AI model
     pub answers: Arc<RwLock<Answers>>,
 
+    /// The state of the game, as last read from the `guess_ai_db` object
+    pub game_state: Arc<RwLock<GameState>>,
+
+    /// The tools the model may call, for the purposes which use them
+    pub tools: ToolRegistry,
+
+    /// Payouts held for approval, decided on through the admin endpoint
+    pub payouts: Arc<RwLock<PayoutQueue>>,
+
+    /// Audit log of the privileged actions: payouts and their decisions, and secret rotations
+    pub audit_log: Arc<RwLock<AuditLog>>,
+
+    /// The pause/resume control plane, driven by the admin endpoint and the game's inactive flag
+    pub pause: Arc<RwLock<PauseControl>>,
+
+    /// The channel the reloaded configuration is sent on, applied between events
+    pub config_updates: watch::Sender<GuessAiConfig>,
+
     /// The Sui client context for the current Secret Guessing game
     pub sui_client_ctx: SuiClientContext,
 
     /// The Twitter client for the current Secret Guessing game
     pub twitter_client: TwitterClient,
 
+    /// Tracks the inference usage and compute budget of each game
+    pub usage_tracker: UsageTracker,
+
     /// Channel receiver for shutdown signals to gracefully stop the subscriber
     pub shutdown_signal: Receiver<bool>,
 }
@@ -92,10 +139,49 @@ impl GuessAiEngine {
             module: Identifier::new(GUESS_AI_MODULE_NAME).unwrap(),
         };
 
+        if atoma_sdk.is_local() {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "local-inference-backend",
+                "!!! RUNNING WITH A LOCAL INFERENCE BACKEND: requests skip confidential compute \
+                 and responses are NOT verified. This mode is for offline development only !!!"
+            );
+        }
+        let game_state = sui_client_ctx.game_state().await?;
+        let active_address = sui_client_ctx.active_address()?;
+        info!(
+            target = "sui_event_subscriber",
+            event = "game-state-synced",
+            guess_game_id = game_state.guess_game_id,
+            guess_count = game_state.guess_count,
+            fee = game_state.fee,
+            treasury_pool_balance = game_state.treasury_pool_balance,
+            is_active = game_state.is_active,
+            "Synced game state from the game db object"
+        );
+        if game_state.agent_address != active_address {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "agent-address-mismatch",
+                agent_address = %game_state.agent_address,
+                active_address = %active_address,
+                "The wallet's active address is not the game's agent address, payouts and attestations will be rejected"
+            );
+        }
+        if !game_state.is_active {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "game-inactive",
+                "The game is inactive, no guesses will be made until it is reactivated"
+            );
+        }
+
         let random_seed = rand::random::<i64>();
         let client_private_key = StaticSecret::random_from_rng(&mut rand::thread_rng());
         let generate_secret_prompt = prompts::create_secret_prompt();
-        let model = config.model.clone();
+        let mut usage_tracker =
+            UsageTracker::new(config.compute_budget.clone(), config.usage_path.clone())?;
+        let BudgetDecision { model, .. } = usage_tracker.decide(&config.model);
         // let tdx_quote_bytes = tdx::generate_tdx_quote_bytes(&mut rng);
         let secret = generate_new_secret(
             &atoma_sdk,
@@ -104,16 +190,41 @@ impl GuessAiEngine {
             model,
             random_seed,
             &mut sui_client_ctx,
+            &mut usage_tracker,
         )
         .await?;
 
         let twitter_client = TwitterClient::new(
-            config.twitter_consumer_key.clone(),
-            config.twitter_consumer_secret.clone(),
-            config.twitter_access_token.clone(),
-            config.twitter_access_token_secret.clone(),
+            config.twitter_consumer_key.expose_secret().to_string(),
+            config.twitter_consumer_secret.expose_secret().to_string(),
+            config.twitter_access_token.expose_secret().to_string(),
+            config
+                .twitter_access_token_secret
+                .expose_secret()
+                .to_string(),
         );
 
+        let (config_updates, _) = watch::channel(config.clone());
+        let payouts = PayoutQueue::open(config.payout_policy.clone(), &config.payout_state_path)?;
+        let audit_log = AuditLog::open(&config.audit_log_path)?;
+        let mut pause = PauseControl::open(&config.pause_state_path)?;
+        if config.pause_when_game_inactive {
+            pause.set_game_inactive(!game_state.is_active);
+        }
+        if let Some(operator_pause) = pause.status().operator_pause {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "engine-paused",
+                reason = ?operator_pause.reason,
+                since = operator_pause.since,
+                "The engine was paused by an operator, no hints or payouts will be issued until it is resumed"
+            );
+        }
+
+        let game_state = Arc::new(RwLock::new(game_state));
+        let mut tools = ToolRegistry::new();
+        tools.register(GameStateTool::new(Arc::clone(&game_state)));
+
         Ok(Self {
             atoma_sdk,
             client_private_key,
@@ -122,13 +233,281 @@ impl GuessAiEngine {
             random_seed,
             secret,
             hints: Vec::new(),
+            secret_embedding: None,
             answers: Arc::new(RwLock::new(HashMap::new())),
+            game_state,
+            tools,
+            payouts: Arc::new(RwLock::new(payouts)),
+            audit_log: Arc::new(RwLock::new(audit_log)),
+            pause: Arc::new(RwLock::new(pause)),
+            config_updates,
             sui_client_ctx,
             twitter_client,
+            usage_tracker,
             shutdown_signal,
         })
     }
 
+    /// Sends a chat completion request for the given purpose and records its usage.
+    ///
+    /// The request goes through the confidential compute endpoint when the
+    /// `confidential-compute` feature is enabled. The model is picked by the
+    /// compute budget of the current game.
+    ///
+    /// If the model is unavailable, the request is retried once with the fallback model
+    /// of the compute budget, if any. Errors that require an operator's attention (such as
+    /// an authentication failure or an insufficient balance) raise an alert.
+    ///
+    /// # Arguments
+    ///
+    /// * `purpose` - The purpose of the request, used for usage accounting
+    /// * `max_tokens` - The maximum number of completion tokens of the request
+    /// * `messages` - The messages of the request
+    /// * `response_format` - The format the model must answer in, if any
+    /// * `model` - The model to use instead of the one picked by the compute budget, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ChatCompletionResponse>` - The response of the model, or a `GuessAiEngineError` if:
+    ///   * The request cannot be built
+    ///   * The Atoma SDK request fails
+    #[instrument(level = "info", skip_all, fields(purpose = ?purpose))]
+    async fn request_chat_completions(
+        &mut self,
+        purpose: InferencePurpose,
+        max_tokens: u32,
+        messages: &[ChatCompletionMessage],
+        response_format: Option<Value>,
+        model: Option<&str>,
+    ) -> Result<ChatCompletionResponse> {
+        let BudgetDecision {
+            model: budget_model,
+            ..
+        } = self.usage_tracker.decide(&self.config.model);
+        let mut model = model.map_or(budget_model, str::to_string);
+        let mut request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": model,
+            "messages": messages,
+            "seed": self.random_seed,
+            "max_tokens": max_tokens,
+            "response_format": response_format,
+        }))?;
+        let use_tools = purpose.uses_tools();
+        let response_body = match self.send_chat_completions(request.clone(), use_tools).await {
+            Ok(response_body) => response_body,
+            Err(AtomaSdkError::ModelUnavailableError(message)) => {
+                let Some(fallback_model) = self
+                    .config
+                    .compute_budget
+                    .fallback_model
+                    .clone()
+                    .filter(|fallback_model| *fallback_model != model)
+                else {
+                    return Err(AtomaSdkError::ModelUnavailableError(message).into());
+                };
+                warn!(
+                    target = "sui_event_subscriber",
+                    event = "model-unavailable",
+                    "Model {model} is unavailable ({message}), retrying with {fallback_model}"
+                );
+                request.model = fallback_model.clone();
+                model = fallback_model;
+                self.send_chat_completions(request, use_tools)
+                    .await
+                    .inspect_err(Self::alert_on_atoma_sdk_error)?
+            }
+            Err(e) => {
+                Self::alert_on_atoma_sdk_error(&e);
+                return Err(e.into());
+            }
+        };
+
+        self.usage_tracker
+            .record(purpose, &model, response_body.usage.as_ref());
+        Ok(response_body)
+    }
+
+    /// Requests a structured response of type `T` from the model.
+    ///
+    /// The request constrains the model with the JSON schema of `T`. If the answer still
+    /// cannot be parsed, the model is asked to repair it, up to `MAX_REPAIR_ATTEMPTS` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `purpose` - The purpose of the request, used for usage accounting
+    /// * `max_tokens` - The maximum number of completion tokens of each request
+    /// * `messages` - The messages of the request, as a JSON array
+    /// * `model` - The model to use instead of the one picked by the compute budget, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(T, String)>` - The parsed response and the model which answered it (which
+    ///   differs from the requested one if it fell back), or a `GuessAiEngineError` if:
+    ///   * Any of the chat completion requests fails, see `request_chat_completions`
+    ///   * The response cannot be parsed after all the repair attempts
+    #[instrument(level = "info", skip_all, fields(purpose = ?purpose, schema = T::SCHEMA_NAME))]
+    async fn request_structured<T: StructuredResponse>(
+        &mut self,
+        purpose: InferencePurpose,
+        max_tokens: u32,
+        messages: Value,
+        model: Option<&str>,
+    ) -> Result<(T, String)> {
+        let mut messages: Vec<ChatCompletionMessage> = serde_json::from_value(messages)?;
+        let mut repair_attempts = RepairAttempts::default();
+        loop {
+            let response_body = self
+                .request_chat_completions(
+                    purpose,
+                    max_tokens,
+                    &messages,
+                    Some(structured_output::response_format::<T>()),
+                    model,
+                )
+                .await?;
+            if let Some(response) =
+                repair_attempts.parse_or_repair(&response_body, &mut messages)?
+            {
+                return Ok((response, response_body.model));
+            }
+        }
+    }
+
+    /// Sends a chat completion request through the confidential compute endpoint when the
+    /// `confidential-compute` feature is enabled, or the plain endpoint otherwise.
+    ///
+    /// With `use_tools`, the model may call the engine's tools, which are run until it answers
+    /// without tool calls.
+    async fn send_chat_completions(
+        &self,
+        request: ChatCompletionRequest,
+        use_tools: bool,
+    ) -> std::result::Result<ChatCompletionResponse, AtomaSdkError> {
+        let use_tools = use_tools && !self.tools.is_empty();
+        #[cfg(feature = "confidential-compute")]
+        return if use_tools {
+            self.atoma_sdk
+                .confidential_chat_completions_with_tools(
+                    &self.client_private_key,
+                    request,
+                    &self.tools,
+                )
+                .await
+        } else {
+            self.atoma_sdk
+                .confidential_chat_completions(&self.client_private_key, request)
+                .await
+        };
+        #[cfg(not(feature = "confidential-compute"))]
+        return if use_tools {
+            self.atoma_sdk
+                .chat_completions_with_tools(request, &self.tools)
+                .await
+        } else {
+            self.atoma_sdk.chat_completions(request).await
+        };
+    }
+
+    /// Scores how semantically close a guess is to the secret of the given game.
+    ///
+    /// The embedding of the secret is computed once per game and cached. Similarity feedback
+    /// must never prevent a guess from being judged, so failures are logged and reported as
+    /// `None`, as is the case when no embedding model is configured.
+    #[instrument(level = "info", skip_all, fields(guess_game_id = guess_game_id))]
+    async fn similarity_bucket(
+        &mut self,
+        guess_game_id: u64,
+        guess: &str,
+    ) -> Option<SimilarityBucket> {
+        let embedding_model = self.config.similarity.embedding_model.clone()?;
+        match self
+            .similarity_score(&embedding_model, guess_game_id, guess)
+            .await
+        {
+            Ok(score) => {
+                score.map(|score| SimilarityBucket::from_score(score, &self.config.similarity))
+            }
+            Err(e) => {
+                warn!(
+                    target = "sui_event_subscriber",
+                    event = "similarity-error",
+                    "Failed to score the similarity of the guess: {e}"
+                );
+                None
+            }
+        }
+    }
+
+    /// Computes the cosine similarity between the embeddings of the guess and the secret
+    async fn similarity_score(
+        &mut self,
+        embedding_model: &str,
+        guess_game_id: u64,
+        guess: &str,
+    ) -> Result<Option<f32>> {
+        if !matches!(&self.secret_embedding, Some((id, _)) if *id == guess_game_id) {
+            let secret_embedding = self
+                .request_embedding(embedding_model, self.secret.clone())
+                .await?;
+            self.secret_embedding = Some((guess_game_id, secret_embedding));
+        }
+        let guess_embedding = self
+            .request_embedding(embedding_model, guess.to_string())
+            .await?;
+        let (_, secret_embedding) = self
+            .secret_embedding
+            .as_ref()
+            .expect("The secret embedding was cached above");
+        Ok(cosine_similarity(secret_embedding, &guess_embedding))
+    }
+
+    /// Requests the embedding of a text and records its usage.
+    ///
+    /// The request goes through the confidential compute endpoint when the
+    /// `confidential-compute` feature is enabled.
+    async fn request_embedding(&mut self, model: &str, text: String) -> Result<Vec<f32>> {
+        let request = CreateEmbeddingRequest {
+            model: model.to_string(),
+            input: EmbeddingInput::Single(text),
+            dimensions: None,
+            user: None,
+        };
+        #[cfg(feature = "confidential-compute")]
+        let response = self
+            .atoma_sdk
+            .confidential_embeddings(&self.client_private_key, request)
+            .await;
+        #[cfg(not(feature = "confidential-compute"))]
+        let response = self.atoma_sdk.embeddings(request).await;
+        let response = response.inspect_err(Self::alert_on_atoma_sdk_error)?;
+
+        self.usage_tracker.record(
+            InferencePurpose::Similarity,
+            model,
+            response.usage.clone().map(Into::into).as_ref(),
+        );
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|embedding| embedding.embedding)
+            .ok_or_else(|| {
+                GuessAiEngineError::InternalError("Embeddings response has no data".to_string())
+            })
+    }
+
+    /// Raises an alert if an Atoma SDK error requires the attention of an operator
+    fn alert_on_atoma_sdk_error(error: &AtomaSdkError) {
+        if error.requires_operator_attention() {
+            error!(
+                target = "sui_event_subscriber",
+                event = "atoma-alert",
+                alert = true,
+                "Atoma API request failed and requires operator attention: {error}"
+            );
+        }
+    }
+
     /// Builds a SuiClient based on the provided configuration.
     ///
     /// This asynchronous method creates a new SuiClient instance using the settings
@@ -168,6 +547,102 @@ impl GuessAiEngine {
         Ok(client)
     }
 
+    /// Records a privileged action in the audit log.
+    ///
+    /// Failures are logged, as they must not prevent the action, which already happened.
+    async fn audit(
+        &self,
+        actor: &str,
+        action: &str,
+        parameters: Value,
+        tx_digest: Option<String>,
+        outcome: AuditOutcome,
+    ) {
+        if let Err(e) = self
+            .audit_log
+            .write()
+            .await
+            .append(actor, action, parameters, tx_digest, outcome)
+        {
+            error!(
+                target = "sui_event_subscriber",
+                event = "audit-log-error",
+                action,
+                "Failed to record privileged action in the audit log: {e}"
+            );
+        }
+    }
+
+    /// Applies a reloaded configuration, whose changes are limited to the reloadable fields
+    /// (see `ConfigReloader`).
+    ///
+    /// The model and hint cadence are read from the configuration on every event, and the
+    /// Twitter client is rebuilt if its credentials changed.
+    fn apply_config(&mut self, config: GuessAiConfig) {
+        if config.twitter_consumer_key != self.config.twitter_consumer_key
+            || config.twitter_consumer_secret != self.config.twitter_consumer_secret
+            || config.twitter_access_token != self.config.twitter_access_token
+            || config.twitter_access_token_secret != self.config.twitter_access_token_secret
+        {
+            self.twitter_client = TwitterClient::new(
+                config.twitter_consumer_key.expose_secret().to_string(),
+                config.twitter_consumer_secret.expose_secret().to_string(),
+                config.twitter_access_token.expose_secret().to_string(),
+                config
+                    .twitter_access_token_secret
+                    .expose_secret()
+                    .to_string(),
+            );
+        }
+        info!(
+            target = "sui_event_subscriber",
+            event = "config-applied",
+            model = %config.model,
+            hint_wait_count = config.hint_wait_count,
+            "Applied the reloaded configuration"
+        );
+        self.config = config;
+    }
+
+    /// Re-reads the state of the game from the `guess_ai_db` object, after a page of events
+    /// or a payout changed it.
+    ///
+    /// If the engine pauses while the game is inactive, the pause control is updated with
+    /// the game's inactive flag. Failures are logged, and the last known state is kept.
+    async fn sync_game_state(&mut self) {
+        let game_state = match self.sui_client_ctx.game_state().await {
+            Ok(game_state) => game_state,
+            Err(e) => {
+                warn!(
+                    target = "sui_event_subscriber",
+                    event = "game-state-sync-error",
+                    "Failed to sync game state, keeping the last known state: {e}"
+                );
+                return;
+            }
+        };
+        let is_active = game_state.is_active;
+        *self.game_state.write().await = game_state;
+        if !self.config.pause_when_game_inactive
+            || !self.pause.write().await.set_game_inactive(!is_active)
+        {
+            return;
+        }
+        if is_active {
+            info!(
+                target = "sui_event_subscriber",
+                event = "game-reactivated",
+                "The game was reactivated on-chain"
+            );
+        } else {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "game-inactive",
+                "The game was deactivated on-chain, pausing the engine"
+            );
+        }
+    }
+
     /// Handles different types of Secret Guessing events received from the blockchain.
     ///
     /// This method processes various events emitted by the Secret Guessing smart contract,
@@ -224,7 +699,9 @@ impl GuessAiEngine {
     ///
     /// This method processes a guess event by:
     /// 1. Checking if the guess matches the secret (either exactly or semantically) using AI
-    /// 2. If correct, withdraws funds from the treasury pool to reward the winner
+    /// 2. If correct, withdraws funds from the treasury pool to reward the winner, or holds
+    ///    the payout for approval if the treasury pool reached the approval threshold of the
+    ///    payout policy
     /// 3. Periodically generates hints using AI when guess count reaches threshold
     ///
     /// # Arguments
@@ -247,7 +724,8 @@ impl GuessAiEngine {
     ///
     /// Uses the Atoma SDK to make two types of AI calls:
     /// 1. Guess validation - Checks if guess matches secret using semantic comparison
-    /// 2. Hint generation - Creates hints every `hint_wait_count` guesses
+    /// 2. Hint generation - Creates hints every `hint_wait_count` guesses, as long as
+    ///    the compute cost of the game is within its budget
     ///
     /// # Example Flow
     ///
@@ -297,39 +775,28 @@ impl GuessAiEngine {
             treasury_pool_balance,
         } = event;
 
+        self.usage_tracker.set_current_game(guess_game_id);
+        self.usage_tracker
+            .set_treasury_revenue(guess_game_id, treasury_pool_balance);
+
         // TODO: Check if the guess is correct
         let (system_prompt, user_prompt) = prompts::check_guess_prompt(&guess, &self.secret);
-        #[cfg(feature = "confidential-compute")]
-        let response_body = self
-            .atoma_sdk
-            .confidential_chat_completions(
-                &self.client_private_key,
-                serde_json::from_value(json!({
-                    "model": self.config.model.clone(),
-                    "messages": [
-                        {"role": "system", "content": system_prompt},
-                        {"role": "user", "content": user_prompt},
-                    ],
-                    "seed": self.random_seed,
-                }))?,
-            )
-            .await?;
-        #[cfg(not(feature = "confidential-compute"))]
-        let response_body = self
-            .atoma_sdk
-            .chat_completions(serde_json::from_value(json!({
-                "model": self.config.model.clone(),
-                "messages": [
+        let (answer, judge_model): (GuessPromptResponse, _) = self
+            .request_structured(
+                InferencePurpose::Judge,
+                self.config.compute_budget.judge_max_tokens,
+                json!([
                     {"role": "system", "content": system_prompt},
                     {"role": "user", "content": user_prompt},
-                ],
-                "seed": self.random_seed,
-            }))?)
+                ]),
+                None,
+            )
             .await?;
-
-        let answer = serde_json::from_str::<GuessPromptResponse>(
-            &response_body.choices[0].message.content.clone(),
-        )?;
+        let similarity = if answer.is_correct {
+            None
+        } else {
+            self.similarity_bucket(guess_game_id, &guess).await
+        };
 
         {
             let mut answers = self.answers.write().await;
@@ -343,7 +810,8 @@ impl GuessAiEngine {
                     event.guess_count,
                     Answer {
                         correct: answer.is_correct,
-                        explanation: answer.explanation,
+                        explanation: answer.explanation.clone(),
+                        similarity,
                     },
                 );
         }
@@ -355,64 +823,89 @@ impl GuessAiEngine {
                 "Guess is correct for sender: {sender}, guess: {guess}, fee: {fee}, guess_count: {guess_count}, guess_game_id: {guess_game_id}, treasury_pool_balance: {treasury_pool_balance}"
             );
 
-            let tx_hash = self
-                .sui_client_ctx
-                .withdraw_funds_from_treasury_pool(sender, None, None, None)
-                .await?;
-
-            // The game ended, we start a new game with new word
-            let generate_secret_prompt = prompts::create_secret_prompt();
-            let random_seed = rand::random::<i64>();
-            self.secret = generate_new_secret(
-                &self.atoma_sdk,
-                &self.client_private_key,
-                generate_secret_prompt,
-                self.config.model.clone(),
-                random_seed,
-                &mut self.sui_client_ctx,
-            )
-            .await?;
-            self.hints.clear();
-            info!(
+            // A payout decided while the engine is paused, or while payouts are refused on
+            // this network, is held like a payout reaching the approval threshold: it is
+            // decided by an operator, the second judge or the timeout, and an approved payout
+            // is only paid out once the engine resumes and payouts are allowed
+            let paused = self.pause.read().await.is_paused();
+            let payouts_allowed = self.sui_client_ctx.payouts_allowed();
+            if !paused
+                && payouts_allowed
+                && !self
+                    .payouts
+                    .read()
+                    .await
+                    .requires_approval(treasury_pool_balance)
+            {
+                return self.pay_out_winner(sender).await;
+            }
+            let vote = JudgeVote {
+                model: judge_model.clone(),
+                is_correct: true,
+                explanation: answer.explanation,
+            };
+            let payout_id = self.payouts.write().await.hold(
+                guess_game_id,
+                guess_count,
+                sender,
+                guess.clone(),
+                treasury_pool_balance,
+                &self.secret,
+                vote,
+            )?;
+            warn!(
                 target = "sui_event_subscriber",
-                event = "new-guess-event",
-                "Withdrew funds from treasury pool successfully, tx_hash: {tx_hash}"
+                event = "payout-held",
+                payout_id,
+                treasury_pool_balance,
+                paused,
+                payouts_allowed,
+                "Payout to {sender} is held for approval, as the engine is paused, payouts are refused on this network or the treasury pool reached the approval threshold"
             );
-            todo!("Add a client for social media to post the tx_hash and sender of the winner");
+            // The payout stays held for an operator if the second judge cannot vote
+            if let Err(e) = self
+                .request_second_judge_vote(payout_id, &guess, &judge_model)
+                .await
+            {
+                error!(
+                    target = "sui_event_subscriber",
+                    event = "payout-second-judge-error",
+                    payout_id,
+                    "Second judge failed to vote on held payout {payout_id}, it waits for an operator: {e}"
+                );
+            }
+            return Ok(());
         }
 
         if guess_count % self.config.hint_wait_count == 0 {
+            if self.pause.read().await.is_paused() {
+                info!(
+                    target = "sui_event_subscriber",
+                    event = "new-guess-event",
+                    "The engine is paused, skipping hint"
+                );
+                return Ok(());
+            }
+            if !self.usage_tracker.decide(&self.config.model).allow_hints {
+                info!(
+                    target = "sui_event_subscriber",
+                    event = "new-guess-event",
+                    "Compute budget of guess game {guess_game_id} is exhausted, skipping hint"
+                );
+                return Ok(());
+            }
             let hint_prompt = prompts::create_hint_prompt(&self.secret, &self.hints);
-            #[cfg(feature = "confidential-compute")]
-            let response_body = self
-                .atoma_sdk
-                .confidential_chat_completions(
-                    &self.client_private_key,
-                    serde_json::from_value(json!({
-                        "model": self.config.model.clone(),
-                        "messages": [
-                            { "role": "system", "content": hint_prompt },
-                        ],
-                        "seed": self.random_seed,
-                    }))?,
-                )
-                .await?;
-            #[cfg(not(feature = "confidential-compute"))]
-            let response_body = self
-                .atoma_sdk
-                .chat_completions(serde_json::from_value(json!({
-                    "model": self.config.model.clone(),
-                    "messages": [
+            let (hint, _): (HintPromptResponse, _) = self
+                .request_structured(
+                    InferencePurpose::Hint,
+                    self.config.compute_budget.hint_max_tokens,
+                    json!([
                         { "role": "system", "content": hint_prompt },
-                    ],
-                    "seed": self.random_seed,
-                }))?)
+                    ]),
+                    None,
+                )
                 .await?;
 
-            let hint = serde_json::from_str::<HintPromptResponse>(
-                &response_body.choices[0].message.content.clone(),
-            )?;
-
             self.hints.push(hint.hint.clone());
 
             todo!("Add a client for social media to post the hint");
@@ -421,6 +914,226 @@ impl GuessAiEngine {
         Ok(())
     }
 
+    /// Pays the treasury pool out to the winner of the current game, and starts a new game
+    /// with a new secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GuessAiEngineError` if the withdrawal or the generation of the new secret fails
+    #[instrument(level = "info", skip(self))]
+    async fn pay_out_winner(&mut self, winner: SuiAddress) -> Result<()> {
+        let result = self
+            .sui_client_ctx
+            .withdraw_funds_from_treasury_pool(winner, None, None, None)
+            .await;
+        self.audit(
+            ENGINE_AUDIT_ACTOR,
+            "payout",
+            json!({ "winner": winner }),
+            result.as_ref().ok().cloned(),
+            AuditOutcome::of(&result),
+        )
+        .await;
+        let tx_hash = result?;
+
+        // The game ended, we start a new game with new word. Its usage is attributed
+        // to the next game, once we observe its first event.
+        self.usage_tracker.clear_current_game();
+        let generate_secret_prompt = prompts::create_secret_prompt();
+        let random_seed = rand::random::<i64>();
+        let BudgetDecision { model, .. } = self.usage_tracker.decide(&self.config.model);
+        let secret = generate_new_secret(
+            &self.atoma_sdk,
+            &self.client_private_key,
+            generate_secret_prompt,
+            model,
+            random_seed,
+            &mut self.sui_client_ctx,
+            &mut self.usage_tracker,
+        )
+        .await;
+        self.audit(
+            ENGINE_AUDIT_ACTOR,
+            "secret_rotation",
+            json!({ "reason": "new_game" }),
+            None,
+            AuditOutcome::of(&secret),
+        )
+        .await;
+        self.secret = secret?;
+        self.hints.clear();
+        self.secret_embedding = None;
+        info!(
+            target = "sui_event_subscriber",
+            event = "new-guess-event",
+            "Withdrew funds from treasury pool successfully, tx_hash: {tx_hash}"
+        );
+        todo!("Add a client for social media to post the tx_hash and sender of the winner");
+    }
+
+    /// Asks the second automated judge of the payout policy, if any, whether the guess of a
+    /// held payout is correct, and decides on the payout accordingly.
+    ///
+    /// A payout the second judge disagrees on is rejected, so that a single model cannot
+    /// be talked into paying out a large treasury pool. If the second judge's model is
+    /// unavailable and the request falls back to the model of the first judge, the vote is
+    /// not counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `payout_id` - The held payout
+    /// * `guess` - The guess of the payout
+    /// * `first_judge_model` - The model which judged the guess correct in the first place
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GuessAiEngineError` if the request to the second judge fails, in which case
+    /// the payout stays held for an operator
+    #[instrument(level = "info", skip(self, guess))]
+    async fn request_second_judge_vote(
+        &mut self,
+        payout_id: u64,
+        guess: &str,
+        first_judge_model: &str,
+    ) -> Result<()> {
+        let Some(model) = self
+            .payouts
+            .read()
+            .await
+            .second_judge_model()
+            .map(str::to_string)
+        else {
+            return Ok(());
+        };
+        let (system_prompt, user_prompt) = prompts::check_guess_prompt(guess, &self.secret);
+        let (answer, model): (GuessPromptResponse, _) = self
+            .request_structured(
+                InferencePurpose::Judge,
+                self.config.compute_budget.judge_max_tokens,
+                json!([
+                    {"role": "system", "content": system_prompt},
+                    {"role": "user", "content": user_prompt},
+                ]),
+                Some(&model),
+            )
+            .await?;
+        if model == first_judge_model {
+            warn!(
+                target = "sui_event_subscriber",
+                event = "payout-second-judge-fallback",
+                payout_id,
+                "Second judge fell back to the first judge's model {model}, held payout {payout_id} waits for an operator"
+            );
+            return Ok(());
+        }
+        let decision = if answer.is_correct {
+            PayoutDecision::Approve
+        } else {
+            PayoutDecision::Reject
+        };
+        info!(
+            target = "sui_event_subscriber",
+            event = "payout-second-judge-vote",
+            payout_id,
+            is_correct = answer.is_correct,
+            "Second judge {model} voted on held payout {payout_id}"
+        );
+        let mut payouts = self.payouts.write().await;
+        payouts.add_vote(
+            payout_id,
+            JudgeVote {
+                model,
+                is_correct: answer.is_correct,
+                explanation: answer.explanation,
+            },
+        )?;
+        payouts.decide(payout_id, decision, DecidedBy::SecondJudge)?;
+        Ok(())
+    }
+
+    /// Acts upon the held payouts which were decided, or whose approval timed out.
+    ///
+    /// Approved payouts are paid out, unless their game already ended (another held payout
+    /// of the same game was approved first), in which case they are dropped. While the engine
+    /// is paused, or while payouts are refused on this network (see
+    /// [`SuiClientContext::verify_network`]), approved payouts stay held, but rejections and
+    /// timeouts are still resolved. Failures are logged, as they must not stop the event loop.
+    async fn process_resolved_payouts(&mut self) {
+        let payouts_allowed =
+            !self.pause.read().await.is_paused() && self.sui_client_ctx.payouts_allowed();
+        let resolved = match self.payouts.write().await.take_resolved(payouts_allowed) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error!(
+                    target = "sui_event_subscriber",
+                    event = "payout-queue-error",
+                    "Failed to resolve the held payouts: {e}"
+                );
+                return;
+            }
+        };
+        for (payout, decision) in resolved {
+            let decided_by = payout.decision.as_ref().map(|(_, decided_by)| decided_by);
+            self.audit(
+                decided_by.map_or(ENGINE_AUDIT_ACTOR, DecidedBy::as_str),
+                "payout_decision",
+                json!({
+                    "payout_id": payout.id,
+                    "guess_game_id": payout.guess_game_id,
+                    "winner": payout.winner,
+                    "treasury_pool_balance": payout.treasury_pool_balance,
+                    "decision": decision,
+                }),
+                None,
+                AuditOutcome::Success,
+            )
+            .await;
+            let current_game_id = self.game_state.read().await.guess_game_id;
+            if decision == PayoutDecision::Reject {
+                info!(
+                    target = "sui_event_subscriber",
+                    event = "payout-rejected",
+                    payout_id = payout.id,
+                    decided_by = ?decided_by,
+                    "Held payout to {} is rejected, guess game {} goes on",
+                    payout.winner,
+                    payout.guess_game_id
+                );
+                continue;
+            }
+            if payout.guess_game_id != current_game_id {
+                info!(
+                    target = "sui_event_subscriber",
+                    event = "payout-dropped",
+                    payout_id = payout.id,
+                    "Held payout to {} is approved, but guess game {} already ended",
+                    payout.winner,
+                    payout.guess_game_id
+                );
+                continue;
+            }
+            info!(
+                target = "sui_event_subscriber",
+                event = "payout-approved",
+                payout_id = payout.id,
+                decided_by = ?decided_by,
+                secret_commitment_salt = %payout.secret_commitment_salt,
+                "Held payout to {} is approved",
+                payout.winner
+            );
+            if let Err(e) = self.pay_out_winner(payout.winner).await {
+                error!(
+                    target = "sui_event_subscriber",
+                    event = "payout-error",
+                    payout_id = payout.id,
+                    "Failed to pay out approved payout: {e}"
+                );
+            }
+            // The payout ends the game, the other payouts held for it are then dropped
+            self.sync_game_state().await;
+        }
+    }
+
     /// Handles a TDX quote rotation event by generating a new secret and updating internal state.
     ///
     /// When a TDX (Trust Domain Extensions) quote rotation occurs, this handler:
@@ -479,19 +1192,31 @@ impl GuessAiEngine {
         let generate_secret_prompt = prompts::create_secret_prompt();
         let mut rng = rand::rngs::OsRng::default();
         let client_private_key = StaticSecret::random_from_rng(&mut rng);
+        let BudgetDecision { model, .. } = self.usage_tracker.decide(&self.config.model);
         let secret = generate_new_secret(
             &self.atoma_sdk,
             &client_private_key,
             generate_secret_prompt,
-            self.config.model.clone(),
+            model,
             random_seed,
             &mut self.sui_client_ctx,
+            &mut self.usage_tracker,
         )
-        .await?;
+        .await;
+        self.audit(
+            ENGINE_AUDIT_ACTOR,
+            "secret_rotation",
+            json!({ "reason": "tdx_quote_rotation", "epoch": epoch }),
+            None,
+            AuditOutcome::of(&secret),
+        )
+        .await;
+        let secret = secret?;
         // Update the self's state
         self.client_private_key = client_private_key;
         self.random_seed = random_seed;
         self.secret = secret;
+        self.secret_embedding = None;
         info!(
             target = "sui_event_subscriber",
             event = "rotate-tdx-quote-event",
@@ -576,9 +1301,11 @@ impl GuessAiEngine {
         );
 
         let mut cursor = cursor::read_cursor_from_toml_file(&self.config.cursor_path)?;
+        let mut config_updates = self.config_updates.subscribe();
         loop {
+            let paused = self.pause.read().await.is_paused();
             tokio::select! {
-                    page = client.event_api().query_events(self.filter.clone(), cursor, self.config.limit, false) => {
+                    page = client.event_api().query_events(self.filter.clone(), cursor, self.config.limit, false), if !paused => {
                         let EventPage {
                             data,
                             next_cursor,
@@ -594,9 +1321,15 @@ impl GuessAiEngine {
                                 continue;
                             }
                         };
-                        cursor = next_cursor;
-
+                        let mut interrupted = false;
+                        let mut handled_events = false;
                         for sui_event in data {
+                            // Stop at the first event after a pause, so that the engine resumes from it
+                            if self.pause.read().await.is_paused() {
+                                interrupted = true;
+                                break;
+                            }
+                            cursor = Some(sui_event.id);
                             let event_name = sui_event.type_.name;
                             trace!(
                                 target = "sui_event_subscriber",
@@ -626,6 +1359,7 @@ impl GuessAiEngine {
                                             "Failed to handle event: {e}"
                                         );
                                     }
+                                    handled_events = true;
                                 }
                                 Err(e) => {
                                     error!(
@@ -638,6 +1372,16 @@ impl GuessAiEngine {
                             }
                         }
 
+                        if !interrupted {
+                            cursor = next_cursor;
+                        }
+                        // The state is read once per page, before the held payouts check it
+                        if handled_events {
+                            self.sync_game_state().await;
+                        }
+
+                        self.process_resolved_payouts().await;
+
                         if !has_next_page {
                             // Update the cursor file with the current cursor
                             cursor::write_cursor_to_toml_file(cursor, &self.config.cursor_path)?;
@@ -654,6 +1398,33 @@ impl GuessAiEngine {
                             .await;
                         }
                     }
+                    () = tokio::time::sleep(Duration::from_millis(DURATION_TO_WAIT_FOR_PAUSE_IN_MILLIS)), if paused => {
+                        if !self.pause.read().await.is_drained() {
+                            // The event in flight is finished, persist the cursor of the last processed event
+                            cursor::write_cursor_to_toml_file(cursor, &self.config.cursor_path)?;
+                            self.pause.write().await.set_drained();
+                            warn!(
+                                target = "atoma-sui-subscriber",
+                                event = "engine-paused",
+                                "The engine is paused, no hints or payouts will be issued until it is resumed"
+                            );
+                        }
+                        if self.config.pause_when_game_inactive {
+                            // Watch for the game to be reactivated on-chain
+                            self.sync_game_state().await;
+                        }
+                        if !self.pause.read().await.is_paused() {
+                            info!(
+                                target = "atoma-sui-subscriber",
+                                event = "engine-resumed",
+                                "The engine resumed, processing events again"
+                            );
+                        }
+                    }
+                    Ok(()) = config_updates.changed() => {
+                        let config = config_updates.borrow_and_update().clone();
+                        self.apply_config(config);
+                    }
                     shutdown_signal_changed = self.shutdown_signal.changed() => {
                         match shutdown_signal_changed {
                             Ok(()) => {
@@ -703,6 +1474,12 @@ pub enum GuessAiEngineError {
     InvalidEvent(serde_json::Value),
     #[error("Failed to send request to Atoma API: {0}")]
     AtomaApiError(#[from] reqwest::Error),
+    #[error("Payout error: {0}")]
+    PayoutError(#[from] PayoutError),
+    #[error("Audit log error: {0}")]
+    AuditError(#[from] AuditError),
+    #[error("Pause state error: {0}")]
+    PauseError(#[from] PauseError),
     #[error("Sui client error: {0}")]
     SuiClientError(#[from] SuiClientError),
     #[error("Failed to generate secret: {0}")]
@@ -717,6 +1494,10 @@ pub enum GuessAiEngineError {
     InternalError(String),
     #[error("Http server error: {0}")]
     HttpServerError(#[from] HttpServerError),
+    #[error("Usage tracking error: {0}")]
+    UsageError(#[from] UsageError),
+    #[error("Invalid structured output: {0}")]
+    StructuredOutputError(#[from] StructuredOutputError),
 }
 
 pub(crate) mod events {
@@ -1035,13 +1816,18 @@ pub(crate) mod cursor {
 }
 
 pub(crate) mod prompts {
+    use schemars::JsonSchema;
     use serde::{Deserialize, Serialize};
+
+    use crate::structured_output::StructuredResponse;
+
     /// Response structure for the guess checking prompt.
     ///
     /// This struct represents the parsed response from the AI model when checking
     /// if a guess matches the secret. It contains both the boolean result and
     /// a detailed explanation of why the guess was considered correct or incorrect.
-    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+    #[schemars(deny_unknown_fields)]
     pub(crate) struct GuessPromptResponse {
         /// Boolean indicating whether the guess matches the secret
         pub(crate) is_correct: bool,
@@ -1050,24 +1836,38 @@ pub(crate) mod prompts {
         pub(crate) explanation: String,
     }
 
+    impl StructuredResponse for GuessPromptResponse {
+        const SCHEMA_NAME: &'static str = "guess_prompt_response";
+    }
+
     /// Response structure for the secret creation prompt.
     ///
     /// This struct represents the parsed response from the AI model when creating a secret.
-    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+    #[schemars(deny_unknown_fields)]
     pub(crate) struct SecretPromptResponse {
         /// The created secret
         pub(crate) secret: String,
     }
 
+    impl StructuredResponse for SecretPromptResponse {
+        const SCHEMA_NAME: &'static str = "secret_prompt_response";
+    }
+
     /// Response structure for the hint creation prompt.
     ///
     /// This struct represents the parsed response from the AI model when creating a hint.
-    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+    #[schemars(deny_unknown_fields)]
     pub(crate) struct HintPromptResponse {
         /// The created hint
         pub(crate) hint: String,
     }
 
+    impl StructuredResponse for HintPromptResponse {
+        const SCHEMA_NAME: &'static str = "hint_prompt_response";
+    }
+
     /// Creates system and user prompts for checking if a guess matches a secret.
     ///
     /// This function generates two prompts used to query an AI model to determine if a guess
@@ -0,0 +1,553 @@
+//! The confidential compute envelope.
+//!
+//! Requests to the confidential compute endpoints are encrypted with a symmetric key derived,
+//! with HKDF-SHA256, from the X25519 shared secret between the client and the node.
+//!
+//! Two envelope versions exist:
+//! - Version 1 (legacy): a single key, derived with an empty HKDF info string, encrypts both
+//!   the request and the response with AES-256-GCM. Requests carry no version field.
+//! - Version 2: the request and the response are encrypted with separate keys, derived with
+//!   domain-separated HKDF info labels that bind the version, cipher suite and direction.
+//!   Both AES-256-GCM and ChaCha20-Poly1305 are supported.
+//!
+//! The version and cipher suite are negotiated from the ones advertised by the node, falling
+//! back to version 1 for nodes that advertise none.
+
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use base64::engine::{general_purpose::STANDARD, Engine};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// The size of the symmetric keys in bytes
+const KEY_SIZE: usize = 32;
+
+/// The size of the nonce in bytes
+pub const NONCE_SIZE: usize = 12;
+
+/// The prefix of the HKDF info labels of version 2 and above
+const INFO_LABEL_PREFIX: &str = "atoma-confidential-compute";
+
+type Result<T> = std::result::Result<T, EnvelopeError>;
+
+/// The version of the confidential compute envelope
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EnvelopeVersion {
+    /// Single key, empty HKDF info string, AES-256-GCM only
+    V1,
+    /// Separate request and response keys, domain-separated HKDF info labels
+    V2,
+}
+
+impl EnvelopeVersion {
+    /// The latest version supported by this client
+    pub const LATEST: Self = Self::V2;
+
+    /// The version number, as sent on the wire
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    /// Parses a version number, returning `None` for versions this client doesn't support
+    pub fn from_u8(version: u8) -> Option<Self> {
+        match version {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+}
+
+/// The AEAD cipher suite used to encrypt the payloads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CipherSuite {
+    /// AES-256-GCM, supported by every node
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    /// ChaCha20-Poly1305, for nodes without AES hardware acceleration
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// The name of the cipher suite, as sent on the wire and used in HKDF info labels
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+}
+
+/// The envelope parameters negotiated with a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnvelopeParams {
+    /// The envelope version
+    pub version: EnvelopeVersion,
+
+    /// The cipher suite
+    pub cipher_suite: CipherSuite,
+}
+
+impl EnvelopeParams {
+    /// The parameters of nodes that predate envelope versioning
+    pub const LEGACY: Self = Self {
+        version: EnvelopeVersion::V1,
+        cipher_suite: CipherSuite::Aes256Gcm,
+    };
+
+    /// Negotiates the envelope parameters with a node.
+    ///
+    /// The highest version supported by both sides is used. The preferred cipher suite is
+    /// used if the node supports it, AES-256-GCM otherwise, as every node supports it.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_versions` - The envelope versions advertised by the node, if any
+    /// * `node_cipher_suites` - The cipher suites advertised by the node, if any
+    /// * `preferred_cipher_suite` - The cipher suite preferred by the client
+    pub fn negotiate(
+        node_versions: Option<&[u8]>,
+        node_cipher_suites: Option<&[CipherSuite]>,
+        preferred_cipher_suite: CipherSuite,
+    ) -> Self {
+        let version = node_versions
+            .and_then(|versions| {
+                versions
+                    .iter()
+                    .filter_map(|version| EnvelopeVersion::from_u8(*version))
+                    .max()
+            })
+            .unwrap_or(EnvelopeVersion::V1);
+        if version == EnvelopeVersion::V1 {
+            return Self::LEGACY;
+        }
+        let cipher_suite = if node_cipher_suites
+            .is_some_and(|cipher_suites| cipher_suites.contains(&preferred_cipher_suite))
+        {
+            preferred_cipher_suite
+        } else {
+            CipherSuite::Aes256Gcm
+        };
+        Self {
+            version,
+            cipher_suite,
+        }
+    }
+
+    /// The version to send in requests, `None` for the legacy version
+    pub fn wire_version(&self) -> Option<u8> {
+        (self.version != EnvelopeVersion::V1).then(|| self.version.as_u8())
+    }
+
+    /// The cipher suite to send in requests, `None` for the legacy version
+    pub fn wire_cipher_suite(&self) -> Option<CipherSuite> {
+        (self.version != EnvelopeVersion::V1).then_some(self.cipher_suite)
+    }
+
+    /// The HKDF info label for the given direction, e.g.
+    /// `atoma-confidential-compute/v2/aes-256-gcm/request`
+    fn info_label(&self, direction: &str) -> Vec<u8> {
+        match self.version {
+            EnvelopeVersion::V1 => Vec::new(),
+            version => format!(
+                "{INFO_LABEL_PREFIX}/v{}/{}/{direction}",
+                version.as_u8(),
+                self.cipher_suite.as_str()
+            )
+            .into_bytes(),
+        }
+    }
+}
+
+/// The symmetric keys of an envelope
+pub struct EnvelopeKeys {
+    /// The key encrypting the request
+    pub request_key: [u8; KEY_SIZE],
+
+    /// The key encrypting the response
+    pub response_key: [u8; KEY_SIZE],
+}
+
+/// Derives the envelope keys from the X25519 shared secret between the client and the node.
+///
+/// # Arguments
+///
+/// * `params` - The negotiated envelope parameters
+/// * `client_private_key` - The client's X25519 private key
+/// * `node_public_key` - The node's X25519 public key
+/// * `salt` - The salt of the request
+pub fn derive_keys(
+    params: &EnvelopeParams,
+    client_private_key: &StaticSecret,
+    node_public_key: &PublicKey,
+    salt: &[u8],
+) -> Result<EnvelopeKeys> {
+    let shared_secret = client_private_key.diffie_hellman(node_public_key);
+    let hkdf = Hkdf::<Sha256>::new(Some(salt), shared_secret.as_bytes());
+    let mut request_key = [0u8; KEY_SIZE];
+    hkdf.expand(&params.info_label("request"), &mut request_key)?;
+    let mut response_key = [0u8; KEY_SIZE];
+    hkdf.expand(&params.info_label("response"), &mut response_key)?;
+    Ok(EnvelopeKeys {
+        request_key,
+        response_key,
+    })
+}
+
+/// Encrypts a payload with the given cipher suite
+pub fn seal(
+    cipher_suite: CipherSuite,
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    match cipher_suite {
+        CipherSuite::Aes256Gcm => Aes256Gcm::new(key.into()).encrypt(nonce.into(), plaintext),
+        CipherSuite::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.into()).encrypt(nonce.into(), plaintext)
+        }
+    }
+    .map_err(|e| EnvelopeError::SealError(e.to_string()))
+}
+
+/// Decrypts a payload with the given cipher suite
+pub fn open(
+    cipher_suite: CipherSuite,
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    match cipher_suite {
+        CipherSuite::Aes256Gcm => Aes256Gcm::new(key.into()).decrypt(nonce.into(), ciphertext),
+        CipherSuite::ChaCha20Poly1305 => {
+            ChaCha20Poly1305::new(key.into()).decrypt(nonce.into(), ciphertext)
+        }
+    }
+    .map_err(|e| EnvelopeError::OpenError(e.to_string()))
+}
+
+/// A known-answer vector, pinning the output of the envelope for fixed inputs.
+///
+/// Node implementations can use the same vectors to check that they interoperate with
+/// this client.
+pub struct KnownAnswerVector {
+    /// The envelope parameters
+    pub params: EnvelopeParams,
+
+    /// The client's X25519 private key
+    pub client_private_key: [u8; 32],
+
+    /// The node's X25519 private key
+    pub node_private_key: [u8; 32],
+
+    /// The salt
+    pub salt: [u8; 16],
+
+    /// The nonce, used for both the request and the response
+    pub nonce: [u8; NONCE_SIZE],
+
+    /// The plaintext, used for both the request and the response
+    pub plaintext: &'static str,
+
+    /// The expected request ciphertext (base64 encoded)
+    pub request_ciphertext: &'static str,
+
+    /// The expected response ciphertext (base64 encoded)
+    pub response_ciphertext: &'static str,
+}
+
+/// The known-answer vectors of every supported envelope version and cipher suite
+pub const KNOWN_ANSWER_VECTORS: &[KnownAnswerVector] = &[
+    KnownAnswerVector {
+        params: EnvelopeParams::LEGACY,
+        client_private_key: [0x11; 32],
+        node_private_key: [0x22; 32],
+        salt: [0x33; 16],
+        nonce: [0x44; NONCE_SIZE],
+        plaintext: r#"{"model":"known-answer"}"#,
+        request_ciphertext: "h02CNbMmdzo8C+wAqWbgVn/s9MG1Gcddm2ymewJE+ejZDWtDxD+ZSg==",
+        response_ciphertext: "h02CNbMmdzo8C+wAqWbgVn/s9MG1Gcddm2ymewJE+ejZDWtDxD+ZSg==",
+    },
+    KnownAnswerVector {
+        params: EnvelopeParams {
+            version: EnvelopeVersion::V2,
+            cipher_suite: CipherSuite::Aes256Gcm,
+        },
+        client_private_key: [0x11; 32],
+        node_private_key: [0x22; 32],
+        salt: [0x33; 16],
+        nonce: [0x44; NONCE_SIZE],
+        plaintext: r#"{"model":"known-answer"}"#,
+        request_ciphertext: "n2X1dMPYb1ayOSAyC5DCkxZLfn+DK7Vy047lmEDhn8ft1TfWQXG2Eg==",
+        response_ciphertext: "8uqbj00uB9XUMHTNDGlE8PyYVkkA3kvDzMMYcmIg2gkJub560qByXw==",
+    },
+    KnownAnswerVector {
+        params: EnvelopeParams {
+            version: EnvelopeVersion::V2,
+            cipher_suite: CipherSuite::ChaCha20Poly1305,
+        },
+        client_private_key: [0x11; 32],
+        node_private_key: [0x22; 32],
+        salt: [0x33; 16],
+        nonce: [0x44; NONCE_SIZE],
+        plaintext: r#"{"model":"known-answer"}"#,
+        request_ciphertext: "/Pqg1w0E4LQwCnVOWN4db02fTw5XQ00HjJdRqJASA2YSsgnLC9zk+A==",
+        response_ciphertext: "P1GaJBVF88TtECCiGGsCOcIIN9BBiGS4G6YsttFhhZZV9WYsqbNqeg==",
+    },
+];
+
+/// Checks the envelope implementation against `KNOWN_ANSWER_VECTORS`.
+///
+/// This is run once when the SDK is created, so that a broken cryptographic dependency
+/// is caught at boot rather than as undecryptable responses.
+///
+/// # Errors
+///
+/// Returns `EnvelopeError::KnownAnswerMismatch` if any vector doesn't match.
+pub fn verify_known_answer_vectors() -> Result<()> {
+    for vector in KNOWN_ANSWER_VECTORS {
+        let client_private_key = StaticSecret::from(vector.client_private_key);
+        let node_public_key = PublicKey::from(&StaticSecret::from(vector.node_private_key));
+        let keys = derive_keys(
+            &vector.params,
+            &client_private_key,
+            &node_public_key,
+            &vector.salt,
+        )?;
+        let plaintext = vector.plaintext.as_bytes();
+        let cipher_suite = vector.params.cipher_suite;
+        let request_ciphertext = seal(cipher_suite, &keys.request_key, &vector.nonce, plaintext)?;
+        let response_ciphertext = seal(cipher_suite, &keys.response_key, &vector.nonce, plaintext)?;
+        if STANDARD.encode(request_ciphertext) != vector.request_ciphertext
+            || STANDARD.encode(response_ciphertext) != vector.response_ciphertext
+        {
+            return Err(EnvelopeError::KnownAnswerMismatch(format!(
+                "version {}, {}",
+                vector.params.version.as_u8(),
+                cipher_suite.as_str()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum EnvelopeError {
+    #[error("Failed to expand key: `{0}`")]
+    KeyExpansionFailed(#[from] hkdf::InvalidLength),
+    #[error("Failed to encrypt payload: `{0}`")]
+    SealError(String),
+    #[error("Failed to decrypt payload: `{0}`")]
+    OpenError(String),
+    #[error("Envelope does not match its known-answer vector: `{0}`")]
+    KnownAnswerMismatch(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2_AES: EnvelopeParams = EnvelopeParams {
+        version: EnvelopeVersion::V2,
+        cipher_suite: CipherSuite::Aes256Gcm,
+    };
+
+    const V2_CHACHA: EnvelopeParams = EnvelopeParams {
+        version: EnvelopeVersion::V2,
+        cipher_suite: CipherSuite::ChaCha20Poly1305,
+    };
+
+    /// Derives the keys of a vector from the client's side
+    fn vector_keys(vector: &KnownAnswerVector) -> EnvelopeKeys {
+        derive_keys(
+            &vector.params,
+            &StaticSecret::from(vector.client_private_key),
+            &PublicKey::from(&StaticSecret::from(vector.node_private_key)),
+            &vector.salt,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn known_answer_vectors_match() {
+        verify_known_answer_vectors().unwrap();
+    }
+
+    #[test]
+    fn known_answer_vectors_cover_every_version_and_cipher_suite() {
+        let params = KNOWN_ANSWER_VECTORS
+            .iter()
+            .map(|vector| vector.params)
+            .collect::<Vec<_>>();
+
+        assert_eq!(params, [EnvelopeParams::LEGACY, V2_AES, V2_CHACHA]);
+    }
+
+    #[test]
+    fn known_answer_ciphertexts_open_to_the_plaintext() {
+        for vector in KNOWN_ANSWER_VECTORS {
+            let keys = vector_keys(vector);
+            let cipher_suite = vector.params.cipher_suite;
+            let request_ciphertext = STANDARD.decode(vector.request_ciphertext).unwrap();
+            let response_ciphertext = STANDARD.decode(vector.response_ciphertext).unwrap();
+
+            let request = open(
+                cipher_suite,
+                &keys.request_key,
+                &vector.nonce,
+                &request_ciphertext,
+            )
+            .unwrap();
+            let response = open(
+                cipher_suite,
+                &keys.response_key,
+                &vector.nonce,
+                &response_ciphertext,
+            )
+            .unwrap();
+
+            assert_eq!(request, vector.plaintext.as_bytes());
+            assert_eq!(response, vector.plaintext.as_bytes());
+        }
+    }
+
+    #[test]
+    fn the_node_derives_the_same_keys() {
+        for vector in KNOWN_ANSWER_VECTORS {
+            let client_keys = vector_keys(vector);
+            let node_keys = derive_keys(
+                &vector.params,
+                &StaticSecret::from(vector.node_private_key),
+                &PublicKey::from(&StaticSecret::from(vector.client_private_key)),
+                &vector.salt,
+            )
+            .unwrap();
+
+            assert_eq!(client_keys.request_key, node_keys.request_key);
+            assert_eq!(client_keys.response_key, node_keys.response_key);
+        }
+    }
+
+    #[test]
+    fn v1_shares_a_key_and_v2_separates_the_directions() {
+        let [legacy, v2_aes, v2_chacha] = KNOWN_ANSWER_VECTORS else {
+            panic!("Expected a vector per version and cipher suite");
+        };
+
+        let legacy_keys = vector_keys(legacy);
+        assert_eq!(legacy_keys.request_key, legacy_keys.response_key);
+
+        let v2_aes_keys = vector_keys(v2_aes);
+        let v2_chacha_keys = vector_keys(v2_chacha);
+        assert_ne!(v2_aes_keys.request_key, v2_aes_keys.response_key);
+        assert_ne!(v2_aes_keys.request_key, legacy_keys.request_key);
+        assert_ne!(v2_aes_keys.request_key, v2_chacha_keys.request_key);
+
+        // A request cannot be opened with the response key, nor reflected as a response
+        let request_ciphertext = STANDARD.decode(v2_aes.request_ciphertext).unwrap();
+        assert!(open(
+            CipherSuite::Aes256Gcm,
+            &v2_aes_keys.response_key,
+            &v2_aes.nonce,
+            &request_ciphertext,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertexts_fail_to_open() {
+        for vector in KNOWN_ANSWER_VECTORS {
+            let keys = vector_keys(vector);
+            let mut ciphertext = STANDARD.decode(vector.response_ciphertext).unwrap();
+            ciphertext[0] ^= 1;
+
+            let opened = open(
+                vector.params.cipher_suite,
+                &keys.response_key,
+                &vector.nonce,
+                &ciphertext,
+            );
+
+            assert!(matches!(opened, Err(EnvelopeError::OpenError(_))));
+        }
+    }
+
+    #[test]
+    fn v2_info_labels_bind_the_version_cipher_suite_and_direction() {
+        assert!(EnvelopeParams::LEGACY.info_label("request").is_empty());
+        assert_eq!(
+            V2_AES.info_label("request"),
+            b"atoma-confidential-compute/v2/aes-256-gcm/request"
+        );
+        assert_eq!(
+            V2_CHACHA.info_label("response"),
+            b"atoma-confidential-compute/v2/chacha20-poly1305/response"
+        );
+    }
+
+    #[test]
+    fn nodes_advertising_nothing_get_the_legacy_envelope() {
+        let params = EnvelopeParams::negotiate(None, None, CipherSuite::ChaCha20Poly1305);
+
+        assert_eq!(params, EnvelopeParams::LEGACY);
+        assert_eq!(params.wire_version(), None);
+        assert_eq!(params.wire_cipher_suite(), None);
+    }
+
+    #[test]
+    fn v1_nodes_get_aes_whatever_they_advertise() {
+        let params = EnvelopeParams::negotiate(
+            Some(&[1]),
+            Some(&[CipherSuite::ChaCha20Poly1305]),
+            CipherSuite::ChaCha20Poly1305,
+        );
+
+        assert_eq!(params, EnvelopeParams::LEGACY);
+    }
+
+    #[test]
+    fn the_highest_common_version_is_negotiated() {
+        let params = EnvelopeParams::negotiate(Some(&[1, 2, 3]), None, CipherSuite::Aes256Gcm);
+
+        assert_eq!(params, V2_AES);
+        assert_eq!(params.wire_version(), Some(2));
+        assert_eq!(params.wire_cipher_suite(), Some(CipherSuite::Aes256Gcm));
+    }
+
+    #[test]
+    fn unsupported_versions_only_fall_back_to_v1() {
+        let params = EnvelopeParams::negotiate(Some(&[3]), None, CipherSuite::Aes256Gcm);
+
+        assert_eq!(params, EnvelopeParams::LEGACY);
+    }
+
+    #[test]
+    fn the_preferred_cipher_suite_is_used_if_the_node_supports_it() {
+        let both = [CipherSuite::Aes256Gcm, CipherSuite::ChaCha20Poly1305];
+
+        assert_eq!(
+            EnvelopeParams::negotiate(Some(&[2]), Some(&both), CipherSuite::ChaCha20Poly1305),
+            V2_CHACHA
+        );
+        assert_eq!(
+            EnvelopeParams::negotiate(
+                Some(&[2]),
+                Some(&[CipherSuite::Aes256Gcm]),
+                CipherSuite::ChaCha20Poly1305
+            ),
+            V2_AES
+        );
+        assert_eq!(
+            EnvelopeParams::negotiate(Some(&[2]), None, CipherSuite::ChaCha20Poly1305),
+            V2_AES
+        );
+    }
+}
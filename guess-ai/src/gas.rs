@@ -0,0 +1,294 @@
+use sui_sdk::{rpc_types::Coin, types::base_types::ObjectRef};
+
+use crate::config::GasPoolConfig;
+
+/// How the SUI balance of the agent wallet compares to the configured thresholds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceStatus {
+    /// The balance is above the low balance threshold
+    Healthy,
+    /// The balance is below the low balance threshold, an operator should top up the wallet
+    Low,
+    /// The balance is below the minimum balance, transactions are refused
+    Critical,
+}
+
+/// A gas coin of the agent wallet
+#[derive(Clone, Debug)]
+struct GasCoin {
+    /// The latest known reference of the coin
+    object_ref: ObjectRef,
+
+    /// The balance of the coin, in MIST
+    balance: u64,
+}
+
+/// The pool of gas coins of the agent wallet.
+///
+/// The pool hands out its coins in turn, so that consecutive transactions don't
+/// contend on a single coin, and tells which coins should be merged (dust) and how
+/// many coins should be split to keep `pool_size` coins available. It holds no
+/// connection to the network: its coins are refreshed by the `SuiClientContext`.
+pub struct GasPool {
+    /// The configuration of the pool
+    config: GasPoolConfig,
+
+    /// The coins whose balance is above the dust threshold, largest first
+    coins: Vec<GasCoin>,
+
+    /// The coins whose balance is at most the dust threshold
+    dust: Vec<GasCoin>,
+
+    /// The total SUI balance of the wallet, in MIST
+    total_balance: u64,
+
+    /// The number of coins handed out since the pool was created
+    handed_out: usize,
+}
+
+impl GasPool {
+    /// Constructor
+    pub fn new(config: GasPoolConfig) -> Self {
+        Self {
+            config,
+            coins: Vec::new(),
+            dust: Vec::new(),
+            total_balance: 0,
+            handed_out: 0,
+        }
+    }
+
+    /// Replaces the coins of the pool with the SUI coins currently owned by the wallet
+    pub fn update(&mut self, coins: Vec<Coin>) {
+        let coins = coins.into_iter().map(|coin| GasCoin {
+            object_ref: coin.object_ref(),
+            balance: coin.balance,
+        });
+        let (mut usable, dust): (Vec<_>, Vec<_>) =
+            coins.partition(|coin| coin.balance > self.config.dust_threshold);
+        usable.sort_by(|a, b| b.balance.cmp(&a.balance));
+        self.total_balance = usable.iter().chain(&dust).map(|coin| coin.balance).sum();
+        self.coins = usable;
+        self.dust = dust;
+    }
+
+    /// The total SUI balance of the wallet, in MIST, as of the last update
+    pub fn total_balance(&self) -> u64 {
+        self.total_balance
+    }
+
+    /// Compares the total balance to the configured thresholds
+    pub fn balance_status(&self) -> BalanceStatus {
+        if self.total_balance < self.config.min_balance {
+            BalanceStatus::Critical
+        } else if self.total_balance < self.config.low_balance_threshold {
+            BalanceStatus::Low
+        } else {
+            BalanceStatus::Healthy
+        }
+    }
+
+    /// The minimum balance below which transactions are refused, in MIST
+    pub fn min_balance(&self) -> u64 {
+        self.config.min_balance
+    }
+
+    /// Hands out the next coin able to pay for `gas_budget`, in turn
+    pub fn next_coin(&mut self, gas_budget: u64) -> Option<ObjectRef> {
+        let candidates = self
+            .coins
+            .iter()
+            .filter(|coin| coin.balance >= gas_budget)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return None;
+        }
+        let coin = candidates[self.handed_out % candidates.len()];
+        self.handed_out += 1;
+        Some(coin.object_ref)
+    }
+
+    /// Whether the pool, as of the last update, has dust coins to merge or is short of coins
+    /// it can split, so that the maintenance transactions are only sent when they change
+    /// something
+    pub fn needs_maintenance(&self) -> bool {
+        self.coins_to_merge().is_some() || self.coins_to_split().is_some()
+    }
+
+    /// The coins to merge into the largest coin, if there is more than one dust coin
+    pub fn coins_to_merge(&self) -> Option<(ObjectRef, Vec<ObjectRef>)> {
+        if self.dust.len() < 2 {
+            return None;
+        }
+        let mut dust = self.dust.iter().map(|coin| coin.object_ref);
+        let primary = match self.coins.first() {
+            Some(coin) => coin.object_ref,
+            None => dust.next()?,
+        };
+        Some((primary, dust.collect()))
+    }
+
+    /// The coin to split, and the number of coins of `coin_balance` to split from it,
+    /// if the pool holds fewer than `pool_size` coins.
+    ///
+    /// The largest coin always keeps at least `coin_balance`, so splitting never
+    /// starves the pool when the balance runs low. Nothing is split if `coin_balance` is zero.
+    pub fn coins_to_split(&self) -> Option<(ObjectRef, Vec<u64>)> {
+        let missing = self.config.pool_size.checked_sub(self.coins.len())?;
+        let largest = self.coins.first()?;
+        let affordable = largest
+            .balance
+            .checked_div(self.config.coin_balance)?
+            .saturating_sub(1) as usize;
+        let count = missing.min(affordable);
+        (count > 0).then(|| (largest.object_ref, vec![self.config.coin_balance; count]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_sdk::types::{
+        base_types::{ObjectID, SequenceNumber},
+        digests::{ObjectDigest, TransactionDigest},
+    };
+
+    use super::*;
+
+    const SUI: u64 = 1_000_000_000;
+
+    fn config() -> GasPoolConfig {
+        GasPoolConfig {
+            pool_size: 3,
+            coin_balance: SUI / 5,
+            dust_threshold: SUI / 20,
+            low_balance_threshold: SUI,
+            min_balance: SUI / 10,
+        }
+    }
+
+    /// A SUI coin whose ID ends with `id`
+    fn coin(id: u8, balance: u64) -> Coin {
+        Coin {
+            coin_type: "0x2::sui::SUI".to_string(),
+            coin_object_id: ObjectID::from_single_byte(id),
+            version: SequenceNumber::from_u64(1),
+            digest: ObjectDigest::random(),
+            balance,
+            previous_transaction: TransactionDigest::random(),
+        }
+    }
+
+    fn id(object_ref: ObjectRef) -> ObjectID {
+        object_ref.0
+    }
+
+    #[test]
+    fn update_sorts_coins_and_sets_aside_dust() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, SUI / 2), coin(2, SUI / 100), coin(3, 2 * SUI)]);
+
+        assert_eq!(pool.total_balance(), 2 * SUI + SUI / 2 + SUI / 100);
+        assert_eq!(pool.balance_status(), BalanceStatus::Healthy);
+        assert_eq!(
+            id(pool.next_coin(0).unwrap()),
+            ObjectID::from_single_byte(3)
+        );
+        assert_eq!(
+            id(pool.next_coin(0).unwrap()),
+            ObjectID::from_single_byte(1)
+        );
+    }
+
+    #[test]
+    fn balance_status_follows_the_thresholds() {
+        let mut pool = GasPool::new(config());
+
+        pool.update(vec![coin(1, SUI / 2)]);
+        assert_eq!(pool.balance_status(), BalanceStatus::Low);
+
+        pool.update(vec![coin(1, SUI / 20)]);
+        assert_eq!(pool.balance_status(), BalanceStatus::Critical);
+    }
+
+    #[test]
+    fn next_coin_hands_out_coins_able_to_pay_in_turn() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, SUI), coin(2, SUI / 10), coin(3, 2 * SUI)]);
+
+        let handed_out = (0..4)
+            .map(|_| id(pool.next_coin(SUI / 2).unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            handed_out,
+            [3, 1, 3, 1].map(ObjectID::from_single_byte).to_vec()
+        );
+        assert_eq!(pool.next_coin(3 * SUI), None);
+    }
+
+    #[test]
+    fn coins_to_merge_needs_two_dust_coins() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, SUI), coin(2, SUI / 100)]);
+        assert_eq!(pool.coins_to_merge(), None);
+
+        pool.update(vec![coin(1, SUI), coin(2, SUI / 100), coin(3, SUI / 50)]);
+        let (primary, dust) = pool.coins_to_merge().unwrap();
+        assert_eq!(id(primary), ObjectID::from_single_byte(1));
+        assert_eq!(
+            dust.into_iter().map(id).collect::<Vec<_>>(),
+            [2, 3].map(ObjectID::from_single_byte).to_vec()
+        );
+    }
+
+    #[test]
+    fn coins_to_merge_without_usable_coins_merges_into_the_first_dust_coin() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, SUI / 100), coin(2, SUI / 50)]);
+
+        let (primary, dust) = pool.coins_to_merge().unwrap();
+
+        assert_eq!(id(primary), ObjectID::from_single_byte(1));
+        assert_eq!(
+            dust.into_iter().map(id).collect::<Vec<_>>(),
+            vec![ObjectID::from_single_byte(2)]
+        );
+    }
+
+    #[test]
+    fn coins_to_split_fills_the_pool_as_far_as_the_balance_allows() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, 10 * SUI)]);
+        let (coin_to_split, amounts) = pool.coins_to_split().unwrap();
+        assert_eq!(id(coin_to_split), ObjectID::from_single_byte(1));
+        assert_eq!(amounts, vec![SUI / 5; 2]);
+
+        // The largest coin keeps `coin_balance`
+        pool.update(vec![coin(1, SUI / 2)]);
+        assert_eq!(pool.coins_to_split().unwrap().1, vec![SUI / 5]);
+
+        pool.update(vec![coin(1, SUI / 4)]);
+        assert_eq!(pool.coins_to_split(), None);
+        assert!(!pool.needs_maintenance());
+    }
+
+    #[test]
+    fn coins_to_split_is_none_when_the_pool_is_full() {
+        let mut pool = GasPool::new(config());
+        pool.update(vec![coin(1, SUI), coin(2, SUI), coin(3, SUI), coin(4, SUI)]);
+
+        assert_eq!(pool.coins_to_split(), None);
+        assert!(!pool.needs_maintenance());
+    }
+
+    #[test]
+    fn coins_to_split_with_a_zero_coin_balance_does_not_panic() {
+        let mut pool = GasPool::new(GasPoolConfig {
+            coin_balance: 0,
+            ..config()
+        });
+        pool.update(vec![coin(1, SUI)]);
+
+        assert_eq!(pool.coins_to_split(), None);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use config::{Config, File};
+use serde::Deserialize;
+
+use crate::{
+    config::ConfigValidator,
+    secrets::{Secret, SecretProviders},
+};
+
+/// Configuration for the OpenAI-compatible confidential compute gateway
+#[derive(Debug, Deserialize)]
+pub struct GatewayConfig {
+    /// Bind address for the gateway.
+    ///
+    /// This field specifies the address and port on which the gateway will bind.
+    pub service_bind_address: String,
+
+    /// The clients allowed to use the gateway, each with its own API key
+    #[serde(default)]
+    pub clients: Vec<GatewayClient>,
+}
+
+/// A client of the gateway
+#[derive(Clone, Debug, Deserialize)]
+pub struct GatewayClient {
+    /// The name of the client, used in logs
+    pub name: String,
+
+    /// The API key the client sends as a bearer token, given inline or read from a secret
+    /// provider
+    pub api_key: Secret,
+}
+
+impl GatewayConfig {
+    /// Creates a new `GatewayConfig` instance from a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_file_path` - Path to the configuration file. The file should be in a format
+    ///   supported by the `config` crate (e.g., YAML, JSON, TOML) and contain a "gateway"
+    ///   section with the required configuration fields.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `GatewayConfig` instance populated with values from the config file.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if:
+    /// * The configuration file cannot be read or parsed
+    /// * The "gateway" section is missing from the configuration
+    /// * The configuration format doesn't match the expected structure
+    pub fn from_file_path<P: AsRef<Path>>(config_file_path: P) -> Self {
+        Self::try_from_file_path(config_file_path)
+            .expect("Failed to generate configuration instance")
+    }
+
+    /// Loads a `GatewayConfig` instance like [`Self::from_file_path`], without validating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "gateway" section doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+    ) -> Result<Self, config::ConfigError> {
+        Config::builder()
+            .add_source(File::with_name(
+                &config_file_path.as_ref().to_string_lossy(),
+            ))
+            .add_source(
+                config::Environment::with_prefix("GATEWAY")
+                    .keep_prefix(true)
+                    .separator("__"),
+            )
+            .build()?
+            .get::<Self>("gateway")
+    }
+
+    /// Reads the secrets which reference a provider, recording the ones which cannot be read
+    /// in `validator`
+    pub fn resolve_secrets(
+        &mut self,
+        providers: &SecretProviders,
+        validator: &mut ConfigValidator,
+    ) {
+        for (i, client) in self.clients.iter_mut().enumerate() {
+            validator.resolve(
+                &format!("gateway.clients[{i}].api_key"),
+                &mut client.api_key,
+                providers,
+            );
+        }
+    }
+
+    /// Checks the configuration values, recording every problem found in `validator`
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.bind_address("gateway.service_bind_address", &self.service_bind_address);
+        for (i, client) in self.clients.iter().enumerate() {
+            validator.non_empty(&format!("gateway.clients[{i}].name"), &client.name);
+            validator.non_empty_secret(&format!("gateway.clients[{i}].api_key"), &client.api_key);
+        }
+    }
+}
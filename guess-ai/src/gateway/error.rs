@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Error type for the gateway.
+#[derive(Error, Debug)]
+pub enum GatewayError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("No client is configured for the gateway")]
+    NoClientsConfigured,
+    #[error("The gateway requires the Atoma confidential compute backend, not a local backend")]
+    LocalBackendNotAllowed,
+}
@@ -0,0 +1,7 @@
+mod config;
+mod error;
+mod server;
+
+pub use config::{GatewayClient, GatewayConfig};
+pub use error::GatewayError;
+pub use server::*;
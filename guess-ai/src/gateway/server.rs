@@ -0,0 +1,381 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::json;
+use tokio::net::TcpListener;
+use tracing::{error, info, instrument};
+use x25519_dalek::StaticSecret;
+
+use crate::{
+    atoma::{AtomaSdk, AtomaSdkError},
+    http_server::constant_time_eq,
+    types::{ChatCompletionRequest, ChatCompletionResponse},
+};
+
+use super::{GatewayClient, GatewayConfig, GatewayError};
+
+const CHAT_COMPLETIONS_PATH: &str = "/v1/chat/completions";
+const HEALTH_PATH: &str = "/health";
+
+/// The prefix of the authorization header value
+const BEARER_PREFIX: &str = "Bearer ";
+
+#[derive(Clone)]
+pub struct GatewayState {
+    /// The Atoma SDK, through which every request is forwarded
+    atoma_sdk: Arc<AtomaSdk>,
+
+    /// The X25519 private key of the gateway, used for the confidential compute envelope
+    client_private_key: Arc<StaticSecret>,
+
+    /// The clients allowed to use the gateway
+    clients: Arc<Vec<GatewayClient>>,
+}
+
+/// Starts the OpenAI-compatible gateway.
+///
+/// The gateway accepts standard `/v1/chat/completions` requests from authenticated clients
+/// and forwards them through the Atoma confidential compute endpoint. Requests are encrypted,
+/// and responses decrypted and verified, within this process, so that clients only ever see
+/// plain OpenAI-compatible requests and responses.
+///
+/// # Arguments
+///
+/// * `config` - The configuration for the gateway.
+/// * `atoma_sdk` - The Atoma SDK used to forward the requests.
+/// * `shutdown_receiver` - The receiver for the shutdown signal.
+///
+/// # Errors
+///
+/// Returns a `GatewayError` if no client is configured, the SDK targets a local backend
+/// (which has no confidential compute), or the server fails to bind or serve.
+pub async fn start_gateway(
+    config: GatewayConfig,
+    atoma_sdk: AtomaSdk,
+    mut shutdown_receiver: tokio::sync::watch::Receiver<bool>,
+) -> Result<(), GatewayError> {
+    if config.clients.is_empty() {
+        return Err(GatewayError::NoClientsConfigured);
+    }
+    if atoma_sdk.is_local() {
+        return Err(GatewayError::LocalBackendNotAllowed);
+    }
+    let tcp_listener = TcpListener::bind(&config.service_bind_address).await?;
+    info!(
+        target = "gateway",
+        event = "gateway-start",
+        service_bind_address = %config.service_bind_address,
+        num_clients = config.clients.len(),
+        "Starting confidential compute gateway"
+    );
+    let state = GatewayState {
+        atoma_sdk: Arc::new(atoma_sdk),
+        client_private_key: Arc::new(StaticSecret::random_from_rng(&mut rand::thread_rng())),
+        clients: Arc::new(config.clients),
+    };
+    let router = create_router(state);
+    let server =
+        axum::serve(tcp_listener, router.into_make_service()).with_graceful_shutdown(async move {
+            shutdown_receiver
+                .changed()
+                .await
+                .expect("Error receiving shutdown signal")
+        });
+    server.await?;
+    Ok(())
+}
+
+/// Creates the router for the gateway.
+fn create_router(state: GatewayState) -> Router {
+    Router::new()
+        .route(CHAT_COMPLETIONS_PATH, post(chat_completions_handler))
+        .route(HEALTH_PATH, get(health))
+        .with_state(state)
+}
+
+#[instrument(level = "info")]
+pub async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Handles an OpenAI-compatible chat completion request.
+///
+/// The request is forwarded through `AtomaSdk::confidential_chat_completions`, and the
+/// decrypted, signature-verified response is returned to the client.
+#[instrument(level = "info", skip_all, fields(model = %request.model))]
+async fn chat_completions_handler(
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Result<Json<ChatCompletionResponse>, GatewayApiError> {
+    let client = authenticate(&state.clients, &headers)?;
+    if request.stream.unwrap_or(false) {
+        return Err(GatewayApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_request_error",
+            "Streaming is not supported by the confidential compute gateway",
+        ));
+    }
+    let response = state
+        .atoma_sdk
+        .confidential_chat_completions(&state.client_private_key, request)
+        .await
+        .map_err(|e| {
+            error!(
+                target = "gateway",
+                event = "chat-completions-error",
+                client = %client.name,
+                "Failed to forward chat completion request: {e}"
+            );
+            GatewayApiError::from(e)
+        })?;
+    info!(
+        target = "gateway",
+        event = "chat-completions",
+        client = %client.name,
+        "Forwarded chat completion request"
+    );
+    Ok(Json(response))
+}
+
+/// Returns the client whose API key is the bearer token of the request
+fn authenticate<'a>(
+    clients: &'a [GatewayClient],
+    headers: &HeaderMap,
+) -> Result<&'a GatewayClient, GatewayApiError> {
+    let unauthorized = || {
+        GatewayApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "authentication_error",
+            "Invalid or missing API key",
+        )
+    };
+    let api_key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .ok_or_else(unauthorized)?;
+    clients
+        .iter()
+        .find(|client| {
+            constant_time_eq(
+                client.api_key.expose_secret().as_bytes(),
+                api_key.as_bytes(),
+            )
+        })
+        .ok_or_else(unauthorized)
+}
+
+/// An OpenAI-compatible error response
+#[derive(Debug)]
+pub struct GatewayApiError {
+    /// The HTTP status of the response
+    status: StatusCode,
+
+    /// The OpenAI error type, e.g. `invalid_request_error`
+    error_type: &'static str,
+
+    /// A human readable description of the error
+    message: String,
+
+    /// How long the client should wait before retrying, if known
+    retry_after: Option<Duration>,
+}
+
+impl GatewayApiError {
+    fn new(status: StatusCode, error_type: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error_type,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+}
+
+impl From<AtomaSdkError> for GatewayApiError {
+    fn from(error: AtomaSdkError) -> Self {
+        let (status, error_type) = match &error {
+            AtomaSdkError::RateLimitedError { .. } => {
+                (StatusCode::TOO_MANY_REQUESTS, "rate_limit_error")
+            }
+            AtomaSdkError::ModelUnavailableError(_) => (StatusCode::NOT_FOUND, "not_found_error"),
+            AtomaSdkError::NoHealthyNodeError(_) => (StatusCode::SERVICE_UNAVAILABLE, "api_error"),
+            AtomaSdkError::ApiError { status, .. } if (400..500).contains(status) => (
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_REQUEST),
+                "invalid_request_error",
+            ),
+            // The gateway's own credentials or balance are at fault, not the client's
+            AtomaSdkError::AuthenticationError(_) | AtomaSdkError::InsufficientBalanceError(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "api_error")
+            }
+            _ => (StatusCode::BAD_GATEWAY, "api_error"),
+        };
+        Self {
+            status,
+            error_type,
+            message: error.to_string(),
+            retry_after: error.retry_after(),
+        }
+    }
+}
+
+impl IntoResponse for GatewayApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "message": self.message,
+                "type": self.error_type,
+                "code": self.status.as_u16(),
+            }
+        });
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(retry_after) = self.retry_after {
+            if let Ok(value) = HeaderValue::from_str(&retry_after.as_secs().max(1).to_string()) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{atoma::AtomaTransportConfig, secrets::Secret};
+
+    const API_KEY: &str = "client-api-key";
+
+    /// Builds the gateway router, forwarding the requests to the backend at `base_url`
+    fn router(base_url: &str) -> Router {
+        let transport_config = AtomaTransportConfig {
+            max_retries: 0,
+            ..AtomaTransportConfig::default()
+        };
+        let atoma_sdk = AtomaSdk::local(
+            base_url.to_string(),
+            None,
+            "model".to_string(),
+            transport_config,
+        )
+        .unwrap();
+        create_router(GatewayState {
+            atoma_sdk: Arc::new(atoma_sdk),
+            client_private_key: Arc::new(StaticSecret::random_from_rng(rand::thread_rng())),
+            clients: Arc::new(vec![GatewayClient {
+                name: "client".to_string(),
+                api_key: Secret::new(API_KEY),
+            }]),
+        })
+    }
+
+    fn chat_completions_request(authorization: Option<&str>, stream: bool) -> Request<Body> {
+        let mut request =
+            Request::post(CHAT_COMPLETIONS_PATH).header(header::CONTENT_TYPE, "application/json");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        let body = json!({
+            "model": "model",
+            "messages": [{ "role": "user", "content": "Hello" }],
+            "stream": stream,
+        });
+        request.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn error_body(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_or_unknown_api_key() {
+        for authorization in [None, Some("Bearer wrong-key"), Some(API_KEY)] {
+            let response = router("http://127.0.0.1:9")
+                .oneshot(chat_completions_request(authorization, false))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            let body = error_body(response).await;
+            assert_eq!(body["error"]["type"], "authentication_error");
+            assert_eq!(body["error"]["code"], 401);
+            assert_eq!(body["error"]["message"], "Invalid or missing API key");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_streaming() {
+        let response = router("http://127.0.0.1:9")
+            .oneshot(chat_completions_request(
+                Some(&format!("Bearer {API_KEY}")),
+                true,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = error_body(response).await;
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+        assert_eq!(body["error"]["code"], 400);
+    }
+
+    #[tokio::test]
+    async fn test_forwards_backend_errors_in_openai_shape() {
+        let backend = Router::new().route(
+            CHAT_COMPLETIONS_PATH,
+            post(|| async {
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, "7")],
+                    Json(json!({
+                        "error": { "code": "TOO_MANY_REQUESTS", "message": "Slow down" }
+                    })),
+                )
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, backend).await });
+
+        let response = router(&base_url)
+            .oneshot(chat_completions_request(
+                Some(&format!("Bearer {API_KEY}")),
+                false,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+        let body = error_body(response).await;
+        assert_eq!(body["error"]["type"], "rate_limit_error");
+        assert_eq!(body["error"]["code"], 429);
+        assert!(body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Slow down"));
+    }
+
+    #[tokio::test]
+    async fn test_health_is_not_authenticated() {
+        let response = router("http://127.0.0.1:9")
+            .oneshot(Request::get(HEALTH_PATH).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -7,6 +7,9 @@ use crate::{
     atoma::{AtomaSdk, AtomaSdkError},
     client::{SuiClientContext, SuiClientError},
     engine::prompts::SecretPromptResponse,
+    structured_output::{self, RepairAttempts, StructuredOutputError},
+    types::ChatCompletionRequest,
+    usage::{InferencePurpose, UsageTracker},
 };
 
 type Result<T> = std::result::Result<T, GenerateSecretError>;
@@ -25,6 +28,7 @@ type Result<T> = std::result::Result<T, GenerateSecretError>;
 /// * `generate_secret_prompt` - The prompt text used to generate the secret
 /// * `model` - The name/identifier of the AI model to use
 /// * `sui_client_ctx` - Reference to the Sui client context for network operations
+/// * `usage_tracker` - The usage tracker in which the request's usage is recorded
 ///
 /// # Returns
 ///
@@ -35,7 +39,9 @@ type Result<T> = std::result::Result<T, GenerateSecretError>;
 /// This function can return the following errors:
 /// * `GenerateSecretError::FailedToSubmitNodePublicKey` - If registering the public key with the network fails
 /// * `GenerateSecretError::FailedToGenerateChatCompletions` - If the AI completion request fails
-/// * `GenerateSecretError::FailedToParseSecretPromptResponse` - If parsing the AI response fails
+/// * `GenerateSecretError::FailedToBuildSecretPromptRequest` - If the request cannot be built
+/// * `GenerateSecretError::InvalidSecretPromptResponse` - If the AI response cannot be parsed,
+///   even after asking the model to repair it
 ///
 /// # Instrumentation
 ///
@@ -55,6 +61,7 @@ pub async fn generate_new_secret(
     model: String,
     random_seed: i64,
     sui_client_ctx: &mut SuiClientContext,
+    usage_tracker: &mut UsageTracker,
 ) -> Result<String> {
     let client_public_key = PublicKey::from(client_private_key);
     // let tdx_quote_bytes = tdx::generate_tdx_quote_bytes(&mut rng);
@@ -64,26 +71,39 @@ pub async fn generate_new_secret(
         .submit_node_public_key(client_public_key, tdx_quote_bytes, None, None, None)
         .await?;
 
-    let chat_completions_request = serde_json::from_value(json!({
+    let mut chat_completions_request: ChatCompletionRequest = serde_json::from_value(json!({
         "model": model,
         "messages": [
             {"role": "system", "content": generate_secret_prompt},
         ],
         "seed": random_seed,
+        "max_tokens": usage_tracker.budget().secret_max_tokens,
+        "response_format": structured_output::response_format::<SecretPromptResponse>(),
     }))?;
 
-    #[cfg(feature = "confidential-compute")]
-    let response_body = atoma_sdk
-        .confidential_chat_completions(&client_private_key, chat_completions_request)
-        .await?;
-    #[cfg(not(feature = "confidential-compute"))]
-    let response_body = atoma_sdk.chat_completions(chat_completions_request).await?;
-
-    let secret = serde_json::from_str::<SecretPromptResponse>(
-        &response_body.choices[0].message.content.clone(),
-    )?;
+    let mut repair_attempts = RepairAttempts::default();
+    loop {
+        #[cfg(feature = "confidential-compute")]
+        let response_body = atoma_sdk
+            .confidential_chat_completions(&client_private_key, chat_completions_request.clone())
+            .await?;
+        #[cfg(not(feature = "confidential-compute"))]
+        let response_body = atoma_sdk
+            .chat_completions(chat_completions_request.clone())
+            .await?;
+        usage_tracker.record(
+            InferencePurpose::Secret,
+            &model,
+            response_body.usage.as_ref(),
+        );
 
-    Ok(secret.secret)
+        if let Some(response) = repair_attempts.parse_or_repair::<SecretPromptResponse>(
+            &response_body,
+            &mut chat_completions_request.messages,
+        )? {
+            return Ok(response.secret);
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -94,6 +114,9 @@ pub enum GenerateSecretError {
     #[error("Failed to generate chat completions")]
     FailedToGenerateChatCompletions(#[from] AtomaSdkError),
 
-    #[error("Failed to parse secret prompt response")]
-    FailedToParseSecretPromptResponse(#[from] serde_json::Error),
+    #[error("Failed to build secret prompt request")]
+    FailedToBuildSecretPromptRequest(#[from] serde_json::Error),
+
+    #[error("Failed to parse secret prompt response: {0}")]
+    InvalidSecretPromptResponse(#[from] StructuredOutputError),
 }
@@ -3,12 +3,23 @@ use std::path::Path;
 use config::{Config, File};
 use serde::Deserialize;
 
+use crate::{
+    config::ConfigValidator,
+    secrets::{Secret, SecretProviders},
+};
+
 #[derive(Debug, Deserialize)]
 pub struct HttpServerConfig {
     /// Bind address for the Http Server.
     ///
     /// This field specifies the address and port on which the Atoma Proxy Server will bind.
     pub service_bind_address: String,
+
+    /// Token the operators send as a bearer token to use the admin endpoints.
+    ///
+    /// The admin endpoints reject every request if unset.
+    #[serde(default)]
+    pub admin_token: Option<Secret>,
 }
 
 impl HttpServerConfig {
@@ -31,18 +42,52 @@ impl HttpServerConfig {
     /// * The "http_service" section is missing from the configuration
     /// * The configuration format doesn't match the expected structure
     pub fn from_file_path<P: AsRef<Path>>(config_file_path: P) -> Self {
-        let builder = Config::builder()
-            .add_source(File::with_name(config_file_path.as_ref().to_str().unwrap()))
+        Self::try_from_file_path(config_file_path)
+            .expect("Failed to generate configuration instance")
+    }
+
+    /// Loads a `HttpServerConfig` instance like [`Self::from_file_path`], without validating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "http_server" section doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+    ) -> Result<Self, config::ConfigError> {
+        Config::builder()
+            .add_source(File::with_name(
+                &config_file_path.as_ref().to_string_lossy(),
+            ))
             .add_source(
                 config::Environment::with_prefix("HTTP_SERVER")
                     .keep_prefix(true)
                     .separator("__"),
-            );
-        let config = builder
-            .build()
-            .expect("Failed to generate atoma-service configuration file");
-        config
+            )
+            .build()?
             .get::<Self>("http_server")
-            .expect("Failed to generate configuration instance")
+    }
+
+    /// Reads the secrets which reference a provider, recording the ones which cannot be read
+    /// in `validator`
+    pub fn resolve_secrets(
+        &mut self,
+        providers: &SecretProviders,
+        validator: &mut ConfigValidator,
+    ) {
+        if let Some(admin_token) = &mut self.admin_token {
+            validator.resolve("http_server.admin_token", admin_token, providers);
+        }
+    }
+
+    /// Checks the configuration values, recording every problem found in `validator`
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.bind_address(
+            "http_server.service_bind_address",
+            &self.service_bind_address,
+        );
+        if let Some(admin_token) = &self.admin_token {
+            validator.non_empty_secret("http_server.admin_token", admin_token);
+        }
     }
 }
@@ -1,25 +1,43 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Query, State},
-    http::Method,
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Method},
+    routing::{get, post},
     Json, Router,
 };
 use reqwest::StatusCode;
+use serde_json::json;
 use tokio::{net::TcpListener, sync::RwLock};
 use tower_http::cors::{Any, CorsLayer};
-use tracing::instrument;
+use tracing::{error, info, instrument, warn};
 
-use crate::engine::Answers;
+use crate::{
+    audit::{AuditEntry, AuditLog, AuditOutcome, AuditQuery},
+    engine::Answers,
+    game_state::GameState,
+    pause::{PauseControl, PauseStatus},
+    payout::{DecidedBy, PayoutError, PayoutQueue, PendingPayout},
+    reload::{ConfigReloader, ReloadError, ReloadOutcome},
+};
 
 use super::{
-    types::{GuessQuery, GuessResponse},
+    types::{GuessQuery, GuessResponse, PauseRequest, PayoutDecisionRequest},
     HttpServerConfig, HttpServerError,
 };
 
 const GET_GUESS_RESPONSE_PATH: &str = "/get_guess_response";
+const GAME_STATE_PATH: &str = "/game/state";
 const HEALTH_PATH: &str = "/health";
+const ADMIN_PAYOUTS_PATH: &str = "/admin/payouts";
+const ADMIN_PAYOUT_DECISION_PATH: &str = "/admin/payouts/{id}/decision";
+const ADMIN_AUDIT_PATH: &str = "/admin/audit";
+const ADMIN_PAUSE_PATH: &str = "/admin/pause";
+const ADMIN_RESUME_PATH: &str = "/admin/resume";
+const ADMIN_CONFIG_RELOAD_PATH: &str = "/admin/config/reload";
+/// The actor recorded in the audit log for the actions taken through the admin endpoints
+const OPERATOR_AUDIT_ACTOR: &str = "operator";
+const BEARER_PREFIX: &str = "Bearer ";
 const WAIT_BETWEEN_GUESS_RESPONSE_CHECKS_MS: u64 = 10;
 const GUESS_RESPONSE_TIMEOUT_SEC: u64 = 15;
 
@@ -27,6 +45,24 @@ const GUESS_RESPONSE_TIMEOUT_SEC: u64 = 15;
 pub struct HttpServerState {
     /// The answers to the guess queries.
     answers: Arc<RwLock<Answers>>,
+
+    /// The state of the game, as last read from the `guess_ai_db` object.
+    game_state: Arc<RwLock<GameState>>,
+
+    /// The payouts held for approval.
+    payouts: Arc<RwLock<PayoutQueue>>,
+
+    /// The audit log of the privileged actions of the engine.
+    audit_log: Arc<RwLock<AuditLog>>,
+
+    /// The pause/resume control plane of the engine.
+    pause: Arc<RwLock<PauseControl>>,
+
+    /// Reloads the configuration of the engine.
+    reloader: Arc<ConfigReloader>,
+
+    /// The bearer token of the admin endpoints, which are disabled if unset.
+    admin_token: Option<Arc<str>>,
 }
 
 /// Starts the HTTP server.
@@ -36,14 +72,35 @@ pub struct HttpServerState {
 ///
 /// * `config` - The configuration for the HTTP server.
 /// * `answers` - The answers to the guess queries.
+/// * `game_state` - The state of the game, kept in sync by the engine.
+/// * `payouts` - The payouts held for approval, acted upon by the engine.
+/// * `audit_log` - The audit log of the privileged actions of the engine.
+/// * `pause` - The pause/resume control plane of the engine.
+/// * `reloader` - Reloads the configuration of the engine.
 /// * `shutdown_receiver` - The receiver for the shutdown signal.
+#[allow(clippy::too_many_arguments)]
 pub async fn start_server(
     config: HttpServerConfig,
     answers: Arc<RwLock<Answers>>,
+    game_state: Arc<RwLock<GameState>>,
+    payouts: Arc<RwLock<PayoutQueue>>,
+    audit_log: Arc<RwLock<AuditLog>>,
+    pause: Arc<RwLock<PauseControl>>,
+    reloader: Arc<ConfigReloader>,
     mut shutdown_receiver: tokio::sync::watch::Receiver<bool>,
 ) -> Result<(), HttpServerError> {
     let tcp_listener = TcpListener::bind(config.service_bind_address).await?;
-    let state = HttpServerState { answers };
+    let state = HttpServerState {
+        answers,
+        game_state,
+        payouts,
+        audit_log,
+        pause,
+        reloader,
+        admin_token: config
+            .admin_token
+            .map(|admin_token| Arc::from(admin_token.expose_secret())),
+    };
     let router = create_router(state);
     let server =
         axum::serve(tcp_listener, router.into_make_service()).with_graceful_shutdown(async move {
@@ -64,14 +121,22 @@ fn create_router(state: HttpServerState) -> Router {
         .allow_headers(Any);
     Router::new()
         .route(GET_GUESS_RESPONSE_PATH, get(get_guess_response_handler))
+        .route(GAME_STATE_PATH, get(get_game_state_handler))
         .route(HEALTH_PATH, get(health))
+        .route(ADMIN_PAYOUTS_PATH, get(list_payouts_handler))
+        .route(ADMIN_PAYOUT_DECISION_PATH, post(decide_payout_handler))
+        .route(ADMIN_AUDIT_PATH, get(query_audit_log_handler))
+        .route(ADMIN_PAUSE_PATH, post(pause_handler))
+        .route(ADMIN_RESUME_PATH, post(resume_handler))
+        .route(ADMIN_CONFIG_RELOAD_PATH, post(reload_config_handler))
         .layer(cors)
         .with_state(state)
 }
 
-#[instrument(level = "info")]
-pub async fn health() -> StatusCode {
-    StatusCode::OK
+/// Handles the GET request for the health of the engine, reporting whether it is paused.
+#[instrument(level = "info", skip(state))]
+pub async fn health(State(state): State<HttpServerState>) -> Json<PauseStatus> {
+    Json(state.pause.read().await.status())
 }
 
 /// Handles the GET request for the guess response.
@@ -91,6 +156,7 @@ async fn get_guess_response_handler(
             break Ok(Json(GuessResponse {
                 correct: response.correct,
                 explanation: response.explanation.clone(),
+                similarity: response.similarity,
             }));
         }
         drop(answers);
@@ -103,3 +169,219 @@ async fn get_guess_response_handler(
         }
     }
 }
+
+/// Handles the GET request for the state of the game, as last read from the
+/// `guess_ai_db` object by the engine.
+#[instrument(level = "info", skip(state))]
+async fn get_game_state_handler(State(state): State<HttpServerState>) -> Json<GameState> {
+    Json(state.game_state.read().await.clone())
+}
+
+/// Handles the GET request for the payouts held for approval, with their evidence.
+#[instrument(level = "info", skip_all)]
+async fn list_payouts_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PendingPayout>>, StatusCode> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(
+        state.payouts.read().await.payouts().cloned().collect(),
+    ))
+}
+
+/// Handles the POST request approving or rejecting a held payout.
+/// The engine pays out approved payouts on its next iteration.
+#[instrument(level = "info", skip(state, headers))]
+async fn decide_payout_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+    Json(request): Json<PayoutDecisionRequest>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_admin(&state, &headers)?;
+    state
+        .payouts
+        .write()
+        .await
+        .decide(id, request.decision, DecidedBy::Operator)
+        .map_err(|e| match e {
+            PayoutError::PayoutNotFound(_) => StatusCode::NOT_FOUND,
+            PayoutError::AlreadyDecided(_) => StatusCode::CONFLICT,
+            PayoutError::Io(_) | PayoutError::Serde(_) => {
+                error!(
+                    target = "http_server",
+                    event = "payout-queue-error",
+                    payout_id = id,
+                    "Failed to persist the decision on held payout {id}: {e}"
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+    info!(
+        target = "http_server",
+        event = "payout-decided",
+        payout_id = id,
+        decision = ?request.decision,
+        "Operator decided on held payout {id}"
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Handles the GET request for the entries of the audit log passing the query filter,
+/// e.g. `?action=payout_decision&since=1700000000`.
+#[instrument(level = "info", skip(state, headers))]
+async fn query_audit_log_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, StatusCode> {
+    authorize_admin(&state, &headers)?;
+    let entries = state.audit_log.read().await.query(&query).map_err(|e| {
+        error!(
+            target = "http_server",
+            event = "audit-log-query-error",
+            "Failed to query the audit log: {e}"
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(entries))
+}
+
+/// Handles the POST request pausing the engine, with an optional JSON body giving the reason.
+/// The engine finishes the event in flight, then stops issuing hints and payouts until it is
+/// resumed.
+#[instrument(level = "info", skip(state, headers))]
+async fn pause_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+    request: Option<Json<PauseRequest>>,
+) -> Result<Json<PauseStatus>, StatusCode> {
+    authorize_admin(&state, &headers)?;
+    let Json(PauseRequest { reason }) = request.unwrap_or_default();
+    let mut pause = state.pause.write().await;
+    let result = pause.pause(reason.clone());
+    record_operator_action(&state, "pause", json!({ "reason": reason }), &result).await;
+    result.map_err(|e| {
+        error!(
+            target = "http_server",
+            event = "pause-error",
+            "Failed to persist the pause: {e}"
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    warn!(
+        target = "http_server",
+        event = "engine-pause-requested",
+        reason = ?reason,
+        "Operator paused the engine"
+    );
+    Ok(Json(pause.status()))
+}
+
+/// Handles the POST request resuming the engine. The engine stays paused while the game is
+/// inactive on-chain.
+#[instrument(level = "info", skip_all)]
+async fn resume_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+) -> Result<Json<PauseStatus>, StatusCode> {
+    authorize_admin(&state, &headers)?;
+    let mut pause = state.pause.write().await;
+    let result = pause.resume();
+    record_operator_action(&state, "resume", json!({}), &result).await;
+    result.map_err(|e| {
+        error!(
+            target = "http_server",
+            event = "resume-error",
+            "Failed to remove the persisted pause: {e}"
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    info!(
+        target = "http_server",
+        event = "engine-resume-requested",
+        "Operator resumed the engine"
+    );
+    Ok(Json(pause.status()))
+}
+
+/// Handles the POST request reloading the configuration file, applying its reloadable fields
+/// (the model, hint cadence, publisher credentials and log level). The reload is rejected if
+/// the configuration is invalid, or if an immutable field changed.
+#[instrument(level = "info", skip_all)]
+async fn reload_config_handler(
+    State(state): State<HttpServerState>,
+    headers: HeaderMap,
+) -> Result<Json<ReloadOutcome>, (StatusCode, String)> {
+    authorize_admin(&state, &headers).map_err(|status| (status, String::new()))?;
+    state
+        .reloader
+        .reload(OPERATOR_AUDIT_ACTOR)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            let status = match e {
+                ReloadError::InvalidConfig(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                ReloadError::ImmutableFieldsChanged(_) => StatusCode::CONFLICT,
+                ReloadError::LogFilter(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, e.to_string())
+        })
+}
+
+/// Records an action taken through the admin endpoints in the audit log
+async fn record_operator_action<T, E: std::fmt::Display>(
+    state: &HttpServerState,
+    action: &str,
+    parameters: serde_json::Value,
+    result: &Result<T, E>,
+) {
+    if let Err(e) = state.audit_log.write().await.append(
+        OPERATOR_AUDIT_ACTOR,
+        action,
+        parameters,
+        None,
+        AuditOutcome::of(result),
+    ) {
+        error!(
+            target = "http_server",
+            event = "audit-log-error",
+            action,
+            "Failed to record operator action in the audit log: {e}"
+        );
+    }
+}
+
+/// Checks that the request carries the admin bearer token
+fn authorize_admin(state: &HttpServerState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let admin_token = state
+        .admin_token
+        .as_deref()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .filter(|token| constant_time_eq(admin_token.as_bytes(), token.as_bytes()))
+        .map(|_| ())
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+/// Compares two byte strings in time independent of their content
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_lengths() {
+        assert!(constant_time_eq(b"admin-token", b"admin-token"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"admin-token", b"admin-tokeN"));
+        assert!(!constant_time_eq(b"admin-token", b"admin-token-2"));
+        assert!(!constant_time_eq(b"admin-token", b""));
+    }
+}
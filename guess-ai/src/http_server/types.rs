@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{payout::PayoutDecision, similarity::SimilarityBucket};
+
 #[derive(Deserialize, Debug)]
 pub struct GuessQuery {
     /// The guess to evaluate.
@@ -14,4 +16,20 @@ pub struct GuessResponse {
     pub correct: bool,
     /// The explanation for the guess.
     pub explanation: String,
+    /// How semantically close the guess is to the secret, bucketed so that
+    /// the secret cannot be recovered from it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<SimilarityBucket>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PayoutDecisionRequest {
+    /// Whether the held payout is approved or rejected.
+    pub decision: PayoutDecision,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PauseRequest {
+    /// Why the engine is paused, recorded with the pause.
+    pub reason: Option<String>,
 }
@@ -1,12 +1,32 @@
 pub mod atoma;
+pub mod audit;
+pub mod bindgen;
 pub mod client;
 pub mod config;
+pub mod contract;
+#[rustfmt::skip]
+pub mod contract_bindings;
 pub mod engine;
+pub mod envelope;
+pub use guess_ai_common::game_state;
+pub mod gas;
+#[cfg(feature = "confidential-compute")]
+pub mod gateway;
 pub mod generate_secret;
 pub mod http_server;
+pub mod network;
+pub mod pause;
+pub mod payout;
+pub(crate) mod persist;
+pub mod reload;
+pub mod secrets;
+pub mod similarity;
+pub mod structured_output;
 // pub mod tdx;
+pub mod tools;
 pub mod twitter;
 pub mod types;
+pub mod usage;
 
-/// The Atoma contract db module name.
-pub(crate) const GUESS_AI_MODULE_NAME: &str = "contract";
+/// The GuessAI contract module name, from the generated bindings
+pub(crate) const GUESS_AI_MODULE_NAME: &str = contract_bindings::MODULE_NAME;
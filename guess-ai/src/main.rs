@@ -3,16 +3,20 @@ use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
 use anyhow::{Error, Result};
 use clap::Parser;
 use dotenv::dotenv;
+#[cfg(feature = "confidential-compute")]
+use guess_ai::gateway::{start_gateway, GatewayConfig};
 use guess_ai::{
     atoma::AtomaSdk,
     client::SuiClientContext,
-    config::GuessAiConfig,
+    config::{ConfigValidator, GuessAiConfig, InferenceBackendConfig},
     engine::{GuessAiEngine, GuessAiEngineError},
     http_server::{start_server, HttpServerConfig},
+    network::Network,
+    reload::{ConfigReloader, LogFilterReloader},
 };
 use sui_sdk::{types::base_types::ObjectID, wallet_context::WalletContext};
 use tokio::task::JoinHandle;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use tracing_subscriber::EnvFilter;
 
 /// Command line arguments for the Guess AI
@@ -21,21 +25,82 @@ struct Args {
     /// Path to the configuration file
     #[arg(short, long)]
     config_path: String,
+
+    /// Validate the configuration, report every problem found, and exit
+    #[arg(long)]
+    check_config: bool,
+
+    /// The network to run on, whose RPC endpoint and object IDs are read from the
+    /// `networks.<network>` section of the configuration file
+    #[arg(long)]
+    network: Option<Network>,
+
+    /// Allow the payouts on mainnet, which are otherwise held
+    #[arg(long)]
+    allow_mainnet: bool,
+
+    /// Run the OpenAI-compatible confidential compute gateway instead of the game engine
+    #[cfg(feature = "confidential-compute")]
+    #[arg(long)]
+    gateway: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
-    tracing_subscriber::fmt()
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
-        .init();
+        .with_filter_reloading();
+    let log_filter = subscriber.reload_handle();
+    subscriber.init();
+    let set_log_filter: LogFilterReloader = Box::new(move |log_level| {
+        let filter = match log_level {
+            Some(log_level) => EnvFilter::try_new(log_level).map_err(|e| e.to_string())?,
+            None => EnvFilter::from_default_env(),
+        };
+        log_filter.reload(filter).map_err(|e| e.to_string())
+    });
 
     let args = Args::parse();
-    let config = GuessAiConfig::from_file_path(&args.config_path);
+    let LoadedConfig {
+        config,
+        http_server_config,
+        #[cfg(feature = "confidential-compute")]
+        gateway_config,
+    } = load_config(&args)?;
+    if args.check_config {
+        println!("Configuration {} is valid", args.config_path);
+        return Ok(());
+    }
+    if config.log_level.is_some() {
+        set_log_filter(config.log_level.as_deref()).map_err(Error::msg)?;
+    }
 
-    let atoma_sdk = AtomaSdk::new(config.atoma_api_key.clone(), config.model.clone());
-    let guess_ai_db = ObjectID::from_str(&config.guess_ai_db).unwrap();
-    let guess_ai_package_id = ObjectID::from_str(&config.guess_ai_package_id).unwrap();
+    let atoma_sdk = match &config.inference_backend {
+        InferenceBackendConfig::Atoma => AtomaSdk::new(
+            config.atoma_api_key.clone(),
+            config.model.clone(),
+            config.atoma_transport.clone(),
+        )?,
+        InferenceBackendConfig::Local {
+            base_url, api_key, ..
+        } => AtomaSdk::local(
+            base_url.clone(),
+            api_key.clone(),
+            config.model.clone(),
+            config.atoma_transport.clone(),
+        )?,
+    };
+    #[cfg(feature = "confidential-compute")]
+    if args.gateway {
+        return run_gateway(
+            gateway_config.expect("The gateway config is loaded in gateway mode"),
+            atoma_sdk,
+        )
+        .await;
+    }
+    let guess_ai_db = ObjectID::from_str(&config.guess_ai_db)?;
+    let guess_ai_package_id = ObjectID::from_str(&config.guess_ai_package_id)?;
     let request_timeout = config.request_timeout.map(|t| Duration::from_secs(t));
     let max_concurrent_requests = config.max_concurrent_requests.map(|t| t as u64);
     let wallet_context = WalletContext::new(
@@ -43,12 +108,46 @@ async fn main() -> Result<()> {
         request_timeout,
         max_concurrent_requests,
     )?;
-    let sui_client_ctx = SuiClientContext::new(guess_ai_db, guess_ai_package_id, wallet_context);
+    let mut sui_client_ctx = SuiClientContext::new(
+        guess_ai_db,
+        guess_ai_package_id,
+        wallet_context,
+        config.gas_pool.clone(),
+    );
+    let network_guard = sui_client_ctx
+        .verify_network(args.network, args.allow_mainnet)
+        .await?;
+    info!(
+        target = "guess-ai-service",
+        event = "network-verified",
+        network = ?network_guard.network(),
+        "Verified the network of the wallet"
+    );
+    if network_guard.is_mainnet_locked() {
+        warn!(
+            target = "guess-ai-service",
+            event = "mainnet-payouts-locked",
+            "Running on mainnet without --allow-mainnet, the payouts are held"
+        );
+    }
     let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
     let engine = GuessAiEngine::new(atoma_sdk, config, sui_client_ctx, shutdown_rx.clone()).await?;
+    let reloader = Arc::new(ConfigReloader::new(
+        &args.config_path,
+        args.network,
+        engine.config_updates.clone(),
+        set_log_filter,
+        Arc::clone(&engine.audit_log),
+    ));
+    tokio::spawn(Arc::clone(&reloader).watch(shutdown_rx.clone()));
     let server = start_server(
-        HttpServerConfig::from_file_path(&args.config_path),
+        http_server_config.expect("The http server config is loaded in engine mode"),
         Arc::clone(&engine.answers),
+        Arc::clone(&engine.game_state),
+        Arc::clone(&engine.payouts),
+        Arc::clone(&engine.audit_log),
+        Arc::clone(&engine.pause),
+        reloader,
         shutdown_rx.clone(),
     );
 
@@ -71,6 +170,95 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Runs the confidential compute gateway until ctrl-c is received
+#[cfg(feature = "confidential-compute")]
+async fn run_gateway(config: GatewayConfig, atoma_sdk: AtomaSdk) -> Result<()> {
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let gateway = start_gateway(config, atoma_sdk, shutdown_rx.clone());
+    let ctrl_c = trigger_shutdown_on_ctrl_c(shutdown_tx.clone(), shutdown_rx);
+    let gateway = spawn_with_shutdown(gateway, &shutdown_tx);
+    let (gateway_result, ctrl_c_result) = tokio::try_join!(gateway, ctrl_c)?;
+    gateway_result?;
+    ctrl_c_result?;
+    info!(
+        target = "guess-ai-service",
+        event = "gateway-stop",
+        message = "Gateway shut down successfully"
+    );
+    Ok(())
+}
+
+/// The configuration sections used in the selected mode, loaded and validated
+struct LoadedConfig {
+    /// The configuration of the engine
+    config: GuessAiConfig,
+
+    /// The configuration of the http server, loaded unless running the gateway
+    http_server_config: Option<HttpServerConfig>,
+
+    /// The configuration of the gateway, loaded when running the gateway
+    #[cfg(feature = "confidential-compute")]
+    gateway_config: Option<GatewayConfig>,
+}
+
+/// Loads and validates the configuration sections used in the selected mode.
+///
+/// The secrets which reference a provider are read first, unlocking the keystore if one is
+/// configured. Every problem found, across all the sections, is reported at once.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` listing every problem found, with the path of its field
+fn load_config(args: &Args) -> Result<LoadedConfig> {
+    let mut validator = ConfigValidator::default();
+    let (config, providers) = GuessAiConfig::load(&args.config_path, args.network, &mut validator);
+
+    #[cfg(feature = "confidential-compute")]
+    let gateway_config = if args.gateway {
+        let mut gateway_config = validator.load(
+            "gateway",
+            GatewayConfig::try_from_file_path(&args.config_path),
+        );
+        if let Some(gateway_config) = &mut gateway_config {
+            if let Some(providers) = &providers {
+                gateway_config.resolve_secrets(providers, &mut validator);
+            }
+            gateway_config.validate(&mut validator);
+        }
+        gateway_config
+    } else {
+        None
+    };
+    #[cfg(feature = "confidential-compute")]
+    let load_http_server_config = !args.gateway;
+    #[cfg(not(feature = "confidential-compute"))]
+    let load_http_server_config = true;
+
+    let http_server_config = if load_http_server_config {
+        let mut http_server_config = validator.load(
+            "http_server",
+            HttpServerConfig::try_from_file_path(&args.config_path),
+        );
+        if let Some(http_server_config) = &mut http_server_config {
+            if let Some(providers) = &providers {
+                http_server_config.resolve_secrets(providers, &mut validator);
+            }
+            http_server_config.validate(&mut validator);
+        }
+        http_server_config
+    } else {
+        None
+    };
+
+    validator.finish()?;
+    Ok(LoadedConfig {
+        config: config.expect("The config is loaded if it is valid"),
+        http_server_config,
+        #[cfg(feature = "confidential-compute")]
+        gateway_config,
+    })
+}
+
 /// Spawns a task that will automatically trigger shutdown if it encounters an error
 ///
 /// This helper function wraps a future in a tokio task that monitors its execution.
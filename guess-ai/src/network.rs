@@ -0,0 +1,292 @@
+use std::{fmt, path::Path, str::FromStr};
+
+use config::Config;
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::ObjectID;
+use thiserror::Error;
+
+type Result<T> = std::result::Result<T, NetworkError>;
+
+/// Chain identifier of Sui mainnet, the first bytes of its genesis checkpoint digest
+const MAINNET_CHAIN_IDENTIFIER: &str = "35834a8a";
+
+/// Chain identifier of Sui testnet
+const TESTNET_CHAIN_IDENTIFIER: &str = "4c78adac";
+
+/// A Sui network the game can be deployed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Localnet,
+    Devnet,
+    Testnet,
+    Mainnet,
+}
+
+impl Network {
+    /// The public full node RPC endpoint of the network, or the default one of a local network
+    pub fn default_rpc_url(self) -> &'static str {
+        match self {
+            Self::Localnet => "http://127.0.0.1:9000",
+            Self::Devnet => "https://fullnode.devnet.sui.io:443",
+            Self::Testnet => "https://fullnode.testnet.sui.io:443",
+            Self::Mainnet => "https://fullnode.mainnet.sui.io:443",
+        }
+    }
+
+    /// The chain identifier of the network, if stable. Devnet is regularly wiped, and every
+    /// local network has its own
+    pub fn chain_identifier(self) -> Option<&'static str> {
+        match self {
+            Self::Localnet | Self::Devnet => None,
+            Self::Testnet => Some(TESTNET_CHAIN_IDENTIFIER),
+            Self::Mainnet => Some(MAINNET_CHAIN_IDENTIFIER),
+        }
+    }
+
+    /// The network of a chain identifier, if it is one of a network with a stable identifier
+    pub fn from_chain_identifier(chain_identifier: &str) -> Option<Self> {
+        match chain_identifier {
+            TESTNET_CHAIN_IDENTIFIER => Some(Self::Testnet),
+            MAINNET_CHAIN_IDENTIFIER => Some(Self::Mainnet),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Localnet => "localnet",
+            Self::Devnet => "devnet",
+            Self::Testnet => "testnet",
+            Self::Mainnet => "mainnet",
+        })
+    }
+}
+
+impl FromStr for Network {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "localnet" => Ok(Self::Localnet),
+            "devnet" => Ok(Self::Devnet),
+            "testnet" => Ok(Self::Testnet),
+            "mainnet" => Ok(Self::Mainnet),
+            _ => Err(NetworkError::UnknownNetwork(s.to_string())),
+        }
+    }
+}
+
+/// The deployment of the game on a network, read from the `networks.<network>` section of
+/// the configuration file
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct NetworkProfile {
+    /// The RPC endpoint of the full node, the network's default one if unset
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+
+    /// Identifier of the GuessAI package
+    pub package_id: ObjectID,
+
+    /// Identifier of the GuessAI database object
+    pub db_id: ObjectID,
+
+    /// Identifier of the GuessAI manager object, only needed by the admin CLI
+    #[serde(default)]
+    pub manager_id: Option<ObjectID>,
+}
+
+impl NetworkProfile {
+    /// Loads the profile of `network` from the configuration file.
+    ///
+    /// Like the other sections, values are loaded from the file and from environment
+    /// variables prefixed with `NETWORKS__`, which take precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `config::ConfigError` if the configuration file cannot be read or parsed, or
+    /// the "networks.<network>" section is missing or doesn't match the expected structure
+    pub fn try_from_file_path<P: AsRef<Path>>(
+        config_file_path: P,
+        network: Network,
+    ) -> std::result::Result<Self, config::ConfigError> {
+        Config::builder()
+            .add_source(config::File::with_name(
+                &config_file_path.as_ref().to_string_lossy(),
+            ))
+            .add_source(
+                config::Environment::with_prefix("NETWORKS")
+                    .keep_prefix(true)
+                    .separator("__"),
+            )
+            .build()?
+            .get::<Self>(&format!("networks.{network}"))
+    }
+
+    /// The RPC endpoint of the full node on `network`
+    pub fn rpc_url(&self, network: Network) -> &str {
+        self.rpc_url
+            .as_deref()
+            .unwrap_or_else(|| network.default_rpc_url())
+    }
+}
+
+/// Guards the transactions moving funds or administering the game on mainnet, which are
+/// only sent if explicitly allowed (with `--allow-mainnet`).
+///
+/// The network is told by the chain identifier of the wallet's RPC endpoint, so that a
+/// wallet pointing at mainnet is caught whatever network was selected. Until the network is
+/// verified, the guarded transactions are refused.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NetworkGuard {
+    /// The network the wallet is connected to, once verified. `None` for a local network,
+    /// or devnet
+    verified: Option<Option<Network>>,
+
+    /// Whether the guarded transactions may be sent on mainnet
+    allow_mainnet: bool,
+}
+
+impl NetworkGuard {
+    /// Verifies that the wallet is connected to the selected network, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `selected` - The network selected with `--network`, if any
+    /// * `chain_identifier` - The chain identifier reported by the wallet's RPC endpoint
+    /// * `allow_mainnet` - Whether the guarded transactions may be sent on mainnet
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkError::NetworkMismatch` if the wallet is connected to another network
+    /// than the selected one
+    pub fn verify(
+        selected: Option<Network>,
+        chain_identifier: &str,
+        allow_mainnet: bool,
+    ) -> Result<Self> {
+        let connected = Network::from_chain_identifier(chain_identifier);
+        if let Some(selected) = selected {
+            if selected.chain_identifier() != connected.and_then(Network::chain_identifier) {
+                return Err(NetworkError::NetworkMismatch {
+                    selected,
+                    chain_identifier: chain_identifier.to_string(),
+                });
+            }
+        }
+        Ok(Self {
+            verified: Some(connected),
+            allow_mainnet,
+        })
+    }
+
+    /// The network the wallet is connected to, if verified and not a local network or devnet
+    pub fn network(&self) -> Option<Network> {
+        self.verified.flatten()
+    }
+
+    /// Whether the wallet is connected to mainnet, while the guarded transactions are not
+    /// allowed there
+    pub fn is_mainnet_locked(&self) -> bool {
+        self.network() == Some(Network::Mainnet) && !self.allow_mainnet
+    }
+
+    /// Checks that a guarded transaction may be sent
+    ///
+    /// # Arguments
+    ///
+    /// * `call` - The name of the guarded call, reported in the error
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkError` if the network was not verified, or is mainnet while the
+    /// guarded transactions are not allowed there
+    pub fn check(&self, call: &str) -> Result<()> {
+        if self.verified.is_none() {
+            return Err(NetworkError::NetworkNotVerified(call.to_string()));
+        }
+        if self.is_mainnet_locked() {
+            return Err(NetworkError::MainnetNotAllowed(call.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NetworkError {
+    #[error("Unknown network `{0}`, expected one of localnet, devnet, testnet or mainnet")]
+    UnknownNetwork(String),
+    #[error("The wallet is connected to chain {chain_identifier}, not to the selected network {selected}")]
+    NetworkMismatch {
+        selected: Network,
+        chain_identifier: String,
+    },
+    #[error("Refusing `{0}`, the network of the wallet was not verified")]
+    NetworkNotVerified(String),
+    #[error("Refusing `{0}` on mainnet, pass --allow-mainnet to allow it")]
+    MainnetNotAllowed(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_accepts_the_selected_network() {
+        let guard =
+            NetworkGuard::verify(Some(Network::Testnet), TESTNET_CHAIN_IDENTIFIER, false).unwrap();
+        assert_eq!(guard.network(), Some(Network::Testnet));
+        assert!(guard.check("set_starting_fee").is_ok());
+    }
+
+    #[test]
+    fn verify_refuses_another_network() {
+        assert!(matches!(
+            NetworkGuard::verify(Some(Network::Testnet), MAINNET_CHAIN_IDENTIFIER, true),
+            Err(NetworkError::NetworkMismatch {
+                selected: Network::Testnet,
+                ..
+            })
+        ));
+        assert!(matches!(
+            NetworkGuard::verify(Some(Network::Localnet), TESTNET_CHAIN_IDENTIFIER, false),
+            Err(NetworkError::NetworkMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_accepts_any_local_chain() {
+        let guard = NetworkGuard::verify(Some(Network::Localnet), "0badc0de", false).unwrap();
+        assert_eq!(guard.network(), None);
+        assert!(guard.check("set_starting_fee").is_ok());
+    }
+
+    #[test]
+    fn verify_without_a_selected_network_detects_mainnet() {
+        let guard = NetworkGuard::verify(None, MAINNET_CHAIN_IDENTIFIER, false).unwrap();
+        assert_eq!(guard.network(), Some(Network::Mainnet));
+        assert!(guard.is_mainnet_locked());
+        assert!(matches!(
+            guard.check("set_starting_fee"),
+            Err(NetworkError::MainnetNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn mainnet_is_allowed_explicitly() {
+        let guard =
+            NetworkGuard::verify(Some(Network::Mainnet), MAINNET_CHAIN_IDENTIFIER, true).unwrap();
+        assert!(!guard.is_mainnet_locked());
+        assert!(guard.check("set_starting_fee").is_ok());
+    }
+
+    #[test]
+    fn unverified_guard_refuses_guarded_calls() {
+        assert!(matches!(
+            NetworkGuard::default().check("set_starting_fee"),
+            Err(NetworkError::NetworkNotVerified(_))
+        ));
+    }
+}
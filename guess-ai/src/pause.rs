@@ -0,0 +1,269 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::persist::write_atomically;
+
+type Result<T> = std::result::Result<T, PauseError>;
+
+/// A pause requested by an operator, through the admin endpoint
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperatorPause {
+    /// Why the operator paused the engine, if given
+    pub reason: Option<String>,
+
+    /// When the engine was paused, in seconds since the Unix epoch
+    pub since: u64,
+}
+
+/// The engine's run state, as reported by the health endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// The engine processes the guesses
+    Running,
+    /// The engine is paused, but still finishing the event in flight
+    Draining,
+    /// The engine is paused and idle, its state persisted
+    Paused,
+}
+
+/// The pause status of the engine
+#[derive(Clone, Debug, Serialize)]
+pub struct PauseStatus {
+    /// The run state of the engine
+    pub state: RunState,
+
+    /// The pause requested by an operator, if any
+    pub operator_pause: Option<OperatorPause>,
+
+    /// Whether the engine is paused because the game is inactive on-chain
+    pub game_inactive: bool,
+}
+
+/// The pause/resume control plane of the engine.
+///
+/// The engine is paused while an operator pause is set, or while the game is inactive
+/// on-chain. Once paused, the engine finishes the event in flight, persists its cursor and
+/// stops consuming events, so that no hints or payouts are issued until it resumes, from
+/// the first unprocessed event. Operator pauses are persisted, and survive restarts.
+pub struct PauseControl {
+    /// The path of the file the operator pause is persisted to
+    path: PathBuf,
+
+    /// The pause requested by an operator, if any
+    operator_pause: Option<OperatorPause>,
+
+    /// Whether the game is inactive on-chain (only tracked if the engine pauses on it)
+    game_inactive: bool,
+
+    /// Whether the engine finished the event in flight since it was paused
+    drained: bool,
+}
+
+impl PauseControl {
+    /// Opens the pause control, restoring the operator pause persisted at `path`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PauseError` if the file exists but cannot be read or parsed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let operator_pause = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            operator_pause,
+            game_inactive: false,
+            drained: false,
+        })
+    }
+
+    /// Whether the engine is paused
+    pub fn is_paused(&self) -> bool {
+        self.operator_pause.is_some() || self.game_inactive
+    }
+
+    /// Whether the engine finished the event in flight since it was paused
+    pub fn is_drained(&self) -> bool {
+        self.drained
+    }
+
+    /// Records that the engine finished the event in flight and persisted its state
+    pub fn set_drained(&mut self) {
+        self.drained = self.is_paused();
+    }
+
+    /// The pause status of the engine
+    pub fn status(&self) -> PauseStatus {
+        let state = match (self.is_paused(), self.drained) {
+            (false, _) => RunState::Running,
+            (true, false) => RunState::Draining,
+            (true, true) => RunState::Paused,
+        };
+        PauseStatus {
+            state,
+            operator_pause: self.operator_pause.clone(),
+            game_inactive: self.game_inactive,
+        }
+    }
+
+    /// Pauses the engine on an operator's request, persisting the pause.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PauseError` if the pause cannot be persisted
+    pub fn pause(&mut self, reason: Option<String>) -> Result<()> {
+        let operator_pause = OperatorPause {
+            reason,
+            since: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+        };
+        write_atomically(&self.path, &serde_json::to_vec(&operator_pause)?)?;
+        self.operator_pause = Some(operator_pause);
+        Ok(())
+    }
+
+    /// Lifts the operator pause, if any. The engine stays paused while the game is inactive.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PauseError` if the persisted pause cannot be removed
+    pub fn resume(&mut self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.operator_pause = None;
+        if !self.is_paused() {
+            self.drained = false;
+        }
+        Ok(())
+    }
+
+    /// Records whether the game is inactive on-chain, returning whether it changed
+    pub fn set_game_inactive(&mut self, game_inactive: bool) -> bool {
+        let changed = self.game_inactive != game_inactive;
+        self.game_inactive = game_inactive;
+        if !self.is_paused() {
+            self.drained = false;
+        }
+        changed
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PauseError {
+    #[error("Failed to read/write the pause state: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to (de)serialize the pause state: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(dir: &tempfile::TempDir) -> PauseControl {
+        PauseControl::open(dir.path().join("pause.json")).unwrap()
+    }
+
+    #[test]
+    fn test_operator_pause_drains_then_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pause_control = open(&dir);
+        assert_eq!(pause_control.status().state, RunState::Running);
+        // Nothing to drain while running
+        pause_control.set_drained();
+        assert!(!pause_control.is_drained());
+
+        pause_control
+            .pause(Some("maintenance".to_string()))
+            .unwrap();
+        assert!(pause_control.is_paused());
+        assert_eq!(pause_control.status().state, RunState::Draining);
+        pause_control.set_drained();
+        assert_eq!(pause_control.status().state, RunState::Paused);
+
+        pause_control.resume().unwrap();
+        assert!(!pause_control.is_paused());
+        assert!(!pause_control.is_drained());
+        assert_eq!(pause_control.status().state, RunState::Running);
+    }
+
+    #[test]
+    fn test_operator_pause_survives_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        open(&dir).pause(Some("maintenance".to_string())).unwrap();
+
+        let mut pause_control = open(&dir);
+        let status = pause_control.status();
+        assert_eq!(status.state, RunState::Draining);
+        assert_eq!(
+            status.operator_pause.and_then(|pause| pause.reason),
+            Some("maintenance".to_string())
+        );
+
+        pause_control.resume().unwrap();
+        assert!(!dir.path().join("pause.json").exists());
+        assert!(!open(&dir).is_paused());
+        // Resuming an engine that is not paused is a no-op
+        pause_control.resume().unwrap();
+    }
+
+    #[test]
+    fn test_game_inactive_pauses_until_active() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pause_control = open(&dir);
+
+        assert!(pause_control.set_game_inactive(true));
+        assert!(!pause_control.set_game_inactive(true));
+        assert!(pause_control.status().game_inactive);
+        pause_control.set_drained();
+        assert_eq!(pause_control.status().state, RunState::Paused);
+
+        assert!(pause_control.set_game_inactive(false));
+        assert_eq!(pause_control.status().state, RunState::Running);
+        assert!(!pause_control.is_drained());
+    }
+
+    #[test]
+    fn test_operator_resume_while_game_inactive_stays_paused() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pause_control = open(&dir);
+        pause_control.pause(None).unwrap();
+        pause_control.set_game_inactive(true);
+        pause_control.set_drained();
+
+        pause_control.resume().unwrap();
+        let status = pause_control.status();
+        assert_eq!(status.state, RunState::Paused);
+        assert!(status.operator_pause.is_none());
+        assert!(status.game_inactive);
+        assert!(!dir.path().join("pause.json").exists());
+
+        pause_control.set_game_inactive(false);
+        assert_eq!(pause_control.status().state, RunState::Running);
+    }
+
+    #[test]
+    fn test_malformed_pause_state_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("pause.json"), "not json").unwrap();
+
+        let result = PauseControl::open(dir.path().join("pause.json"));
+        assert!(matches!(result, Err(PauseError::Serde(_))));
+    }
+}
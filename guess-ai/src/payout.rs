@@ -0,0 +1,568 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::engine::{general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sui_sdk::types::base_types::SuiAddress;
+use thiserror::Error;
+
+use crate::{config::PayoutPolicyConfig, persist::write_atomically};
+
+/// The size of the salt of the secret commitment, in bytes
+const SECRET_COMMITMENT_SALT_SIZE: usize = 32;
+
+/// The decision taken on a held payout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutDecision {
+    /// The winner is paid out, and a new game starts
+    Approve,
+    /// The winner is not paid out, and the game goes on
+    Reject,
+}
+
+/// Who took the decision on a held payout
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecidedBy {
+    /// An operator, through the admin endpoint
+    Operator,
+    /// The second automated judge
+    SecondJudge,
+    /// Nobody decided before the deadline, and the default action applied
+    Timeout,
+}
+
+impl DecidedBy {
+    /// The name of the decider, as serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Operator => "operator",
+            Self::SecondJudge => "second_judge",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// The verdict of a judge on a guess
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JudgeVote {
+    /// The model which judged the guess
+    pub model: String,
+
+    /// Whether the model judged the guess correct
+    pub is_correct: bool,
+
+    /// The explanation of the model
+    pub explanation: String,
+}
+
+/// A payout held until it is approved or rejected, with the evidence supporting it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingPayout {
+    /// The ID of the payout
+    pub id: u64,
+
+    /// The game the guess was made in
+    pub guess_game_id: u64,
+
+    /// The index of the guess in the game
+    pub guess_count: u64,
+
+    /// The player who made the guess, and would receive the payout
+    pub winner: SuiAddress,
+
+    /// The guess judged correct
+    pub guess: String,
+
+    /// The balance of the treasury pool when the guess was made, in MIST
+    pub treasury_pool_balance: u64,
+
+    /// The base64 encoded SHA-256 commitment to the secret the guess was judged against,
+    /// so that the secret can be checked once disclosed, without revealing it now
+    pub secret_commitment: String,
+
+    /// The base64 encoded salt of the secret commitment, kept private while the game may
+    /// go on, and only logged once the payout is approved
+    #[serde(skip)]
+    pub secret_commitment_salt: String,
+
+    /// The verdicts of the judges, the first one being the judge of the game
+    pub votes: Vec<JudgeVote>,
+
+    /// When the payout was held, in seconds since the Unix epoch
+    pub created_at: u64,
+
+    /// When the default action applies, in seconds since the Unix epoch
+    pub deadline: u64,
+
+    /// The decision taken on the payout, if any
+    pub decision: Option<(PayoutDecision, DecidedBy)>,
+}
+
+/// A held payout as persisted, with the salt of its secret commitment
+#[derive(Serialize, Deserialize)]
+struct PersistedPayout {
+    #[serde(flatten)]
+    payout: PendingPayout,
+
+    /// The base64 encoded salt of the secret commitment
+    secret_commitment_salt: String,
+}
+
+/// The state of the payout queue, as persisted
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedQueue {
+    /// The ID of the next held payout
+    next_id: u64,
+
+    /// The held payouts
+    payouts: Vec<PersistedPayout>,
+}
+
+/// The payouts held for approval.
+///
+/// The queue is persisted on every change, so that a winner's claim (and the salt of the
+/// commitment to the secret it was judged against) survives restarts.
+pub struct PayoutQueue {
+    /// The payout policy
+    config: PayoutPolicyConfig,
+
+    /// The path of the file the queue is persisted to
+    path: PathBuf,
+
+    /// The ID of the next held payout
+    next_id: u64,
+
+    /// The held payouts, by ID
+    payouts: BTreeMap<u64, PendingPayout>,
+}
+
+impl PayoutQueue {
+    /// Opens the payout queue, restoring the payouts persisted at `path`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PayoutError` if the file exists but cannot be read or parsed
+    pub fn open<P: AsRef<Path>>(config: PayoutPolicyConfig, path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let persisted: PersistedQueue = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => PersistedQueue::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let payouts = persisted
+            .payouts
+            .into_iter()
+            .map(|persisted| {
+                let mut payout = persisted.payout;
+                payout.secret_commitment_salt = persisted.secret_commitment_salt;
+                (payout.id, payout)
+            })
+            .collect();
+        Ok(Self {
+            config,
+            path,
+            next_id: persisted.next_id,
+            payouts,
+        })
+    }
+
+    /// Applies `change` to the queue and persists it, rolling the change back if the queue
+    /// cannot be persisted
+    fn transact<T>(&mut self, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let (next_id, payouts) = (self.next_id, self.payouts.clone());
+        let result = change(self).and_then(|value| self.persist().map(|()| value));
+        if result.is_err() {
+            self.next_id = next_id;
+            self.payouts = payouts;
+        }
+        result
+    }
+
+    /// Persists the queue
+    fn persist(&self) -> Result<()> {
+        let persisted = PersistedQueue {
+            next_id: self.next_id,
+            payouts: self
+                .payouts
+                .values()
+                .map(|payout| PersistedPayout {
+                    payout: payout.clone(),
+                    secret_commitment_salt: payout.secret_commitment_salt.clone(),
+                })
+                .collect(),
+        };
+        write_atomically(&self.path, &serde_json::to_vec(&persisted)?)?;
+        Ok(())
+    }
+
+    /// Whether a payout of the given treasury pool balance must be held for approval
+    pub fn requires_approval(&self, treasury_pool_balance: u64) -> bool {
+        self.config
+            .approval_threshold
+            .is_some_and(|threshold| treasury_pool_balance >= threshold)
+    }
+
+    /// The model of the second automated judge, if any
+    pub fn second_judge_model(&self) -> Option<&str> {
+        self.config.second_judge_model.as_deref()
+    }
+
+    /// Holds a payout for approval.
+    ///
+    /// # Arguments
+    ///
+    /// * `guess_game_id` - The game the guess was made in
+    /// * `guess_count` - The index of the guess in the game
+    /// * `winner` - The player who made the guess
+    /// * `guess` - The guess judged correct
+    /// * `treasury_pool_balance` - The balance of the treasury pool, in MIST
+    /// * `secret` - The secret the guess was judged against, only kept as a commitment
+    /// * `vote` - The verdict of the judge of the game
+    ///
+    /// # Returns
+    ///
+    /// Returns the ID of the held payout
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PayoutError` if the queue cannot be persisted, in which case the payout is
+    /// not held
+    #[allow(clippy::too_many_arguments)]
+    pub fn hold(
+        &mut self,
+        guess_game_id: u64,
+        guess_count: u64,
+        winner: SuiAddress,
+        guess: String,
+        treasury_pool_balance: u64,
+        secret: &str,
+        vote: JudgeVote,
+    ) -> Result<u64> {
+        let mut salt = vec![0u8; SECRET_COMMITMENT_SALT_SIZE];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let secret_commitment = STANDARD.encode(
+            Sha256::new()
+                .chain_update(&salt)
+                .chain_update(secret.as_bytes())
+                .finalize(),
+        );
+        let created_at = now();
+        let deadline = created_at + self.config.approval_timeout;
+        self.transact(|queue| {
+            let id = queue.next_id;
+            queue.next_id += 1;
+            queue.payouts.insert(
+                id,
+                PendingPayout {
+                    id,
+                    guess_game_id,
+                    guess_count,
+                    winner,
+                    guess,
+                    treasury_pool_balance,
+                    secret_commitment,
+                    secret_commitment_salt: STANDARD.encode(&salt),
+                    votes: vec![vote],
+                    created_at,
+                    deadline,
+                    decision: None,
+                },
+            );
+            Ok(id)
+        })
+    }
+
+    /// Records the verdict of an additional judge on a held payout
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PayoutError` if there is no such payout, it is already decided, or the
+    /// queue cannot be persisted
+    pub fn add_vote(&mut self, id: u64, vote: JudgeVote) -> Result<()> {
+        self.transact(|queue| {
+            queue.get_undecided(id)?.votes.push(vote);
+            Ok(())
+        })
+    }
+
+    /// Decides on a held payout. The decision is acted upon by the engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PayoutError` if there is no such payout, it is already decided, or the
+    /// queue cannot be persisted
+    pub fn decide(
+        &mut self,
+        id: u64,
+        decision: PayoutDecision,
+        decided_by: DecidedBy,
+    ) -> Result<()> {
+        self.transact(|queue| {
+            queue.get_undecided(id)?.decision = Some((decision, decided_by));
+            Ok(())
+        })
+    }
+
+    /// The held payouts
+    pub fn payouts(&self) -> impl Iterator<Item = &PendingPayout> {
+        self.payouts.values()
+    }
+
+    /// Removes and returns the payouts which are decided, or whose deadline passed,
+    /// applying the default action to the latter.
+    ///
+    /// Approved payouts are only removed if `payouts_allowed`, otherwise they stay held (with
+    /// their decision) until payouts are allowed again. Rejected payouts are always removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PayoutError` if the queue cannot be persisted, in which case no payout is
+    /// removed
+    pub fn take_resolved(
+        &mut self,
+        payouts_allowed: bool,
+    ) -> Result<Vec<(PendingPayout, PayoutDecision)>> {
+        let now = now();
+        let timeout_action = self.config.timeout_action;
+        let changed = self.payouts.values().any(|payout| {
+            (payout.decision.is_none() && payout.deadline <= now)
+                || is_resolved(payout, payouts_allowed)
+        });
+        if !changed {
+            return Ok(Vec::new());
+        }
+        self.transact(|queue| {
+            for payout in queue.payouts.values_mut() {
+                if payout.decision.is_none() && payout.deadline <= now {
+                    payout.decision = Some((timeout_action, DecidedBy::Timeout));
+                }
+            }
+            let resolved = queue
+                .payouts
+                .values()
+                .filter(|payout| is_resolved(payout, payouts_allowed))
+                .map(|payout| payout.id)
+                .collect::<Vec<_>>();
+            Ok(resolved
+                .into_iter()
+                .filter_map(|id| queue.payouts.remove(&id))
+                .filter_map(|payout| {
+                    let (decision, _) = payout.decision.clone()?;
+                    Some((payout, decision))
+                })
+                .collect())
+        })
+    }
+
+    /// Returns the held payout with the given ID, if it is not decided yet
+    fn get_undecided(&mut self, id: u64) -> Result<&mut PendingPayout> {
+        let payout = self
+            .payouts
+            .get_mut(&id)
+            .ok_or(PayoutError::PayoutNotFound(id))?;
+        if payout.decision.is_some() {
+            return Err(PayoutError::AlreadyDecided(id));
+        }
+        Ok(payout)
+    }
+}
+
+/// Whether a held payout is decided, and can be acted upon: approved payouts wait until
+/// payouts are allowed
+fn is_resolved(payout: &PendingPayout, payouts_allowed: bool) -> bool {
+    match &payout.decision {
+        Some((PayoutDecision::Approve, _)) => payouts_allowed,
+        Some((PayoutDecision::Reject, _)) => true,
+        None => false,
+    }
+}
+
+/// The current time, in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+type Result<T> = std::result::Result<T, PayoutError>;
+
+#[derive(Debug, Error)]
+pub enum PayoutError {
+    #[error("Payout {0} not found")]
+    PayoutNotFound(u64),
+    #[error("Payout {0} is already decided")]
+    AlreadyDecided(u64),
+    #[error("Failed to read/write the payout queue: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to (de)serialize the payout queue: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote() -> JudgeVote {
+        JudgeVote {
+            model: "judge".to_string(),
+            is_correct: true,
+            explanation: "The guess is the secret".to_string(),
+        }
+    }
+
+    fn policy(approval_timeout: u64, timeout_action: PayoutDecision) -> PayoutPolicyConfig {
+        PayoutPolicyConfig {
+            approval_threshold: Some(100),
+            approval_timeout,
+            timeout_action,
+            second_judge_model: None,
+        }
+    }
+
+    fn hold(queue: &mut PayoutQueue) -> u64 {
+        queue
+            .hold(
+                1,
+                2,
+                SuiAddress::ZERO,
+                "guess".to_string(),
+                1_000,
+                "secret",
+                vote(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn hold_is_persisted_and_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("payouts.json");
+        let config = policy(3600, PayoutDecision::Reject);
+        let mut queue = PayoutQueue::open(config.clone(), &path).unwrap();
+        let id = hold(&mut queue);
+        let salt = queue
+            .payouts()
+            .next()
+            .unwrap()
+            .secret_commitment_salt
+            .clone();
+        assert!(!salt.is_empty());
+
+        let restored = PayoutQueue::open(config, &path).unwrap();
+        let payout = restored.payouts().next().unwrap();
+        assert_eq!(payout.id, id);
+        assert_eq!(payout.secret_commitment_salt, salt);
+        assert_eq!(payout.votes.len(), 1);
+        assert_eq!(restored.next_id, id + 1);
+    }
+
+    #[test]
+    fn decide_rejects_unknown_and_decided_payouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue =
+            PayoutQueue::open(policy(3600, PayoutDecision::Reject), dir.path().join("q")).unwrap();
+        let id = hold(&mut queue);
+        assert!(matches!(
+            queue.decide(id + 1, PayoutDecision::Approve, DecidedBy::Operator),
+            Err(PayoutError::PayoutNotFound(_))
+        ));
+        queue
+            .decide(id, PayoutDecision::Approve, DecidedBy::Operator)
+            .unwrap();
+        assert!(matches!(
+            queue.decide(id, PayoutDecision::Reject, DecidedBy::SecondJudge),
+            Err(PayoutError::AlreadyDecided(_))
+        ));
+        assert!(matches!(
+            queue.add_vote(id, vote()),
+            Err(PayoutError::AlreadyDecided(_))
+        ));
+    }
+
+    #[test]
+    fn take_resolved_keeps_undecided_and_approved_payouts_while_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("payouts.json");
+        let config = policy(3600, PayoutDecision::Reject);
+        let mut queue = PayoutQueue::open(config.clone(), &path).unwrap();
+        let approved = hold(&mut queue);
+        let rejected = hold(&mut queue);
+        let undecided = hold(&mut queue);
+        queue
+            .decide(approved, PayoutDecision::Approve, DecidedBy::Operator)
+            .unwrap();
+        queue
+            .decide(rejected, PayoutDecision::Reject, DecidedBy::SecondJudge)
+            .unwrap();
+
+        let resolved = queue.take_resolved(false).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].0.id, rejected);
+        assert_eq!(resolved[0].1, PayoutDecision::Reject);
+
+        let resolved = queue.take_resolved(true).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].0.id, approved);
+        assert_eq!(resolved[0].1, PayoutDecision::Approve);
+
+        let restored = PayoutQueue::open(config, &path).unwrap();
+        let ids = restored
+            .payouts()
+            .map(|payout| payout.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![undecided]);
+    }
+
+    #[test]
+    fn take_resolved_applies_the_timeout_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue =
+            PayoutQueue::open(policy(0, PayoutDecision::Approve), dir.path().join("q")).unwrap();
+        let id = hold(&mut queue);
+
+        // The timed out payout is approved, but stays held while payouts are locked
+        assert!(queue.take_resolved(false).unwrap().is_empty());
+        assert_eq!(
+            queue.payouts().next().unwrap().decision,
+            Some((PayoutDecision::Approve, DecidedBy::Timeout))
+        );
+
+        let resolved = queue.take_resolved(true).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].0.id, id);
+        assert_eq!(resolved[0].1, PayoutDecision::Approve);
+    }
+
+    #[test]
+    fn changes_are_rolled_back_if_they_cannot_be_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = PayoutQueue::open(
+            policy(3600, PayoutDecision::Reject),
+            dir.path().join("missing").join("payouts.json"),
+        )
+        .unwrap();
+        assert!(queue
+            .hold(
+                1,
+                2,
+                SuiAddress::ZERO,
+                "guess".to_string(),
+                1_000,
+                "secret",
+                vote()
+            )
+            .is_err());
+        assert_eq!(queue.payouts().count(), 0);
+        assert_eq!(queue.next_id, 0);
+    }
+}
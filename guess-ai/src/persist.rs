@@ -0,0 +1,62 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+/// Writes `contents` to `path` atomically: the contents are written to a temporary file
+/// next to it, flushed to disk, and renamed over `path`. A crash mid-write leaves either
+/// the previous file or the new one, never a truncated one.
+///
+/// # Errors
+///
+/// Returns an `io::Error` if the temporary file cannot be written or renamed
+pub(crate) fn write_atomically<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    write_through_tmp(path.as_ref(), contents, |tmp_path| File::create(tmp_path))
+}
+
+/// Writes `contents` to `path` atomically, like [`write_atomically`], in a file readable by
+/// its owner only.
+///
+/// The permissions are set on the temporary file before anything is written to it, whether
+/// it is created or left over by an earlier write, so the contents are never readable by
+/// others, and the file renamed over `path` replaces its permissions too.
+///
+/// # Errors
+///
+/// Returns an `io::Error` if the temporary file cannot be written or renamed
+pub(crate) fn write_atomically_owner_only<P: AsRef<Path>>(
+    path: P,
+    contents: &[u8],
+) -> io::Result<()> {
+    write_through_tmp(path.as_ref(), contents, |tmp_path| {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            let file = options.open(tmp_path)?;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            Ok(file)
+        }
+        #[cfg(not(unix))]
+        options.open(tmp_path)
+    })
+}
+
+/// Writes `contents` to the temporary file opened by `open`, flushes it to disk and renames
+/// it over `path`
+fn write_through_tmp(
+    path: &Path,
+    contents: &[u8],
+    open: impl FnOnce(&Path) -> io::Result<File>,
+) -> io::Result<()> {
+    let mut tmp_path = OsString::from(path.as_os_str());
+    tmp_path.push(".tmp");
+    let mut file = open(Path::new(&tmp_path))?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
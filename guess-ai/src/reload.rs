@@ -0,0 +1,455 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+use tokio::sync::{watch, Mutex, RwLock};
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    audit::{AuditLog, AuditOutcome},
+    config::{ConfigError, ConfigValidator, GuessAiConfig},
+    network::Network,
+};
+
+type Result<T> = std::result::Result<T, ReloadError>;
+
+/// The interval at which the configuration file is checked for changes
+const CONFIG_WATCH_INTERVAL_SECS: u64 = 2;
+
+/// The actor recorded in the audit log for the reloads triggered by a change of the file
+pub const CONFIG_WATCHER_AUDIT_ACTOR: &str = "config-watcher";
+
+/// Applies log filter directives, or restores the `RUST_LOG` ones if `None`
+pub type LogFilterReloader =
+    Box<dyn Fn(Option<&str>) -> std::result::Result<(), String> + Send + Sync>;
+
+/// The fields changed by a reload, all of them reloadable
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReloadOutcome {
+    /// The paths of the changed fields, e.g. `guess_ai.model`
+    pub changed: Vec<&'static str>,
+}
+
+/// Reloads the configuration while the engine runs, when its file changes or on an
+/// operator's request.
+///
+/// Only the model, the hint cadence, the publisher (Twitter) credentials and the log level
+/// are applied live. A reload changing any other field is rejected as a whole, as those
+/// fields (e.g. the package ID or the cursor path) are only read at startup.
+pub struct ConfigReloader {
+    /// Path of the configuration file
+    path: PathBuf,
+
+    /// The network selected at startup, whose profile is applied to the reloaded configuration
+    network: Option<Network>,
+
+    /// The channel the engine receives the reloaded configuration from, holding the
+    /// configuration currently applied
+    updates: watch::Sender<GuessAiConfig>,
+
+    /// Applies the log level
+    set_log_filter: LogFilterReloader,
+
+    /// The audit log the reloads are recorded in
+    audit_log: Arc<RwLock<AuditLog>>,
+
+    /// Serializes the reloads, which may be triggered concurrently
+    reloading: Mutex<()>,
+}
+
+impl ConfigReloader {
+    /// Constructor
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the configuration file
+    /// * `network` - The network selected at startup, if any
+    /// * `updates` - The channel the engine receives the reloaded configuration from
+    /// * `set_log_filter` - Applies the log level
+    /// * `audit_log` - The audit log the reloads are recorded in
+    pub fn new(
+        path: impl Into<PathBuf>,
+        network: Option<Network>,
+        updates: watch::Sender<GuessAiConfig>,
+        set_log_filter: LogFilterReloader,
+        audit_log: Arc<RwLock<AuditLog>>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            network,
+            updates,
+            set_log_filter,
+            audit_log,
+            reloading: Mutex::new(()),
+        }
+    }
+
+    /// Reloads the configuration file, and applies its reloadable fields.
+    ///
+    /// The reload is recorded in the audit log, whether it succeeds or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who requested the reload, recorded in the audit log
+    ///
+    /// # Returns
+    ///
+    /// The reloadable fields which changed
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ReloadError` if the configuration is invalid, or an immutable field changed
+    #[instrument(level = "info", skip(self))]
+    pub async fn reload(&self, actor: &str) -> Result<ReloadOutcome> {
+        let _reloading = self.reloading.lock().await;
+        let result = self.try_reload();
+        match &result {
+            Ok(outcome) if outcome.changed.is_empty() => {}
+            Ok(outcome) => info!(
+                target = "config_reloader",
+                event = "config-reloaded",
+                changed = ?outcome.changed,
+                "Reloaded the configuration"
+            ),
+            Err(e) => error!(
+                target = "config_reloader",
+                event = "config-reload-rejected",
+                "Rejected the configuration reload, the running configuration is kept: {e}"
+            ),
+        }
+        let parameters = match &result {
+            Ok(outcome) => json!({ "changed": outcome.changed }),
+            Err(ReloadError::ImmutableFieldsChanged(fields)) => json!({ "rejected": fields }),
+            Err(_) => json!({}),
+        };
+        if let Err(e) = self.audit_log.write().await.append(
+            actor,
+            "config_reload",
+            parameters,
+            None,
+            AuditOutcome::of(&result),
+        ) {
+            error!(
+                target = "config_reloader",
+                event = "audit-log-error",
+                "Failed to record the configuration reload in the audit log: {e}"
+            );
+        }
+        result
+    }
+
+    fn try_reload(&self) -> Result<ReloadOutcome> {
+        let mut validator = ConfigValidator::default();
+        let (config, _) = GuessAiConfig::load(&self.path, self.network, &mut validator);
+        validator.finish()?;
+        let config = config.expect("The config is loaded if it is valid");
+
+        let current = self.updates.borrow().clone();
+        let immutable = immutable_changes(&current, &config);
+        if !immutable.is_empty() {
+            return Err(ReloadError::ImmutableFieldsChanged(immutable));
+        }
+        let outcome = ReloadOutcome {
+            changed: reloadable_changes(&current, &config),
+        };
+        if current.log_level != config.log_level {
+            (self.set_log_filter)(config.log_level.as_deref()).map_err(ReloadError::LogFilter)?;
+        }
+        if !outcome.changed.is_empty() {
+            self.updates.send_replace(config);
+        }
+        Ok(outcome)
+    }
+
+    /// Reloads the configuration whenever its file is modified, until shutdown.
+    ///
+    /// The file is polled rather than watched, so that editors replacing it (rather than
+    /// writing it in place) and bind-mounted files are handled alike.
+    pub async fn watch(self: Arc<Self>, mut shutdown_signal: watch::Receiver<bool>) {
+        let mut modified = self.modified().ok();
+        let mut interval = tokio::time::interval(Duration::from_secs(CONFIG_WATCH_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let last_modified = match self.modified() {
+                        Ok(last_modified) => Some(last_modified),
+                        Err(e) => {
+                            // Only logged once, until the file can be read again
+                            if modified.is_some() {
+                                warn!(
+                                    target = "config_reloader",
+                                    event = "config-file-unreadable",
+                                    path = %self.path.display(),
+                                    "Failed to read the modification time of the configuration file: {e}"
+                                );
+                            }
+                            modified = None;
+                            continue;
+                        }
+                    };
+                    if last_modified == modified {
+                        continue;
+                    }
+                    modified = last_modified;
+                    info!(
+                        target = "config_reloader",
+                        event = "config-file-changed",
+                        path = %self.path.display(),
+                        "Configuration file changed, reloading it"
+                    );
+                    // Failures are logged and audited by `reload`
+                    let _ = self.reload(CONFIG_WATCHER_AUDIT_ACTOR).await;
+                }
+                _ = shutdown_signal.changed() => break,
+            }
+        }
+    }
+
+    /// The last modification time of the configuration file
+    fn modified(&self) -> std::io::Result<SystemTime> {
+        fs::metadata(&self.path).and_then(|metadata| metadata.modified())
+    }
+}
+
+/// The reloadable fields changed in `new`
+fn reloadable_changes(current: &GuessAiConfig, new: &GuessAiConfig) -> Vec<&'static str> {
+    [
+        ("guess_ai.model", current.model != new.model),
+        (
+            "guess_ai.hint_wait_count",
+            current.hint_wait_count != new.hint_wait_count,
+        ),
+        (
+            "guess_ai.twitter_consumer_key",
+            current.twitter_consumer_key != new.twitter_consumer_key,
+        ),
+        (
+            "guess_ai.twitter_consumer_secret",
+            current.twitter_consumer_secret != new.twitter_consumer_secret,
+        ),
+        (
+            "guess_ai.twitter_access_token",
+            current.twitter_access_token != new.twitter_access_token,
+        ),
+        (
+            "guess_ai.twitter_access_token_secret",
+            current.twitter_access_token_secret != new.twitter_access_token_secret,
+        ),
+        ("guess_ai.log_level", current.log_level != new.log_level),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+/// The immutable fields changed in `new`.
+///
+/// The configuration is destructured exhaustively, so that a new field must be classified
+/// as reloadable or immutable.
+fn immutable_changes(current: &GuessAiConfig, new: &GuessAiConfig) -> Vec<&'static str> {
+    let GuessAiConfig {
+        // Reloadable, see `reloadable_changes`
+        model: _,
+        hint_wait_count: _,
+        twitter_consumer_key: _,
+        twitter_consumer_secret: _,
+        twitter_access_token: _,
+        twitter_access_token_secret: _,
+        log_level: _,
+        // Immutable
+        atoma_api_key,
+        keystore,
+        cursor_path,
+        http_rpc_node_addr,
+        limit,
+        guess_ai_package_id,
+        guess_ai_db,
+        request_timeout,
+        max_concurrent_requests,
+        sui_config_path,
+        usage_path,
+        audit_log_path,
+        pause_state_path,
+        payout_state_path,
+        pause_when_game_inactive,
+        inference_backend,
+        atoma_transport,
+        compute_budget,
+        similarity,
+        gas_pool,
+        payout_policy,
+    } = new;
+    [
+        (
+            "guess_ai.atoma_api_key",
+            current.atoma_api_key != *atoma_api_key,
+        ),
+        ("guess_ai.keystore", current.keystore != *keystore),
+        ("guess_ai.cursor_path", current.cursor_path != *cursor_path),
+        (
+            "guess_ai.http_rpc_node_addr",
+            current.http_rpc_node_addr != *http_rpc_node_addr,
+        ),
+        ("guess_ai.limit", current.limit != *limit),
+        (
+            "guess_ai.guess_ai_package_id",
+            current.guess_ai_package_id != *guess_ai_package_id,
+        ),
+        ("guess_ai.guess_ai_db", current.guess_ai_db != *guess_ai_db),
+        (
+            "guess_ai.request_timeout",
+            current.request_timeout != *request_timeout,
+        ),
+        (
+            "guess_ai.max_concurrent_requests",
+            current.max_concurrent_requests != *max_concurrent_requests,
+        ),
+        (
+            "guess_ai.sui_config_path",
+            current.sui_config_path != *sui_config_path,
+        ),
+        ("guess_ai.usage_path", current.usage_path != *usage_path),
+        (
+            "guess_ai.audit_log_path",
+            current.audit_log_path != *audit_log_path,
+        ),
+        (
+            "guess_ai.pause_state_path",
+            current.pause_state_path != *pause_state_path,
+        ),
+        (
+            "guess_ai.payout_state_path",
+            current.payout_state_path != *payout_state_path,
+        ),
+        (
+            "guess_ai.pause_when_game_inactive",
+            current.pause_when_game_inactive != *pause_when_game_inactive,
+        ),
+        (
+            "guess_ai.inference_backend",
+            current.inference_backend != *inference_backend,
+        ),
+        (
+            "guess_ai.atoma_transport",
+            current.atoma_transport != *atoma_transport,
+        ),
+        (
+            "guess_ai.compute_budget",
+            current.compute_budget != *compute_budget,
+        ),
+        ("guess_ai.similarity", current.similarity != *similarity),
+        ("guess_ai.gas_pool", current.gas_pool != *gas_pool),
+        (
+            "guess_ai.payout_policy",
+            current.payout_policy != *payout_policy,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum ReloadError {
+    #[error("{0}")]
+    InvalidConfig(#[from] ConfigError),
+    #[error("Immutable fields changed, restart the engine to apply them: {}", .0.join(", "))]
+    ImmutableFieldsChanged(Vec<&'static str>),
+    #[error("Failed to apply the log level: {0}")]
+    LogFilter(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::Secret;
+
+    fn config() -> GuessAiConfig {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                [guess_ai]
+                atoma_api_key = "atoma-key"
+                twitter_consumer_key = "consumer-key"
+                twitter_consumer_secret = "consumer-secret"
+                twitter_access_token = "access-token"
+                twitter_access_token_secret = "access-token-secret"
+                cursor_path = "./cursor.toml"
+                hint_wait_count = 5
+                model = "deepseek-ai/DeepSeek-V3"
+                guess_ai_package_id = "0x1"
+                guess_ai_db = "0x2"
+                sui_config_path = "./client.yaml"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .get("guess_ai")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_config() {
+        let current = config();
+        assert!(reloadable_changes(&current, &config()).is_empty());
+        assert!(immutable_changes(&current, &config()).is_empty());
+    }
+
+    #[test]
+    fn test_reloadable_changes_are_accepted() {
+        let current = config();
+        let mut new = config();
+        new.model = "meta-llama/Llama-3.3-70B-Instruct".to_string();
+        new.hint_wait_count = 10;
+
+        assert_eq!(
+            reloadable_changes(&current, &new),
+            ["guess_ai.model", "guess_ai.hint_wait_count"]
+        );
+        assert!(immutable_changes(&current, &new).is_empty());
+    }
+
+    #[test]
+    fn test_immutable_changes_are_rejected_with_their_path() {
+        let current = config();
+        let mut new = config();
+        new.guess_ai_package_id = "0x3".to_string();
+        new.cursor_path = "./other-cursor.toml".to_string();
+        new.model = "meta-llama/Llama-3.3-70B-Instruct".to_string();
+
+        let immutable = immutable_changes(&current, &new);
+        assert_eq!(
+            immutable,
+            ["guess_ai.cursor_path", "guess_ai.guess_ai_package_id"]
+        );
+        assert_eq!(
+            ReloadError::ImmutableFieldsChanged(immutable).to_string(),
+            "Immutable fields changed, restart the engine to apply them: \
+             guess_ai.cursor_path, guess_ai.guess_ai_package_id"
+        );
+    }
+
+    #[test]
+    fn test_secret_changes_are_reloadable_only_for_twitter() {
+        let current = config();
+        let mut new = config();
+        new.twitter_access_token = Secret::new("rotated-access-token");
+        new.atoma_api_key = Secret::new("rotated-atoma-key");
+
+        assert_eq!(
+            reloadable_changes(&current, &new),
+            ["guess_ai.twitter_access_token"]
+        );
+        assert_eq!(
+            immutable_changes(&current, &new),
+            ["guess_ai.atoma_api_key"]
+        );
+    }
+}
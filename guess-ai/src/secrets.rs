@@ -0,0 +1,597 @@
+//! Secrets of the configuration, and the providers they are read from.
+//!
+//! A secret field of the configuration holds either the secret itself, or a reference to the
+//! provider to read it from, at boot:
+//!
+//! ```toml
+//! atoma_api_key = { provider = "file", path = "/run/secrets/atoma_api_key" }
+//! twitter_consumer_key = { provider = "env", var = "TWITTER_CONSUMER_KEY" }
+//! twitter_consumer_secret = { provider = "keystore", name = "twitter_consumer_secret" }
+//! ```
+//!
+//! The keystore is a file encrypted with a key derived from a passphrase (itself read from a
+//! file or an environment variable). The passphrase is the only secret protecting it.
+//!
+//! Sealing the keystore to the TDX trust domain is out of scope: it needs a key released
+//! against a quote of the trust domain, by a key broker verifying it. Deriving the key from
+//! the measurement alone would not seal anything, as the measurement is public (every quote
+//! of the trust domain carries it).
+
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf};
+
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use argon2::Argon2;
+use base64::engine::{general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::persist::write_atomically_owner_only;
+
+type Result<T> = std::result::Result<T, SecretError>;
+
+/// The version of the keystore file format
+const KEYSTORE_VERSION: u8 = 1;
+
+/// The size of the salt of the passphrase key derivation
+const KEYSTORE_SALT_SIZE: usize = 16;
+
+/// The size of the AES-256-GCM nonce
+const KEYSTORE_NONCE_SIZE: usize = 12;
+
+/// A secret value, such as an API key, whose `Debug` output is redacted.
+///
+/// In the configuration, it is either the secret itself, or a [`SecretRef`] to the provider
+/// to read it from, which must be resolved (see [`SecretProviders`]) before the secret is used.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret {
+    /// Where the secret is read from, unless it is given inline
+    reference: Option<SecretRef>,
+
+    /// The secret, once resolved
+    value: Option<String>,
+}
+
+impl Secret {
+    /// Wraps a secret value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            reference: None,
+            value: Some(value.into()),
+        }
+    }
+
+    /// Where the secret is read from, unless it is given inline
+    pub fn reference(&self) -> Option<&SecretRef> {
+        self.reference.as_ref()
+    }
+
+    /// Whether the secret is known, given inline or read from its provider
+    pub fn is_resolved(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Returns the secret value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the secret was not resolved, which the configuration loading guarantees
+    pub fn expose_secret(&self) -> &str {
+        self.value.as_deref().unwrap_or_else(|| {
+            panic!(
+                "Secret {:?} was used before it was resolved",
+                self.reference
+            )
+        })
+    }
+
+    /// Reads the secret from its provider, if it is not given inline.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the provider cannot read the secret
+    pub fn resolve(&mut self, providers: &SecretProviders) -> Result<()> {
+        if let Some(reference) = &self.reference {
+            self.value = Some(providers.read(reference)?);
+        }
+        Ok(())
+    }
+}
+
+/// An empty secret, given inline, so that a missing optional secret is reported as empty
+/// rather than unresolved
+impl Default for Secret {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.reference, &self.value) {
+            (_, Some(_)) => f.write_str("Secret(<redacted>)"),
+            (Some(reference), None) => write!(f, "Secret(<unresolved {reference:?}>)"),
+            (None, None) => f.write_str("Secret(<unresolved>)"),
+        }
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum SecretRepr {
+            Inline(String),
+            Reference(SecretRef),
+        }
+        Ok(match SecretRepr::deserialize(deserializer)? {
+            SecretRepr::Inline(value) => Self::new(value),
+            SecretRepr::Reference(reference) => Self {
+                reference: Some(reference),
+                value: None,
+            },
+        })
+    }
+}
+
+impl Serialize for Secret {
+    /// Serializes the reference to the secret, never the secret itself
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match &self.reference {
+            Some(reference) => reference.serialize(serializer),
+            None => serializer.serialize_str("<redacted>"),
+        }
+    }
+}
+
+/// A reference to a secret, and the provider to read it from
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum SecretRef {
+    /// A file holding the secret, readable by its owner only
+    File { path: String },
+    /// An environment variable holding the secret
+    Env { var: String },
+    /// An entry of the encrypted keystore
+    Keystore { name: String },
+}
+
+/// A backend reading secrets
+pub trait SecretProvider {
+    /// Reads the secret identified by `key`: a file path, a variable name or a keystore entry
+    /// name, depending on the provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the secret cannot be read
+    fn read(&self, key: &str) -> Result<String>;
+}
+
+/// Reads secrets from files, which must not be accessible by the group or by others
+#[derive(Debug, Default)]
+pub struct FileSecretProvider;
+
+impl SecretProvider for FileSecretProvider {
+    fn read(&self, key: &str) -> Result<String> {
+        let metadata = fs::metadata(key)?;
+        if !metadata.is_file() {
+            return Err(SecretError::NotAFile(key.to_string()));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = metadata.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return Err(SecretError::PermissionsTooOpen {
+                    path: key.to_string(),
+                    mode,
+                });
+            }
+        }
+        Ok(fs::read_to_string(key)?
+            .trim_end_matches(['\r', '\n'])
+            .to_string())
+    }
+}
+
+/// Reads secrets from environment variables
+#[derive(Debug, Default)]
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    fn read(&self, key: &str) -> Result<String> {
+        std::env::var(key).map_err(|_| SecretError::MissingEnvVar(key.to_string()))
+    }
+}
+
+/// How the key of a keystore file is derived, besides the passphrase
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum KeystoreSealing {
+    /// The key is derived from the passphrase only
+    #[default]
+    None,
+    /// The key was also derived from the TDX measurement, which is no longer supported
+    Tdx,
+}
+
+/// Configuration of the encrypted keystore
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct KeystoreConfig {
+    /// Path of the keystore file
+    pub path: String,
+
+    /// The passphrase unlocking the keystore, read from a file or an environment variable
+    pub passphrase: Secret,
+}
+
+/// The keystore file, as written to disk
+#[derive(Deserialize, Serialize)]
+struct KeystoreFile {
+    version: u8,
+    #[serde(default)]
+    sealing: KeystoreSealing,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An encrypted keystore of named secrets, unlocked at boot
+pub struct Keystore {
+    /// Path of the keystore file
+    path: PathBuf,
+
+    /// The salt of the passphrase key derivation
+    salt: Vec<u8>,
+
+    /// The key encrypting the entries
+    key: [u8; 32],
+
+    /// The secrets, by name
+    entries: BTreeMap<String, String>,
+}
+
+impl Keystore {
+    /// Unlocks the keystore at `config.path`, or creates an empty one if the file doesn't
+    /// exist and `create` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the passphrase is not resolved, the keystore cannot be read,
+    /// or it cannot be decrypted with the passphrase
+    pub fn unlock(config: &KeystoreConfig, create: bool) -> Result<Self> {
+        let passphrase = config
+            .passphrase
+            .value
+            .as_deref()
+            .ok_or(SecretError::UnresolvedPassphrase)?;
+        let path = PathBuf::from(&config.path);
+        if create && !path.exists() {
+            let mut salt = vec![0u8; KEYSTORE_SALT_SIZE];
+            rand::thread_rng().fill_bytes(&mut salt);
+            let key = derive_key(passphrase, &salt)?;
+            return Ok(Self {
+                path,
+                salt,
+                key,
+                entries: BTreeMap::new(),
+            });
+        }
+
+        let file: KeystoreFile = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(SecretError::UnsupportedKeystoreVersion(file.version));
+        }
+        if file.sealing != KeystoreSealing::None {
+            return Err(SecretError::MeasurementBoundKeystore);
+        }
+        let salt = STANDARD.decode(&file.salt)?;
+        let nonce = STANDARD.decode(&file.nonce)?;
+        if nonce.len() != KEYSTORE_NONCE_SIZE {
+            return Err(SecretError::MalformedKeystore);
+        }
+        let key = derive_key(passphrase, &salt)?;
+        let plaintext = Aes256Gcm::new(&key.into())
+            .decrypt(
+                nonce.as_slice().into(),
+                STANDARD.decode(&file.ciphertext)?.as_ref(),
+            )
+            .map_err(|_| SecretError::KeystoreLocked)?;
+        Ok(Self {
+            path,
+            salt,
+            key,
+            entries: serde_json::from_slice(&plaintext)?,
+        })
+    }
+
+    /// The names of the secrets in the keystore
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Adds or replaces a secret. The keystore must be saved for the change to persist
+    pub fn insert(&mut self, name: String, value: Secret) {
+        self.entries.insert(name, value.expose_secret().to_string());
+    }
+
+    /// Removes a secret, returning whether it existed. The keystore must be saved for the
+    /// change to persist
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// Encrypts the keystore to its file, readable by its owner only.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the keystore cannot be written
+    pub fn save(&self) -> Result<()> {
+        let mut nonce = [0u8; KEYSTORE_NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new(&self.key.into())
+            .encrypt(&nonce.into(), serde_json::to_vec(&self.entries)?.as_ref())
+            .map_err(|_| SecretError::MalformedKeystore)?;
+        let file = KeystoreFile {
+            version: KEYSTORE_VERSION,
+            sealing: KeystoreSealing::None,
+            salt: STANDARD.encode(&self.salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        write_atomically_owner_only(&self.path, &serde_json::to_vec_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+impl SecretProvider for Keystore {
+    fn read(&self, key: &str) -> Result<String> {
+        self.entries
+            .get(key)
+            .cloned()
+            .ok_or_else(|| SecretError::MissingKeystoreEntry(key.to_string()))
+    }
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystore")
+            .field("path", &self.path)
+            .field("names", &self.entries.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The secret providers, dispatching each [`SecretRef`] to its backend
+#[derive(Debug, Default)]
+pub struct SecretProviders {
+    file: FileSecretProvider,
+    env: EnvSecretProvider,
+    keystore: Option<Keystore>,
+}
+
+impl SecretProviders {
+    /// Builds the providers, unlocking the keystore if one is configured.
+    ///
+    /// The keystore passphrase is itself resolved with the file and environment providers.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the keystore cannot be unlocked
+    pub fn new(keystore: Option<&KeystoreConfig>) -> Result<Self> {
+        let mut providers = Self::default();
+        if let Some(config) = keystore {
+            let mut config = config.clone();
+            if matches!(
+                config.passphrase.reference,
+                Some(SecretRef::Keystore { .. })
+            ) {
+                return Err(SecretError::KeystorePassphraseInKeystore);
+            }
+            config.passphrase.resolve(&providers)?;
+            providers.keystore = Some(Keystore::unlock(&config, false)?);
+        }
+        Ok(providers)
+    }
+
+    /// Reads the referenced secret from its provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SecretError` if the provider cannot read the secret, or the keystore is
+    /// referenced but not configured
+    pub fn read(&self, reference: &SecretRef) -> Result<String> {
+        match reference {
+            SecretRef::File { path } => self.file.read(path),
+            SecretRef::Env { var } => self.env.read(var),
+            SecretRef::Keystore { name } => self
+                .keystore
+                .as_ref()
+                .ok_or(SecretError::NoKeystore)?
+                .read(name),
+        }
+    }
+}
+
+/// Derives the keystore key from the passphrase
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| SecretError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Failed to read secret: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Secret file {0} is not a regular file")]
+    NotAFile(String),
+    #[error("Secret file {path} is accessible by others (mode {mode:o}), it must be 600 or 400")]
+    PermissionsTooOpen { path: String, mode: u32 },
+    #[error("Environment variable {0} is not set")]
+    MissingEnvVar(String),
+    #[error("A secret references the keystore, but no keystore is configured")]
+    NoKeystore,
+    #[error("Keystore has no secret named {0}")]
+    MissingKeystoreEntry(String),
+    #[error("The keystore passphrase must be read from a file or an environment variable")]
+    KeystorePassphraseInKeystore,
+    #[error("The keystore passphrase is not resolved")]
+    UnresolvedPassphrase,
+    #[error("Failed to decrypt the keystore: wrong passphrase")]
+    KeystoreLocked,
+    #[error("Malformed keystore")]
+    MalformedKeystore,
+    #[error("Unsupported keystore version {0}")]
+    UnsupportedKeystoreVersion(u8),
+    #[error(
+        "Keystore is bound to the TDX measurement, which is no longer supported: re-create it"
+    )]
+    MeasurementBoundKeystore,
+    #[error("Failed to derive the keystore key: {0}")]
+    KeyDerivation(String),
+    #[error("Failed to (de)serialize the keystore: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Failed to decode the keystore: {0}")]
+    Base64(#[from] base64::DecodeError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystore_config(dir: &tempfile::TempDir, passphrase: &str) -> KeystoreConfig {
+        KeystoreConfig {
+            path: dir
+                .path()
+                .join("keystore.json")
+                .to_string_lossy()
+                .into_owned(),
+            passphrase: Secret::new(passphrase),
+        }
+    }
+
+    #[cfg(unix)]
+    fn mode(path: impl AsRef<std::path::Path>) -> u32 {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = keystore_config(&dir, "correct horse");
+        let mut keystore = Keystore::unlock(&config, true).unwrap();
+        keystore.insert("atoma_api_key".to_string(), Secret::new("sk-123"));
+        keystore.save().unwrap();
+
+        let keystore = Keystore::unlock(&config, false).unwrap();
+        assert_eq!(keystore.names().collect::<Vec<_>>(), ["atoma_api_key"]);
+        assert_eq!(keystore.read("atoma_api_key").unwrap(), "sk-123");
+        assert!(matches!(
+            keystore.read("twitter_access_token"),
+            Err(SecretError::MissingKeystoreEntry(_))
+        ));
+    }
+
+    #[test]
+    fn test_keystore_rejects_wrong_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        Keystore::unlock(&keystore_config(&dir, "correct horse"), true)
+            .unwrap()
+            .save()
+            .unwrap();
+
+        let result = Keystore::unlock(&keystore_config(&dir, "battery staple"), false);
+        assert!(matches!(result, Err(SecretError::KeystoreLocked)));
+    }
+
+    #[test]
+    fn test_keystore_rejects_measurement_bound_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = keystore_config(&dir, "correct horse");
+        Keystore::unlock(&config, true).unwrap().save().unwrap();
+        let mut file: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config.path).unwrap()).unwrap();
+        assert_eq!(file["sealing"], "none");
+        file["sealing"] = "tdx".into();
+        fs::write(&config.path, file.to_string()).unwrap();
+
+        let result = Keystore::unlock(&config, false);
+        assert!(matches!(result, Err(SecretError::MeasurementBoundKeystore)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_keystore_save_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = keystore_config(&dir, "correct horse");
+        let mut keystore = Keystore::unlock(&config, true).unwrap();
+        keystore.insert("atoma_api_key".to_string(), Secret::new("sk-123"));
+        // Both the keystore and a temporary file left over by an earlier save are readable by
+        // others
+        let tmp_path = format!("{}.tmp", config.path);
+        for path in [&config.path, &tmp_path] {
+            fs::write(path, "").unwrap();
+            fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        keystore.save().unwrap();
+        assert_eq!(mode(&config.path), 0o600);
+        assert!(!std::path::Path::new(&tmp_path).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_provider_rejects_open_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("atoma_api_key");
+        fs::write(&path, "sk-123\n").unwrap();
+        let key = path.to_string_lossy();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(
+            FileSecretProvider.read(&key),
+            Err(SecretError::PermissionsTooOpen { mode: 0o644, .. })
+        ));
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(FileSecretProvider.read(&key).unwrap(), "sk-123");
+    }
+
+    #[test]
+    fn test_default_secret_is_resolved_and_empty() {
+        let secret = Secret::default();
+        assert!(secret.is_resolved());
+        assert_eq!(secret.expose_secret(), "");
+    }
+
+    #[test]
+    fn test_providers_resolve_keystore_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = keystore_config(&dir, "correct horse");
+        let mut keystore = Keystore::unlock(&config, true).unwrap();
+        keystore.insert("atoma_api_key".to_string(), Secret::new("sk-123"));
+        keystore.save().unwrap();
+
+        let providers = SecretProviders::new(Some(&config)).unwrap();
+        let mut secret: Secret =
+            serde_json::from_str(r#"{"provider": "keystore", "name": "atoma_api_key"}"#).unwrap();
+        assert!(!secret.is_resolved());
+        secret.resolve(&providers).unwrap();
+        assert_eq!(secret.expose_secret(), "sk-123");
+        assert_eq!(format!("{secret:?}"), "Secret(<redacted>)");
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimilarityConfig;
+
+/// Computes the cosine similarity between two embeddings.
+///
+/// Returns `None` if the embeddings have different dimensions, or if any of them is a
+/// zero vector.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let (dot, norm_a, norm_b) =
+        a.iter()
+            .zip(b)
+            .fold((0f64, 0f64, 0f64), |(dot, norm_a, norm_b), (&x, &y)| {
+                let (x, y) = (x as f64, y as f64);
+                (dot + x * y, norm_a + x * x, norm_b + y * y)
+            });
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some((dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(-1.0, 1.0) as f32)
+}
+
+/// How semantically close a guess is to the secret.
+///
+/// Only this coarse bucket is ever exposed to players, never the raw similarity score,
+/// so that the secret cannot be recovered by following the gradient of the score
+/// across many guesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityBucket {
+    /// The guess is unrelated to the secret
+    Cold,
+    /// The guess is loosely related to the secret
+    Cool,
+    /// The guess is related to the secret
+    Warm,
+    /// The guess is closely related to the secret
+    Hot,
+}
+
+impl SimilarityBucket {
+    /// Buckets a cosine similarity score using the configured thresholds
+    pub fn from_score(score: f32, config: &SimilarityConfig) -> Self {
+        if score >= config.hot_threshold {
+            Self::Hot
+        } else if score >= config.warm_threshold {
+            Self::Warm
+        } else if score >= config.cool_threshold {
+            Self::Cool
+        } else {
+            Self::Cold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        let similarity = |a: &[f32], b: &[f32]| cosine_similarity(a, b).unwrap();
+        assert!((similarity(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]) - 1.0).abs() < 1e-6);
+        assert!((similarity(&[1.0, 0.0], &[-3.0, 0.0]) + 1.0).abs() < 1e-6);
+        assert!(similarity(&[1.0, 0.0], &[0.0, 5.0]).abs() < 1e-6);
+        assert!(
+            (similarity(&[1.0, 1.0], &[1.0, 0.0]) - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity_of_zero_vectors() {
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 2.0]), None);
+        assert_eq!(cosine_similarity(&[1.0, 2.0], &[0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_cosine_similarity_of_mismatched_lengths() {
+        assert_eq!(cosine_similarity(&[1.0, 2.0], &[1.0, 2.0, 3.0]), None);
+        assert_eq!(cosine_similarity(&[1.0], &[]), None);
+    }
+
+    #[test]
+    fn test_bucket_boundaries() {
+        let config = SimilarityConfig::default();
+        let cases = [
+            (-1.0, SimilarityBucket::Cold),
+            (0.0, SimilarityBucket::Cold),
+            (0.299, SimilarityBucket::Cold),
+            (0.3, SimilarityBucket::Cool),
+            (0.499, SimilarityBucket::Cool),
+            (0.5, SimilarityBucket::Warm),
+            (0.699, SimilarityBucket::Warm),
+            (0.7, SimilarityBucket::Hot),
+            (1.0, SimilarityBucket::Hot),
+        ];
+        for (score, bucket) in cases {
+            assert_eq!(
+                SimilarityBucket::from_score(score, &config),
+                bucket,
+                "{score}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bucket_follows_configured_thresholds() {
+        let config = SimilarityConfig {
+            embedding_model: None,
+            cool_threshold: 0.1,
+            warm_threshold: 0.2,
+            hot_threshold: 0.9,
+        };
+        assert_eq!(
+            SimilarityBucket::from_score(0.15, &config),
+            SimilarityBucket::Cool
+        );
+        assert_eq!(
+            SimilarityBucket::from_score(0.8, &config),
+            SimilarityBucket::Warm
+        );
+        assert_eq!(
+            SimilarityBucket::from_score(0.9, &config),
+            SimilarityBucket::Hot
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::types::{ChatCompletionMessage, ChatCompletionResponse, ChatCompletionRole};
+
+/// The maximum number of repair requests sent after a response that cannot be parsed
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// The markdown code fence models tend to wrap JSON answers in
+const CODE_FENCE: &str = "```";
+
+/// A response the model is asked to produce as a JSON object matching the schema of `Self`
+pub trait StructuredResponse: DeserializeOwned + JsonSchema {
+    /// The name of the schema, sent to the model along with the schema
+    const SCHEMA_NAME: &'static str;
+}
+
+/// Builds the `response_format` of a chat completion request, constraining the model
+/// to answer with a JSON object matching the schema of `T`
+pub fn response_format<T: StructuredResponse>() -> Value {
+    json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": T::SCHEMA_NAME,
+            "strict": true,
+            "schema": schema_for!(T),
+        },
+    })
+}
+
+/// Parses the first choice of a chat completion response into `T`.
+///
+/// Code fences and any prose surrounding the JSON object are stripped before parsing,
+/// as not every model honours `response_format`.
+///
+/// # Errors
+///
+/// Returns a `StructuredOutputError` if the response has no choices, the first choice has
+/// no content, or its content doesn't match the schema of `T`.
+pub fn parse_response<T: StructuredResponse>(
+    response: &ChatCompletionResponse,
+) -> Result<T, StructuredOutputError> {
+    let content = response
+        .choices
+        .first()
+        .ok_or(StructuredOutputError::EmptyChoices)?
+        .message
+        .content
+        .as_deref()
+        .filter(|content| !content.trim().is_empty())
+        .ok_or(StructuredOutputError::EmptyContent)?;
+    serde_json::from_str(extract_json(content)).map_err(|e| StructuredOutputError::InvalidJson {
+        schema: T::SCHEMA_NAME,
+        error: e.to_string(),
+        content: content.to_string(),
+    })
+}
+
+/// Builds the messages to append to a conversation whose last response could not be
+/// parsed: the faulty response, followed by a request to answer again with valid JSON.
+pub fn repair_messages(
+    response: &ChatCompletionResponse,
+    error: &StructuredOutputError,
+) -> Vec<ChatCompletionMessage> {
+    let content = response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .unwrap_or_default();
+    vec![
+        ChatCompletionMessage::new(ChatCompletionRole::Assistant, content),
+        ChatCompletionMessage::new(
+            ChatCompletionRole::User,
+            format!(
+                "Your previous answer could not be parsed: {error}. Answer again with only a \
+                 JSON object matching the requested schema, without code fences or any other text."
+            ),
+        ),
+    ]
+}
+
+/// Counts the repair requests sent for a structured response, see [`Self::parse_or_repair`]
+#[derive(Debug, Default)]
+pub struct RepairAttempts {
+    /// The number of repair requests sent so far
+    attempts: usize,
+}
+
+impl RepairAttempts {
+    /// Parses the first choice of `response` into `T`, see [`parse_response`].
+    ///
+    /// If it cannot be parsed and fewer than `MAX_REPAIR_ATTEMPTS` repairs were requested,
+    /// the messages asking the model to repair its answer (see [`repair_messages`]) are
+    /// appended to `messages` and `None` is returned: the caller then sends `messages` again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `StructuredOutputError` of the response once `MAX_REPAIR_ATTEMPTS` repairs
+    /// were requested
+    pub fn parse_or_repair<T: StructuredResponse>(
+        &mut self,
+        response: &ChatCompletionResponse,
+        messages: &mut Vec<ChatCompletionMessage>,
+    ) -> Result<Option<T>, StructuredOutputError> {
+        match parse_response::<T>(response) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(e) if self.attempts < MAX_REPAIR_ATTEMPTS => {
+                self.attempts += 1;
+                warn!(
+                    target = "structured_output",
+                    event = "structured-output-repair",
+                    schema = T::SCHEMA_NAME,
+                    repair_attempts = self.attempts,
+                    "Failed to parse model response, asking the model to repair it: {e}"
+                );
+                messages.extend(repair_messages(response, &e));
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Extracts the JSON object from a model answer, stripping markdown code fences
+/// and any prose before or after the object
+pub fn extract_json(content: &str) -> &str {
+    let mut content = content.trim();
+    if let Some(start) = content.find(CODE_FENCE) {
+        // Skip the opening fence and its language tag, e.g. "```json"
+        let fenced = &content[start + CODE_FENCE.len()..];
+        let fenced = fenced.split_once('\n').map_or(fenced, |(_, body)| body);
+        content = fenced.find(CODE_FENCE).map_or(fenced, |end| &fenced[..end]);
+    }
+    match (content.find('{'), content.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => content.trim(),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StructuredOutputError {
+    #[error("Chat completion response has no choices")]
+    EmptyChoices,
+    #[error("Chat completion response has no content")]
+    EmptyContent,
+    #[error("Response does not match the `{schema}` schema: {error}")]
+    InvalidJson {
+        schema: &'static str,
+        error: String,
+        content: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    struct Hint {
+        hint: String,
+    }
+
+    impl StructuredResponse for Hint {
+        const SCHEMA_NAME: &'static str = "hint";
+    }
+
+    fn response(content: &str) -> ChatCompletionResponse {
+        serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "created": 0,
+            "model": "model",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop",
+            }],
+            "usage": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_or_repair_parses_a_valid_answer() {
+        let mut messages = Vec::new();
+        let hint = RepairAttempts::default()
+            .parse_or_repair::<Hint>(&response(r#"{"hint": "warm"}"#), &mut messages)
+            .unwrap();
+        assert_eq!(hint.unwrap().hint, "warm");
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn parse_or_repair_asks_for_repairs_then_gives_up() {
+        let mut repair_attempts = RepairAttempts::default();
+        let mut messages = Vec::new();
+        for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+            let hint = repair_attempts
+                .parse_or_repair::<Hint>(&response("not JSON"), &mut messages)
+                .unwrap();
+            assert!(hint.is_none());
+            assert_eq!(messages.len(), 2 * attempt);
+        }
+        assert_eq!(messages[0].content.as_deref(), Some("not JSON"));
+        assert!(matches!(messages[1].role, ChatCompletionRole::User));
+
+        let result = repair_attempts.parse_or_repair::<Hint>(&response("not JSON"), &mut messages);
+        assert!(matches!(
+            result,
+            Err(StructuredOutputError::InvalidJson { schema: "hint", .. })
+        ));
+        assert_eq!(messages.len(), 2 * MAX_REPAIR_ATTEMPTS);
+    }
+
+    #[test]
+    fn parse_or_repair_accepts_a_repaired_answer() {
+        let mut repair_attempts = RepairAttempts::default();
+        let mut messages = Vec::new();
+        let hint = repair_attempts
+            .parse_or_repair::<Hint>(&response(""), &mut messages)
+            .unwrap();
+        assert!(hint.is_none());
+        let hint = repair_attempts
+            .parse_or_repair::<Hint>(
+                &response("```json\n{\"hint\": \"cold\"}\n```"),
+                &mut messages,
+            )
+            .unwrap();
+        assert_eq!(hint.unwrap().hint, "cold");
+    }
+
+    #[test]
+    fn extract_json_keeps_a_bare_object() {
+        assert_eq!(extract_json(r#"{"hint": "warm"}"#), r#"{"hint": "warm"}"#);
+        assert_eq!(extract_json("  {\"a\": 1}\n"), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn extract_json_strips_code_fences() {
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), "{\"a\": 1}");
+        assert_eq!(extract_json("```\n{\"a\": 1}\n```"), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn extract_json_strips_surrounding_prose() {
+        assert_eq!(
+            extract_json("Here is my answer: {\"a\": {\"b\": 2}} Hope it helps!"),
+            "{\"a\": {\"b\": 2}}"
+        );
+        assert_eq!(
+            extract_json("Sure!\n```json\n{\"a\": 1}\n```\nAnything else?"),
+            "{\"a\": 1}"
+        );
+    }
+
+    #[test]
+    fn extract_json_handles_an_unterminated_fence() {
+        assert_eq!(extract_json("```json\n{\"a\": 1}"), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn extract_json_without_an_object_returns_the_trimmed_content() {
+        assert_eq!(extract_json("  no JSON here  "), "no JSON here");
+        assert_eq!(extract_json("} backwards {"), "} backwards {");
+        assert_eq!(extract_json(""), "");
+    }
+}
@@ -0,0 +1,291 @@
+use std::{collections::BTreeMap, future::Future, pin::Pin, sync::Arc};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{error, info, instrument};
+
+use crate::{
+    game_state::GameState,
+    types::{ChatCompletionMessage, FunctionDefinition, Tool, ToolCall},
+};
+
+/// The name of the tool reading the state of the game
+pub const GAME_STATE_TOOL_NAME: &str = "get_game_state";
+
+/// The future returned by a tool handler
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + 'a>>;
+
+/// A Rust function that the model can call as a tool.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// struct Echo;
+///
+/// impl ToolHandler for Echo {
+///     fn definition(&self) -> FunctionDefinition {
+///         FunctionDefinition {
+///             name: "echo".to_string(),
+///             description: Some("Echoes its arguments back".to_string()),
+///             parameters: Some(json!({"type": "object"})),
+///             strict: None,
+///         }
+///     }
+///
+///     fn call(&self, arguments: Value) -> ToolFuture<'_> {
+///         Box::pin(async move { Ok(arguments) })
+///     }
+/// }
+/// ```
+pub trait ToolHandler: Send + Sync {
+    /// The function definition advertised to the model
+    fn definition(&self) -> FunctionDefinition;
+
+    /// Executes the tool with the arguments generated by the model
+    fn call(&self, arguments: Value) -> ToolFuture<'_>;
+}
+
+/// A registry of tool handlers, keyed by function name
+#[derive(Default)]
+pub struct ToolRegistry {
+    /// The registered handlers, ordered by name so that the tools are advertised in a stable
+    /// order
+    handlers: BTreeMap<String, Box<dyn ToolHandler>>,
+}
+
+impl ToolRegistry {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a tool handler, replacing any handler previously registered with the same name
+    pub fn register<H: ToolHandler + 'static>(&mut self, handler: H) -> &mut Self {
+        let name = handler.definition().name;
+        self.handlers.insert(name, Box::new(handler));
+        self
+    }
+
+    /// Whether no tool is registered
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// The tools to advertise to the model
+    pub fn tools(&self) -> Vec<Tool> {
+        self.handlers
+            .values()
+            .map(|handler| Tool::function(handler.definition()))
+            .collect()
+    }
+
+    /// Executes a tool call generated by the model, and returns the tool message to feed
+    /// back to the model.
+    ///
+    /// Failures (unknown tool, invalid arguments or a failing handler) are reported to the
+    /// model in the tool message, as `{"error": "<reason>"}`, so that it can recover.
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(
+            tool_call_id = %tool_call.id,
+            function = %tool_call.function.name,
+        )
+    )]
+    pub async fn execute(&self, tool_call: &ToolCall) -> ChatCompletionMessage {
+        let content = match self.call(tool_call).await {
+            Ok(result) => {
+                info!(
+                    target = "tool_registry",
+                    event = "tool-call-succeeded",
+                    "Tool call succeeded"
+                );
+                result
+            }
+            Err(e) => {
+                error!(
+                    target = "tool_registry",
+                    event = "tool-call-failed",
+                    "Tool call failed: {e}"
+                );
+                json!({ "error": e.to_string() })
+            }
+        };
+        ChatCompletionMessage::tool(tool_call.id.clone(), content.to_string())
+    }
+
+    /// Looks up the handler of a tool call and runs it
+    async fn call(&self, tool_call: &ToolCall) -> Result<Value, ToolError> {
+        let handler = self
+            .handlers
+            .get(&tool_call.function.name)
+            .ok_or_else(|| ToolError::UnknownTool(tool_call.function.name.clone()))?;
+        let arguments = if tool_call.function.arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(&tool_call.function.arguments)?
+        };
+        handler.call(arguments).await
+    }
+}
+
+/// Reads the state of the game, as last synced from the `guess_ai_db` object, so that the
+/// model can refer to the progress of the game
+pub struct GameStateTool {
+    /// The state of the game, shared with the engine
+    game_state: Arc<RwLock<GameState>>,
+}
+
+impl GameStateTool {
+    /// Constructor
+    pub fn new(game_state: Arc<RwLock<GameState>>) -> Self {
+        Self { game_state }
+    }
+}
+
+impl ToolHandler for GameStateTool {
+    fn definition(&self) -> FunctionDefinition {
+        FunctionDefinition {
+            name: GAME_STATE_TOOL_NAME.to_string(),
+            description: Some(
+                "Returns the current state of the guessing game: the number of guesses made, \
+                 the fee of the next guess and the balance of the treasury pool paid out to \
+                 the winner, both in MIST (1 SUI = 10^9 MIST)"
+                    .to_string(),
+            ),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false,
+            })),
+            strict: None,
+        }
+    }
+
+    fn call(&self, _arguments: Value) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let game_state = self.game_state.read().await;
+            Ok(json!({
+                "guess_game_id": game_state.guess_game_id,
+                "guess_count": game_state.guess_count,
+                "fee": game_state.fee,
+                "treasury_pool_balance": game_state.treasury_pool_balance,
+                "is_active": game_state.is_active,
+            }))
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ToolError {
+    #[error("Unknown tool: `{0}`")]
+    UnknownTool(String),
+    #[error("Invalid tool arguments: `{0}`")]
+    InvalidArguments(#[from] serde_json::Error),
+    #[error("Tool execution failed: `{0}`")]
+    ExecutionError(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+
+    use super::*;
+    use crate::types::{FunctionCall, ToolType};
+
+    /// A tool returning its name, to tell the handlers apart
+    struct Named(&'static str);
+
+    impl ToolHandler for Named {
+        fn definition(&self) -> FunctionDefinition {
+            FunctionDefinition {
+                name: self.0.to_string(),
+                description: None,
+                parameters: None,
+                strict: None,
+            }
+        }
+
+        fn call(&self, _arguments: Value) -> ToolFuture<'_> {
+            Box::pin(async move { Ok(json!(self.0)) })
+        }
+    }
+
+    fn tool_call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            tool_type: ToolType::Function,
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn tools_are_advertised_by_name() {
+        let mut tools = ToolRegistry::new();
+        tools
+            .register(Named("roll_dice"))
+            .register(Named("get_weather"))
+            .register(Named("lookup"));
+
+        let names = tools
+            .tools()
+            .into_iter()
+            .map(|tool| tool.function.name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["get_weather", "lookup", "roll_dice"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_tools_are_reported_to_the_model() {
+        let tools = ToolRegistry::new();
+
+        let message = tools.execute(&tool_call("lookup", "")).await;
+
+        assert_eq!(message.tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(
+            message.content.as_deref(),
+            Some(r#"{"error":"Unknown tool: `lookup`"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn game_state_tool_reads_the_shared_state() {
+        let game_state = Arc::new(RwLock::new(GameState {
+            guess_ai_db: ObjectID::ZERO,
+            version: 1,
+            agent_address: SuiAddress::ZERO,
+            guess_game_id: 3,
+            guess_count: 12,
+            fee: 1_100_000_000,
+            starting_fee: 1_000_000_000,
+            fee_rate_increase_per_guess_per_mille: 100,
+            update_fee_every_n_guesses: 10,
+            protocol_fee_per_mille: 50,
+            treasury_pool_balance: 12_000_000_000,
+            protocol_fee_pool_balance: 600_000_000,
+            is_active: true,
+        }));
+        let mut tools = ToolRegistry::new();
+        tools.register(GameStateTool::new(Arc::clone(&game_state)));
+        game_state.write().await.guess_count = 13;
+
+        let message = tools.execute(&tool_call(GAME_STATE_TOOL_NAME, "{}")).await;
+        let content: Value = serde_json::from_str(message.content.as_deref().unwrap()).unwrap();
+
+        assert_eq!(
+            content,
+            json!({
+                "guess_game_id": 3,
+                "guess_count": 13,
+                "fee": 1_100_000_000u64,
+                "treasury_pool_balance": 12_000_000_000u64,
+                "is_active": true,
+            })
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::envelope::CipherSuite;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateChatCompletionRequest {
     #[serde(flatten)]
@@ -20,7 +22,7 @@ pub struct CreateChatCompletionStreamRequest {
     pub stream: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChatCompletionRequest {
     /// ID of the model to use
     pub model: String,
@@ -70,13 +72,15 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
-    /// A list of functions the model may generate JSON inputs for
+    /// A list of functions the model may generate JSON inputs for.
+    /// Deprecated in favor of `tools`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub functions: Option<Vec<Value>>,
+    pub functions: Option<Vec<FunctionDefinition>>,
 
-    /// Controls how the model responds to function calls
+    /// Controls how the model responds to function calls.
+    /// Deprecated in favor of `tool_choice`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_call: Option<Value>,
+    pub function_call: Option<FunctionCallChoice>,
 
     /// The format to return the response in
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -84,28 +88,193 @@ pub struct ChatCompletionRequest {
 
     /// A list of tools the model may call
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<Vec<Value>>,
+    pub tools: Option<Vec<Tool>>,
 
     /// Controls which (if any) tool the model should use
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_choice: Option<Value>,
+    pub tool_choice: Option<ToolChoice>,
 
     /// If specified, our system will make a best effort to sample deterministically
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChatCompletionMessage {
-    /// The role of the message author. One of: "system", "user", "assistant", "tool", or "function"
-    pub role: String,
+    /// The role of the message author
+    pub role: ChatCompletionRole,
 
-    /// The contents of the message
-    pub content: String,
+    /// The contents of the message. Can be null for assistant messages with tool calls
+    pub content: Option<String>,
 
     /// The name of the author of this message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+
+    /// The tool calls generated by the model, for assistant messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+
+    /// The ID of the tool call this message is responding to, for tool messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatCompletionMessage {
+    /// Creates a plain text message
+    pub fn new(role: ChatCompletionRole, content: String) -> Self {
+        Self {
+            role,
+            content: Some(content),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Creates a tool message, carrying the result of the tool call with ID `tool_call_id`
+    pub fn tool(tool_call_id: String, content: String) -> Self {
+        Self {
+            role: ChatCompletionRole::Tool,
+            content: Some(content),
+            name: None,
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id),
+        }
+    }
+}
+
+/// The role of the author of a chat completion message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatCompletionRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+    Function,
+}
+
+/// A tool the model may call
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tool {
+    /// The type of the tool. Currently, only `function` is supported
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+
+    /// The function definition
+    pub function: FunctionDefinition,
+}
+
+impl Tool {
+    /// Creates a function tool
+    pub fn function(function: FunctionDefinition) -> Self {
+        Self {
+            tool_type: ToolType::Function,
+            function,
+        }
+    }
+}
+
+/// The type of a tool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolType {
+    Function,
+}
+
+/// The definition of a function the model may call
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionDefinition {
+    /// The name of the function
+    pub name: String,
+
+    /// A description of what the function does, used by the model to choose when to call it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The parameters the function accepts, described as a JSON Schema object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+
+    /// Whether the model must strictly follow the parameters schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// Controls which (if any) tool the model should use
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolChoice {
+    /// One of `none`, `auto` or `required`
+    Mode(ToolChoiceMode),
+    /// Forces the model to call a specific function
+    Named(NamedToolChoice),
+}
+
+/// The tool choice modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoiceMode {
+    /// The model will not call any tool
+    None,
+    /// The model can pick between generating a message or calling tools
+    Auto,
+    /// The model must call one or more tools
+    Required,
+}
+
+/// A tool choice forcing the model to call a specific function
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamedToolChoice {
+    /// The type of the tool
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+
+    /// The function to call
+    pub function: FunctionName,
+}
+
+/// Controls how the model responds to function calls (deprecated in favor of `ToolChoice`)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FunctionCallChoice {
+    /// Either `none` or `auto`
+    Mode(ToolChoiceMode),
+    /// Forces the model to call a specific function
+    Named(FunctionName),
+}
+
+/// The name of a function
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionName {
+    /// The name of the function
+    pub name: String,
+}
+
+/// A tool call generated by the model
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// The ID of the tool call
+    pub id: String,
+
+    /// The type of the tool
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+
+    /// The function the model called
+    pub function: FunctionCall,
+}
+
+/// A function call generated by the model
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// The name of the function to call
+    pub name: String,
+
+    /// The arguments to call the function with, as generated by the model in JSON format.
+    /// The model does not always generate valid JSON, so arguments must be validated
+    pub arguments: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -152,7 +321,7 @@ pub struct ChatCompletionChoice {
     pub logprobs: Option<Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct CompletionUsage {
     /// Number of tokens in the prompt.
     pub prompt_tokens: i32,
@@ -164,6 +333,17 @@ pub struct CompletionUsage {
     pub total_tokens: i32,
 }
 
+impl From<Usage> for CompletionUsage {
+    fn from(usage: Usage) -> Self {
+        let completion_tokens = usage.completion_tokens.unwrap_or_default();
+        Self {
+            prompt_tokens: usage.prompt_tokens.try_into().unwrap_or(i32::MAX),
+            completion_tokens: completion_tokens.try_into().unwrap_or(i32::MAX),
+            total_tokens: usage.total_tokens.try_into().unwrap_or(i32::MAX),
+        }
+    }
+}
+
 // For streaming responses
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChatCompletionChunk {
@@ -212,6 +392,100 @@ pub struct ChatCompletionChunkDelta {
     pub tool_calls: Option<Vec<Value>>,
 }
 
+/// Request body for the embeddings endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateEmbeddingRequest {
+    /// ID of the embedding model to use.
+    pub model: String,
+
+    /// The text(s) to embed.
+    pub input: EmbeddingInput,
+
+    /// The number of dimensions the resulting embeddings should have, if the model supports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+
+    /// A unique identifier representing your end-user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// The input of an embeddings request, either a single text or a batch of texts
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    /// A single text
+    Single(String),
+    /// A batch of texts, embedded in order
+    Multiple(Vec<String>),
+}
+
+impl EmbeddingInput {
+    /// The texts to embed
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            Self::Single(text) => vec![text.as_str()],
+            Self::Multiple(texts) => texts.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Response body of the embeddings endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateEmbeddingResponse {
+    /// The object type, which is always "list".
+    pub object: String,
+
+    /// The model used to generate the embeddings.
+    pub model: String,
+
+    /// The embeddings, one per input text.
+    pub data: Vec<EmbeddingObject>,
+
+    /// Usage statistics for the embeddings request.
+    pub usage: Option<EmbeddingUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmbeddingObject {
+    /// The object type, which is always "embedding".
+    pub object: String,
+
+    /// The embedding vector.
+    pub embedding: Vec<f32>,
+
+    /// The index of the input text this embedding corresponds to.
+    pub index: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    /// Number of tokens in the input.
+    pub prompt_tokens: i32,
+
+    /// Total number of tokens used.
+    pub total_tokens: i32,
+}
+
+impl From<Usage> for EmbeddingUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens.try_into().unwrap_or(i32::MAX),
+            total_tokens: usage.total_tokens.try_into().unwrap_or(i32::MAX),
+        }
+    }
+}
+
+impl From<EmbeddingUsage> for CompletionUsage {
+    fn from(usage: EmbeddingUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: 0,
+            total_tokens: usage.total_tokens,
+        }
+    }
+}
+
 /// A request for confidential computation that includes encrypted data and associated cryptographic parameters
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ConfidentialComputeRequest {
@@ -245,6 +519,14 @@ pub struct ConfidentialComputeRequest {
     /// Number of compute units to be used for the request, for image generations,
     /// as this value is known in advance (the number of pixels to generate)
     pub num_compute_units: Option<u64>,
+
+    /// Version of the confidential compute envelope, omitted for the legacy version 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope_version: Option<u8>,
+
+    /// Cipher suite used to encrypt the payloads, omitted for the legacy version 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cipher_suite: Option<CipherSuite>,
 }
 
 /// Represents usage statistics for a confidential compute request
@@ -286,4 +568,8 @@ pub struct ConfidentialComputeResponse {
     /// Usage statistics for the request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<Usage>,
+
+    /// Version of the confidential compute envelope, if echoed by the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub envelope_version: Option<u8>,
 }
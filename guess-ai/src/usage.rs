@@ -0,0 +1,615 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info, instrument, warn};
+
+use crate::{config::ComputeBudgetConfig, persist::write_atomically, types::CompletionUsage};
+
+/// The number of tokens priced by `ModelPrice::mist_per_million_tokens`
+const TOKENS_PER_PRICE_UNIT: u128 = 1_000_000;
+
+/// The denominator used by all the per mille thresholds
+const PER_MILLE: u128 = 1_000;
+
+type Result<T> = std::result::Result<T, UsageError>;
+
+/// The purpose an inference request was issued for.
+///
+/// Usage is aggregated per purpose, so that operators can tell which part
+/// of the game loop is driving the compute bill.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InferencePurpose {
+    /// Generation of a new secret for a game
+    Secret,
+    /// Judging whether a guess matches the secret
+    Judge,
+    /// Generation of a new hint
+    Hint,
+    /// Embedding of a guess or secret, for similarity feedback
+    Similarity,
+}
+
+impl InferencePurpose {
+    /// Whether the model may call the engine's tools, e.g. to refer to the progress of the
+    /// game in a hint. The judge and the secret generation only get the prompt
+    pub fn uses_tools(&self) -> bool {
+        matches!(self, Self::Hint)
+    }
+}
+
+/// Aggregated token usage
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// The number of requests issued
+    pub requests: u64,
+
+    /// The total number of prompt tokens
+    pub prompt_tokens: u64,
+
+    /// The total number of completion tokens
+    pub completion_tokens: u64,
+
+    /// The total cost of the requests, in MIST
+    pub cost_mist: u64,
+}
+
+impl TokenUsage {
+    /// Adds a single request's usage to the totals
+    fn add(&mut self, prompt_tokens: u64, completion_tokens: u64, cost_mist: u64) {
+        self.requests += 1;
+        self.prompt_tokens += prompt_tokens;
+        self.completion_tokens += completion_tokens;
+        self.cost_mist = self.cost_mist.saturating_add(cost_mist);
+    }
+
+    /// Merges the totals of `other` into `self`
+    fn merge(&mut self, other: &TokenUsage) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cost_mist = self.cost_mist.saturating_add(other.cost_mist);
+    }
+}
+
+/// Usage statistics for a single guess game
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameUsage {
+    /// The usage of the game, split by inference purpose
+    pub by_purpose: BTreeMap<InferencePurpose, TokenUsage>,
+
+    /// The latest treasury pool balance observed for the game, in MIST
+    pub treasury_revenue: u64,
+}
+
+impl GameUsage {
+    /// The total cost of all the inference requests of the game, in MIST
+    pub fn cost_mist(&self) -> u64 {
+        self.by_purpose
+            .values()
+            .fold(0u64, |acc, usage| acc.saturating_add(usage.cost_mist))
+    }
+
+    /// The compute cost of the game relative to its treasury revenue, in per mille.
+    ///
+    /// Returns `None` if the game has no revenue yet.
+    pub fn cost_per_mille_of_revenue(&self) -> Option<u64> {
+        if self.treasury_revenue == 0 {
+            return None;
+        }
+        let ratio = self.cost_mist() as u128 * PER_MILLE / self.treasury_revenue as u128;
+        Some(ratio.min(u64::MAX as u128) as u64)
+    }
+}
+
+/// The persisted usage totals
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Usage per guess game id
+    pub games: BTreeMap<u64, GameUsage>,
+
+    /// Usage that could not be attributed to a game yet, e.g. a secret
+    /// generated at startup before any game event was observed
+    pub unassigned: BTreeMap<InferencePurpose, TokenUsage>,
+
+    /// Usage across all games, per purpose
+    pub overall: BTreeMap<InferencePurpose, TokenUsage>,
+}
+
+/// The decision taken by the compute budget for the next inference requests
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetDecision {
+    /// The model to use for the next requests
+    pub model: String,
+
+    /// Whether hints should still be issued
+    pub allow_hints: bool,
+}
+
+/// Tracks the inference usage of the engine, per request, per game and per purpose.
+///
+/// Totals are persisted to `usage_path` (if set) after every recorded request,
+/// so that they survive restarts. A failure to persist them is logged rather than returned:
+/// the request already ran and was paid for, and its usage is written with the next one.
+pub struct UsageTracker {
+    /// The compute budget configuration
+    budget: ComputeBudgetConfig,
+
+    /// The game id to which new usage is attributed
+    current_game_id: Option<u64>,
+
+    /// Optional file path where the totals are persisted
+    usage_path: Option<String>,
+
+    /// The usage totals
+    totals: UsageTotals,
+
+    /// The latest budget decision, so that a change of budget state is only reported once
+    last_decision: Option<BudgetDecision>,
+}
+
+impl UsageTracker {
+    /// Constructor
+    ///
+    /// Loads previously persisted totals from `usage_path`, if the file exists.
+    pub fn new(budget: ComputeBudgetConfig, usage_path: Option<String>) -> Result<Self> {
+        let totals = match &usage_path {
+            Some(path) => read_totals_from_file(path)?,
+            None => UsageTotals::default(),
+        };
+        Ok(Self {
+            budget,
+            current_game_id: None,
+            usage_path,
+            totals,
+            last_decision: None,
+        })
+    }
+
+    /// The compute budget configuration
+    pub fn budget(&self) -> &ComputeBudgetConfig {
+        &self.budget
+    }
+
+    /// The usage totals
+    pub fn totals(&self) -> &UsageTotals {
+        &self.totals
+    }
+
+    /// The game id to which new usage is attributed
+    pub fn current_game_id(&self) -> Option<u64> {
+        self.current_game_id
+    }
+
+    /// Sets the game to which new usage is attributed.
+    ///
+    /// Any usage that was recorded before a game was known is attributed to this game.
+    pub fn set_current_game(&mut self, guess_game_id: u64) {
+        if self.current_game_id == Some(guess_game_id) {
+            return;
+        }
+        self.current_game_id = Some(guess_game_id);
+        if self.totals.unassigned.is_empty() {
+            return;
+        }
+        let unassigned = std::mem::take(&mut self.totals.unassigned);
+        let game = self.totals.games.entry(guess_game_id).or_default();
+        for (purpose, usage) in unassigned {
+            game.by_purpose.entry(purpose).or_default().merge(&usage);
+        }
+        self.persist();
+    }
+
+    /// Stops attributing new usage to the current game, e.g. once it has been won.
+    ///
+    /// Usage recorded afterwards is attributed to the next game set via `set_current_game`.
+    pub fn clear_current_game(&mut self) {
+        self.current_game_id = None;
+    }
+
+    /// Updates the treasury revenue of a game, as observed on-chain
+    pub fn set_treasury_revenue(&mut self, guess_game_id: u64, treasury_pool_balance: u64) {
+        self.totals
+            .games
+            .entry(guess_game_id)
+            .or_default()
+            .treasury_revenue = treasury_pool_balance;
+    }
+
+    /// Records the usage of a single inference request for the current game.
+    ///
+    /// # Arguments
+    ///
+    /// * `purpose` - The purpose the request was issued for
+    /// * `model` - The model that served the request
+    /// * `usage` - The usage reported by the node, if any
+    #[instrument(level = "info", skip_all, fields(purpose = ?purpose, model = %model))]
+    pub fn record(
+        &mut self,
+        purpose: InferencePurpose,
+        model: &str,
+        usage: Option<&CompletionUsage>,
+    ) {
+        let Some(usage) = usage else {
+            warn!(
+                target = "usage_tracker",
+                event = "missing-usage",
+                "Inference response did not report usage, the request is not accounted for"
+            );
+            return;
+        };
+        let prompt_tokens = usage.prompt_tokens.max(0) as u64;
+        let completion_tokens = usage.completion_tokens.max(0) as u64;
+        let cost_mist = self.cost_mist(model, prompt_tokens + completion_tokens);
+
+        let purposes = match self.current_game_id {
+            Some(guess_game_id) => {
+                &mut self
+                    .totals
+                    .games
+                    .entry(guess_game_id)
+                    .or_default()
+                    .by_purpose
+            }
+            None => &mut self.totals.unassigned,
+        };
+        purposes
+            .entry(purpose)
+            .or_default()
+            .add(prompt_tokens, completion_tokens, cost_mist);
+        self.totals.overall.entry(purpose).or_default().add(
+            prompt_tokens,
+            completion_tokens,
+            cost_mist,
+        );
+
+        info!(
+            target = "usage_tracker",
+            event = "inference-usage",
+            guess_game_id = ?self.current_game_id,
+            prompt_tokens,
+            completion_tokens,
+            cost_mist,
+            "Recorded inference usage"
+        );
+        self.persist();
+    }
+
+    /// Decides which model to use, and whether hints should still be issued,
+    /// based on the compute cost of the current game relative to its treasury revenue.
+    ///
+    /// A change of decision is logged, once: a warning when the game goes over budget, and
+    /// an info when it is back within budget, e.g. once a new game starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `default_model` - The model to use while the game is within budget
+    pub fn decide(&mut self, default_model: &str) -> BudgetDecision {
+        let ratio = self
+            .current_game_id
+            .and_then(|guess_game_id| self.totals.games.get(&guess_game_id))
+            .and_then(GameUsage::cost_per_mille_of_revenue);
+        let decision = match ratio {
+            Some(ratio) => BudgetDecision {
+                model: match &self.budget.fallback_model {
+                    Some(fallback_model) if ratio >= self.budget.fallback_threshold_per_mille => {
+                        fallback_model.clone()
+                    }
+                    _ => default_model.to_string(),
+                },
+                allow_hints: ratio < self.budget.stop_hints_threshold_per_mille,
+            },
+            None => BudgetDecision {
+                model: default_model.to_string(),
+                allow_hints: true,
+            },
+        };
+        if self.last_decision.as_ref() == Some(&decision) {
+            return decision;
+        }
+        let over_budget = decision.model != default_model || !decision.allow_hints;
+        if over_budget {
+            warn!(
+                target = "usage_tracker",
+                event = "compute-budget-exceeded",
+                guess_game_id = ?self.current_game_id,
+                cost_per_mille_of_revenue = ?ratio,
+                model = %decision.model,
+                allow_hints = decision.allow_hints,
+                "Compute cost of the game is nearing its treasury revenue"
+            );
+        } else if self.last_decision.is_some() {
+            info!(
+                target = "usage_tracker",
+                event = "compute-budget-restored",
+                guess_game_id = ?self.current_game_id,
+                model = %decision.model,
+                "Compute cost of the game is within budget"
+            );
+        }
+        self.last_decision = Some(decision.clone());
+        decision
+    }
+
+    /// Computes the cost, in MIST, of `tokens` tokens served by `model`
+    fn cost_mist(&self, model: &str, tokens: u64) -> u64 {
+        let Some(price) = self
+            .budget
+            .model_prices
+            .iter()
+            .find(|price| price.model == model)
+        else {
+            warn!(
+                target = "usage_tracker",
+                event = "missing-model-price",
+                model = %model,
+                "No price configured for model, its usage is accounted at zero cost"
+            );
+            return 0;
+        };
+        let cost = tokens as u128 * price.mist_per_million_tokens as u128 / TOKENS_PER_PRICE_UNIT;
+        cost.min(u64::MAX as u128) as u64
+    }
+
+    /// Writes the totals to `usage_path` atomically, if set, logging any failure
+    fn persist(&self) {
+        let Some(path) = &self.usage_path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(&self.totals)
+            .map_err(UsageError::from)
+            .and_then(|totals| Ok(write_atomically(path, &totals)?));
+        if let Err(e) = result {
+            error!(
+                target = "usage_tracker",
+                event = "usage-persist-error",
+                path = %path,
+                "Failed to persist the usage totals: {e}"
+            );
+        }
+    }
+}
+
+/// Reads the usage totals from a JSON file.
+///
+/// Returns empty totals if the file doesn't exist.
+fn read_totals_from_file(path: &str) -> Result<UsageTotals> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(UsageTotals::default()),
+        Err(e) => return Err(UsageError::UsageFileError(e)),
+    };
+    Ok(serde_json::from_str(&content)?)
+}
+
+#[derive(Debug, Error)]
+pub enum UsageError {
+    #[error("Failed to read/write usage file: {0}")]
+    UsageFileError(#[from] std::io::Error),
+    #[error("Failed to (de)serialize usage totals: {0}")]
+    SerializeUsageError(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelPrice;
+
+    const MODEL: &str = "large";
+    const FALLBACK_MODEL: &str = "small";
+
+    fn budget() -> ComputeBudgetConfig {
+        ComputeBudgetConfig {
+            model_prices: vec![ModelPrice {
+                model: MODEL.to_string(),
+                mist_per_million_tokens: 2_000_000,
+            }],
+            fallback_model: Some(FALLBACK_MODEL.to_string()),
+            ..ComputeBudgetConfig::default()
+        }
+    }
+
+    fn usage(prompt_tokens: i32, completion_tokens: i32) -> CompletionUsage {
+        CompletionUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    fn game_usage(cost_mist: u64, treasury_revenue: u64) -> GameUsage {
+        GameUsage {
+            by_purpose: BTreeMap::from([(
+                InferencePurpose::Judge,
+                TokenUsage {
+                    requests: 1,
+                    cost_mist,
+                    ..TokenUsage::default()
+                },
+            )]),
+            treasury_revenue,
+        }
+    }
+
+    /// A tracker whose current game cost `cost_mist` for `treasury_revenue`
+    fn tracker_with_game(cost_mist: u64, treasury_revenue: u64) -> UsageTracker {
+        let mut tracker = UsageTracker::new(budget(), None).unwrap();
+        tracker
+            .totals
+            .games
+            .insert(1, game_usage(cost_mist, treasury_revenue));
+        tracker.set_current_game(1);
+        tracker
+    }
+
+    #[test]
+    fn cost_mist_prices_the_tokens_of_the_model() {
+        let tracker = UsageTracker::new(budget(), None).unwrap();
+        assert_eq!(tracker.cost_mist(MODEL, 1_500), 3_000);
+        // Rounded down to the MIST
+        assert_eq!(tracker.cost_mist(MODEL, 1), 2);
+        assert_eq!(tracker.cost_mist(MODEL, 0), 0);
+    }
+
+    #[test]
+    fn cost_mist_of_an_unpriced_model_is_zero() {
+        let tracker = UsageTracker::new(budget(), None).unwrap();
+        assert_eq!(tracker.cost_mist(FALLBACK_MODEL, 1_000_000), 0);
+    }
+
+    #[test]
+    fn game_cost_sums_the_purposes() {
+        let mut game = game_usage(100, 0);
+        game.by_purpose.insert(
+            InferencePurpose::Hint,
+            TokenUsage {
+                cost_mist: u64::MAX,
+                ..TokenUsage::default()
+            },
+        );
+        assert_eq!(game.cost_mist(), u64::MAX);
+        assert_eq!(game_usage(100, 0).cost_mist(), 100);
+    }
+
+    #[test]
+    fn cost_per_mille_of_revenue() {
+        assert_eq!(game_usage(100, 0).cost_per_mille_of_revenue(), None);
+        assert_eq!(game_usage(0, 1_000).cost_per_mille_of_revenue(), Some(0));
+        assert_eq!(
+            game_usage(250, 1_000).cost_per_mille_of_revenue(),
+            Some(250)
+        );
+        assert_eq!(
+            game_usage(3_000, 1_000).cost_per_mille_of_revenue(),
+            Some(3_000)
+        );
+        assert_eq!(
+            game_usage(u64::MAX, 1).cost_per_mille_of_revenue(),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn decide_keeps_the_default_model_within_budget() {
+        let default = BudgetDecision {
+            model: MODEL.to_string(),
+            allow_hints: true,
+        };
+        // No game, or a game without revenue yet
+        assert_eq!(
+            UsageTracker::new(budget(), None).unwrap().decide(MODEL),
+            default
+        );
+        assert_eq!(tracker_with_game(100, 0).decide(MODEL), default);
+        assert_eq!(tracker_with_game(499, 1_000).decide(MODEL), default);
+    }
+
+    #[test]
+    fn decide_falls_back_then_stops_hints() {
+        assert_eq!(
+            tracker_with_game(500, 1_000).decide(MODEL),
+            BudgetDecision {
+                model: FALLBACK_MODEL.to_string(),
+                allow_hints: true,
+            }
+        );
+        assert_eq!(
+            tracker_with_game(800, 1_000).decide(MODEL),
+            BudgetDecision {
+                model: FALLBACK_MODEL.to_string(),
+                allow_hints: false,
+            }
+        );
+    }
+
+    #[test]
+    fn decide_without_a_fallback_model_only_stops_hints() {
+        let mut tracker = tracker_with_game(900, 1_000);
+        tracker.budget.fallback_model = None;
+        assert_eq!(
+            tracker.decide(MODEL),
+            BudgetDecision {
+                model: MODEL.to_string(),
+                allow_hints: false,
+            }
+        );
+    }
+
+    #[test]
+    fn decide_remembers_the_latest_decision() {
+        let mut tracker = tracker_with_game(600, 1_000);
+        let decision = tracker.decide(MODEL);
+        assert_eq!(tracker.last_decision.as_ref(), Some(&decision));
+        assert_eq!(tracker.decide(MODEL), decision);
+
+        // A new game is back within budget
+        tracker.set_current_game(2);
+        let decision = tracker.decide(MODEL);
+        assert_eq!(decision.model, MODEL);
+        assert_eq!(tracker.last_decision, Some(decision));
+    }
+
+    #[test]
+    fn set_current_game_merges_the_unassigned_usage() {
+        let mut tracker = UsageTracker::new(budget(), None).unwrap();
+        tracker.record(InferencePurpose::Secret, MODEL, Some(&usage(100, 400)));
+        assert_eq!(
+            tracker.totals.unassigned[&InferencePurpose::Secret].cost_mist,
+            1_000
+        );
+
+        tracker.set_current_game(1);
+        assert!(tracker.totals.unassigned.is_empty());
+        tracker.record(InferencePurpose::Secret, MODEL, Some(&usage(50, 0)));
+        let secret = &tracker.totals.games[&1].by_purpose[&InferencePurpose::Secret];
+        assert_eq!(secret.requests, 2);
+        assert_eq!(secret.prompt_tokens, 150);
+        assert_eq!(secret.completion_tokens, 400);
+        assert_eq!(secret.cost_mist, 1_100);
+        assert_eq!(
+            tracker.totals.overall[&InferencePurpose::Secret].requests,
+            2
+        );
+    }
+
+    #[test]
+    fn set_current_game_merges_into_existing_usage() {
+        let mut tracker = tracker_with_game(100, 0);
+        tracker.clear_current_game();
+        tracker.record(InferencePurpose::Judge, MODEL, Some(&usage(0, 50)));
+        tracker.set_current_game(1);
+        let judge = &tracker.totals.games[&1].by_purpose[&InferencePurpose::Judge];
+        assert_eq!(judge.requests, 2);
+        assert_eq!(judge.cost_mist, 200);
+    }
+
+    #[test]
+    fn totals_are_persisted_and_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json").to_str().unwrap().to_string();
+        let mut tracker = UsageTracker::new(budget(), Some(path.clone())).unwrap();
+        tracker.set_current_game(1);
+        tracker.record(InferencePurpose::Judge, MODEL, Some(&usage(10, 20)));
+
+        let restored = UsageTracker::new(budget(), Some(path)).unwrap();
+        assert_eq!(
+            restored.totals().games[&1].by_purpose[&InferencePurpose::Judge].completion_tokens,
+            20
+        );
+    }
+
+    #[test]
+    fn persist_failure_does_not_lose_the_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("usage.json");
+        let mut tracker =
+            UsageTracker::new(budget(), Some(path.to_str().unwrap().to_string())).unwrap();
+        tracker.record(InferencePurpose::Judge, MODEL, Some(&usage(10, 20)));
+        assert_eq!(
+            tracker.totals().overall[&InferencePurpose::Judge].requests,
+            1
+        );
+        assert!(!path.exists());
+    }
+}